@@ -0,0 +1,10 @@
+-- Exit reasons: allow strategy and circuit breaker exits
+-- Migration 006: The original CHECK constraint only listed the four price/manual exits,
+-- so positions closed by a sell signal or a circuit breaker flatten could not be saved.
+
+ALTER TABLE positions
+DROP CONSTRAINT IF EXISTS positions_exit_reason_check;
+
+ALTER TABLE positions
+ADD CONSTRAINT positions_exit_reason_check
+CHECK (exit_reason IN ('StopLoss', 'TakeProfit', 'TimeStop', 'Manual', 'StrategySell', 'CircuitBreaker'));
@@ -0,0 +1,34 @@
+-- Backtest run registry
+-- Migration 007: Store every backtest run (config, data range, git revision and full
+-- metrics) so parameter changes can be compared over time. Headline metrics are
+-- duplicated into columns for listing and sorting; everything else lives in JSONB.
+
+CREATE TABLE IF NOT EXISTS backtest_runs (
+    id UUID PRIMARY KEY,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    name TEXT NOT NULL,
+    strategy VARCHAR(100) NOT NULL,
+    tokens TEXT[] NOT NULL,
+    config JSONB NOT NULL,
+
+    -- Data range replayed
+    data_start TIMESTAMPTZ NOT NULL,
+    data_end TIMESTAMPTZ NOT NULL,
+
+    git_revision VARCHAR(64),
+
+    -- Headline metrics
+    total_return_pct DOUBLE PRECISION NOT NULL,
+    total_trades INTEGER NOT NULL,
+    win_rate DOUBLE PRECISION NOT NULL,
+    max_drawdown_pct DOUBLE PRECISION NOT NULL,
+    sharpe_ratio DOUBLE PRECISION NOT NULL,
+
+    -- Complete BacktestMetrics (trades, equity curve, breaker events)
+    metrics JSONB NOT NULL,
+
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_backtest_runs_user_created ON backtest_runs(user_id, created_at DESC);
+CREATE INDEX idx_backtest_runs_strategy ON backtest_runs(user_id, strategy);
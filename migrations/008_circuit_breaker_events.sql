@@ -0,0 +1,24 @@
+-- Circuit breaker audit trail
+-- Migration 008: Record every circuit breaker trip of the live bot with the
+-- portfolio state that caused it and the entry pause it started, if any.
+
+CREATE TABLE IF NOT EXISTS circuit_breaker_events (
+    id UUID PRIMARY KEY,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    trip VARCHAR(32) NOT NULL,
+    occurred_at TIMESTAMPTZ NOT NULL,
+
+    -- Trading state at the time of the trip
+    portfolio_value DOUBLE PRECISION NOT NULL,
+    peak_portfolio_value DOUBLE PRECISION NOT NULL,
+    daily_pnl DOUBLE PRECISION NOT NULL,
+    consecutive_losses INTEGER NOT NULL,
+    daily_trades INTEGER NOT NULL,
+
+    -- New entries blocked until (NULL if the trip didn't pause)
+    paused_until TIMESTAMPTZ,
+
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_circuit_breaker_events_user_time ON circuit_breaker_events(user_id, occurred_at DESC);
@@ -1,14 +1,18 @@
+use crate::models::{Candle, CandleProvenance};
 use crate::Result;
+use chrono::{DateTime, Utc};
 use reqwest::Client;
 use serde::Deserialize;
 
 const BIRDEYE_API_BASE: &str = "https://public-api.birdeye.so";
+const MAX_OHLCV_ITEMS: usize = 1000;
+const OHLCV_INTERVAL_SECS: i64 = 300;
 
 /// Client for Birdeye API (Solana data)
 ///
 /// **FREE TIER LIMITATIONS:**
 /// - Only `/defi/price` and `/defi/token_trending` are available
-/// - OHLCV, security, trades, price_volume require premium
+/// - OHLCV (`get_ohlcv`), security, trades, price_volume require premium
 #[derive(Clone)]
 pub struct BirdeyeClient {
     client: Client,
@@ -58,6 +62,23 @@ struct TrendingTokenRaw {
     price_24h_change_percent: Option<f64>,
 }
 
+#[derive(Debug, Deserialize)]
+struct OhlcvData {
+    items: Vec<OhlcvItem>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct OhlcvItem {
+    unix_time: i64,
+    o: f64,
+    h: f64,
+    l: f64,
+    c: f64,
+    /// Volume in token units
+    v: f64,
+}
+
 // ============== Public Types ==============
 
 #[derive(Debug, Clone)]
@@ -193,13 +214,96 @@ impl BirdeyeClient {
 
         Ok(tokens)
     }
+
+    /// 5-minute OHLCV candles between `from` and `to`
+    /// Endpoint: GET /defi/ohlcv?address={address}&type=5m&time_from={from}&time_to={to}
+    ///
+    /// **FREE TIER:** ❌ Premium only (the API answers 401/403 without it)
+    ///
+    /// Pages forward from `from` (1000 candles per request). Birdeye reports
+    /// volume in token units; it is converted to USD at the candle's close.
+    pub async fn get_ohlcv(
+        &self,
+        symbol: &str,
+        address: &str,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<Candle>> {
+        let mut candles: Vec<Candle> = Vec::new();
+        let mut time_from = from.timestamp();
+
+        while time_from <= to.timestamp() {
+            let url = format!(
+                "{}/defi/ohlcv?address={}&type=5m&time_from={}&time_to={}",
+                BIRDEYE_API_BASE,
+                address,
+                time_from,
+                to.timestamp()
+            );
+
+            let response = self
+                .client
+                .get(&url)
+                .header("X-API-KEY", &self.api_key)
+                .header("x-chain", "solana")
+                .send()
+                .await?;
+
+            if !response.status().is_success() {
+                let status = response.status();
+                let body = response.text().await?;
+                return Err(format!("Birdeye API error: {} {}", status, body).into());
+            }
+
+            let page = parse_ohlcv(symbol, &response.text().await?)?;
+            let Some(newest) = page.iter().map(|c| c.timestamp.timestamp()).max() else {
+                break;
+            };
+            let full_page = page.len() >= MAX_OHLCV_ITEMS;
+            candles.extend(page);
+            if !full_page {
+                break;
+            }
+            time_from = newest + OHLCV_INTERVAL_SECS;
+        }
+
+        candles.sort_by_key(|c| c.timestamp);
+        candles.dedup_by_key(|c| c.timestamp);
+        Ok(candles)
+    }
+}
+
+/// Decode an OHLCV response body into candles with USD volume
+fn parse_ohlcv(symbol: &str, body: &str) -> Result<Vec<Candle>> {
+    let data: BirdeyeResponse<OhlcvData> = serde_json::from_str(body)?;
+    if !data.success {
+        return Err("Birdeye API returned success=false".into());
+    }
+
+    Ok(data
+        .data
+        .items
+        .into_iter()
+        .filter_map(|item| {
+            Some(Candle {
+                token: symbol.to_string(),
+                timestamp: DateTime::from_timestamp(item.unix_time, 0)?,
+                open: item.o,
+                high: item.h,
+                low: item.l,
+                close: item.c,
+                volume: item.v * item.c,
+                provenance: CandleProvenance::Backfilled,
+            })
+        })
+        .collect())
 }
 
 // ============== PREMIUM TIER ENDPOINTS (commented out) ==============
 //
 // The following endpoints require a premium Birdeye subscription:
 //
-// ❌ get_ohlcv() - Real OHLCV candles
+// ✅ get_ohlcv() - Real OHLCV candles (implemented above, used by backfill)
 // ❌ get_price_volume() - Price + volume combined
 // ❌ get_security() - Security checks (mint auth, freeze, etc.)
 // ❌ get_trades() - Recent trades for buy/sell flow
@@ -214,6 +318,25 @@ impl BirdeyeClient {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_parse_ohlcv_converts_volume_to_usd() {
+        let body = r#"{
+            "success": true,
+            "data": {
+                "items": [
+                    {"address": "So1", "type": "5m", "unixTime": 1717200000,
+                     "o": 150.0, "h": 151.0, "l": 149.0, "c": 150.0, "v": 20.0}
+                ]
+            }
+        }"#;
+
+        let candles = parse_ohlcv("SOL", body).unwrap();
+        assert_eq!(candles.len(), 1);
+        assert_eq!(candles[0].timestamp.timestamp(), 1717200000);
+        assert_eq!(candles[0].high, 151.0);
+        assert_eq!(candles[0].volume, 3000.0);
+    }
+
     fn get_test_client() -> BirdeyeClient {
         let api_key = std::env::var("BIRDEYE_API_KEY").unwrap_or_else(|_| "test_key".to_string());
         BirdeyeClient::new(api_key)
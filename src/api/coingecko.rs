@@ -265,6 +265,35 @@ impl CoinGeckoClient {
 
         Ok(data)
     }
+
+    /// Fetch market chart data between two unix timestamps (seconds)
+    ///
+    /// Granularity is picked by CoinGecko from the range length and plan:
+    /// windows of a day or less give the finest data available.
+    pub async fn get_market_chart_range(
+        &self,
+        coin_id: &str,
+        from: i64,
+        to: i64,
+    ) -> Result<MarketChartData> {
+        let url = format!(
+            "{}/coins/{}/market_chart/range?vs_currency=usd&from={}&to={}&x_cg_demo_api_key={}",
+            COINGECKO_API_BASE, coin_id, from, to, self.api_key
+        );
+
+        tracing::debug!("Fetching market chart for {} ({}..{})", coin_id, from, to);
+
+        let response = self.make_request(&url).await?;
+
+        let data: MarketChartData = response
+            .json()
+            .await
+            .context("Failed to parse market chart")?;
+
+        tracing::debug!("Fetched {} price points for {}", data.prices.len(), coin_id);
+
+        Ok(data)
+    }
 }
 
 #[cfg(test)]
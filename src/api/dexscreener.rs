@@ -41,6 +41,8 @@ struct TokenInfo {
 #[derive(Debug, Deserialize)]
 struct VolumeData {
     h24: f64,
+    #[serde(default)]
+    m5: f64,
 }
 
 #[derive(Debug, Deserialize, Default)]
@@ -115,6 +117,7 @@ impl DexScreenerClient {
             token: pair.base_token.symbol,
             price: pair.price_usd.parse()?,
             volume_24h: pair.volume.h24,
+            volume_5m: pair.volume.m5,
             timestamp: Utc::now(),
             source: DataSource::DexScreener,
         })
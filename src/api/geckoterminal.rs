@@ -0,0 +1,246 @@
+use crate::models::{Candle, CandleProvenance};
+use crate::Result;
+use chrono::{DateTime, Utc};
+use governor::{Quota, RateLimiter};
+use reqwest::Client;
+use serde::Deserialize;
+use std::num::NonZeroU32;
+use std::sync::Arc;
+
+const GECKOTERMINAL_API_BASE: &str = "https://api.geckoterminal.com/api/v2";
+const RATE_LIMIT_RPM: u32 = 30; // Free API: 30 requests per minute
+const MAX_CANDLES_PER_REQUEST: usize = 1000;
+const CANDLE_MINUTES: i64 = 5;
+
+type GeckoTerminalRateLimiter = RateLimiter<
+    governor::state::direct::NotKeyed,
+    governor::state::InMemoryState,
+    governor::clock::DefaultClock,
+>;
+
+/// Client for GeckoTerminal's free DEX API (pool OHLCV with USD volume)
+///
+/// No API key needed. Candles come from the token's most liquid Solana pool,
+/// so they include real traded volume (unlike CoinGecko's market chart).
+#[derive(Clone)]
+pub struct GeckoTerminalClient {
+    client: Client,
+    rate_limiter: Arc<GeckoTerminalRateLimiter>,
+}
+
+// ============== Response Types ==============
+
+#[derive(Debug, Deserialize)]
+struct PoolsResponse {
+    data: Vec<PoolData>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PoolData {
+    attributes: PoolAttributes,
+}
+
+#[derive(Debug, Deserialize)]
+struct PoolAttributes {
+    address: String,
+    #[serde(default)]
+    reserve_in_usd: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OhlcvResponse {
+    data: OhlcvData,
+}
+
+#[derive(Debug, Deserialize)]
+struct OhlcvData {
+    attributes: OhlcvAttributes,
+}
+
+#[derive(Debug, Deserialize)]
+struct OhlcvAttributes {
+    /// `[unix_seconds, open, high, low, close, volume_usd]`, newest first
+    ohlcv_list: Vec<[f64; 6]>,
+}
+
+// ============== Implementation ==============
+
+impl GeckoTerminalClient {
+    pub fn new() -> Self {
+        let quota = Quota::per_minute(NonZeroU32::new(RATE_LIMIT_RPM).unwrap());
+        Self {
+            client: Client::builder()
+                .timeout(std::time::Duration::from_secs(30))
+                .build()
+                .unwrap_or_default(),
+            rate_limiter: Arc::new(RateLimiter::direct(quota)),
+        }
+    }
+
+    async fn get(&self, url: &str) -> Result<reqwest::Response> {
+        self.rate_limiter.until_ready().await;
+        let response = self
+            .client
+            .get(url)
+            .header("Accept", "application/json")
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(format!("GeckoTerminal API error: {} {}", status, body).into());
+        }
+        Ok(response)
+    }
+
+    /// Address of the token's most liquid Solana pool
+    /// Endpoint: GET /networks/solana/tokens/{address}/pools
+    pub async fn find_top_pool(&self, mint_address: &str) -> Result<String> {
+        let url = format!(
+            "{}/networks/solana/tokens/{}/pools?page=1",
+            GECKOTERMINAL_API_BASE, mint_address
+        );
+        let pools: PoolsResponse = self.get(&url).await?.json().await?;
+
+        pools
+            .data
+            .into_iter()
+            .max_by(|a, b| {
+                let reserve = |p: &PoolData| {
+                    p.attributes
+                        .reserve_in_usd
+                        .as_deref()
+                        .and_then(|r| r.parse::<f64>().ok())
+                        .unwrap_or(0.0)
+                };
+                reserve(a).total_cmp(&reserve(b))
+            })
+            .map(|pool| pool.attributes.address)
+            .ok_or_else(|| format!("No GeckoTerminal pools found for {}", mint_address).into())
+    }
+
+    /// 5-minute candles of `token`'s price in USD from `pool`, between `from` and `to`
+    /// Endpoint: GET /networks/solana/pools/{pool}/ohlcv/minute?aggregate=5
+    ///
+    /// Pages backwards from `to` (1000 candles per request). Buckets without
+    /// trades are missing from the response.
+    pub async fn get_ohlcv(
+        &self,
+        symbol: &str,
+        pool: &str,
+        token: &str,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<Candle>> {
+        let mut candles = Vec::new();
+        let mut before = to.timestamp() + CANDLE_MINUTES * 60;
+
+        while before > from.timestamp() {
+            let url = format!(
+                "{}/networks/solana/pools/{}/ohlcv/minute?aggregate={}&before_timestamp={}&limit={}&currency=usd&token={}",
+                GECKOTERMINAL_API_BASE, pool, CANDLE_MINUTES, before, MAX_CANDLES_PER_REQUEST, token
+            );
+            let body = self.get(&url).await?.text().await?;
+            let page = parse_ohlcv(symbol, &body)?;
+            let Some(oldest) = page.iter().map(|c| c.timestamp.timestamp()).min() else {
+                break;
+            };
+
+            let full_page = page.len() >= MAX_CANDLES_PER_REQUEST;
+            candles.extend(page);
+            if !full_page || oldest >= before {
+                break;
+            }
+            before = oldest;
+        }
+
+        candles.retain(|c| c.timestamp >= from && c.timestamp <= to);
+        candles.sort_by_key(|c| c.timestamp);
+        candles.dedup_by_key(|c| c.timestamp);
+        tracing::debug!(
+            "Fetched {} GeckoTerminal candles for {} ({})",
+            candles.len(),
+            symbol,
+            pool
+        );
+        Ok(candles)
+    }
+}
+
+impl Default for GeckoTerminalClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Decode an OHLCV response body into candles (unsorted)
+fn parse_ohlcv(symbol: &str, body: &str) -> Result<Vec<Candle>> {
+    let response: OhlcvResponse = serde_json::from_str(body)?;
+    Ok(response
+        .data
+        .attributes
+        .ohlcv_list
+        .into_iter()
+        .filter_map(|[timestamp, open, high, low, close, volume]| {
+            Some(Candle {
+                token: symbol.to_string(),
+                timestamp: DateTime::from_timestamp(timestamp as i64, 0)?,
+                open,
+                high,
+                low,
+                close,
+                volume,
+                provenance: CandleProvenance::Backfilled,
+            })
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_ohlcv() {
+        let body = r#"{
+            "data": {
+                "id": "abc",
+                "type": "ohlcv_request_response",
+                "attributes": {
+                    "ohlcv_list": [
+                        [1717200300, 150.5, 151.0, 150.1, 150.9, 12345.6],
+                        [1717200000, 150.0, 150.7, 149.8, 150.5, 9876.5]
+                    ]
+                }
+            },
+            "meta": {}
+        }"#;
+
+        let candles = parse_ohlcv("SOL", body).unwrap();
+        assert_eq!(candles.len(), 2);
+        assert_eq!(candles[1].timestamp.timestamp(), 1717200000);
+        assert_eq!(candles[1].open, 150.0);
+        assert_eq!(candles[0].close, 150.9);
+        assert_eq!(candles[0].volume, 12345.6);
+        assert!(candles.iter().all(|c| c.token == "SOL"));
+    }
+
+    #[tokio::test]
+    #[ignore] // Requires network
+    async fn test_get_ohlcv_live() {
+        let client = GeckoTerminalClient::new();
+        let sol_mint = "So11111111111111111111111111111111111111112";
+        let pool = client.find_top_pool(sol_mint).await.unwrap();
+
+        let to = Utc::now();
+        let from = to - chrono::Duration::hours(6);
+        let candles = client
+            .get_ohlcv("SOL", &pool, sol_mint, from, to)
+            .await
+            .unwrap();
+
+        assert!(candles.len() > 50);
+        assert!(candles.iter().any(|c| c.volume > 0.0));
+    }
+}
@@ -1,9 +1,11 @@
 pub mod birdeye;
 pub mod coingecko;
 pub mod dexscreener;
+pub mod geckoterminal;
 pub mod jupiter;
 
 pub use birdeye::{BirdeyeClient, TrendingToken};
 pub use coingecko::{CoinGeckoClient, MarketChartData};
 pub use dexscreener::DexScreenerClient;
+pub use geckoterminal::GeckoTerminalClient;
 pub use jupiter::{JupiterClient, Quote};
@@ -0,0 +1,120 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Progress of a chunked backfill, saved after every completed window so an
+/// interrupted run can pick up where it stopped
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BackfillCheckpoint {
+    pub token: String,
+    /// Start of the range this run covers
+    pub from: DateTime<Utc>,
+    /// End of the requested range
+    pub to: DateTime<Utc>,
+    pub chunk_minutes: i64,
+    /// Everything in `from..completed_until` has been fetched and stored
+    pub completed_until: DateTime<Utc>,
+    /// Candles stored so far by this run
+    pub stored: usize,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl BackfillCheckpoint {
+    pub fn new(token: &str, from: DateTime<Utc>, to: DateTime<Utc>, chunk: Duration) -> Self {
+        Self {
+            token: token.to_string(),
+            from,
+            to,
+            chunk_minutes: chunk.num_minutes(),
+            completed_until: from,
+            stored: 0,
+            updated_at: Utc::now(),
+        }
+    }
+
+    /// Where a new run over `from..to` should start, given this earlier progress
+    ///
+    /// Resumes when the new range starts inside the part already done (a rerun
+    /// of `--days 90` starts a little later than the interrupted one); any other
+    /// range starts from scratch. Returns `to` if nothing is left to do.
+    pub fn resume_point(&self, from: DateTime<Utc>, to: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let overlaps_done = from >= self.from && from < self.completed_until;
+        overlaps_done.then(|| self.completed_until.min(to))
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.completed_until >= self.to
+    }
+
+    /// Record a finished window
+    pub fn advance(&mut self, until: DateTime<Utc>, stored: usize) {
+        self.completed_until = until;
+        self.stored += stored;
+        self.updated_at = Utc::now();
+    }
+}
+
+/// Consecutive windows of at most `chunk` covering `from..to`
+pub fn plan_chunks(
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+    chunk: Duration,
+) -> Vec<(DateTime<Utc>, DateTime<Utc>)> {
+    let mut chunks = Vec::new();
+    if chunk <= Duration::zero() {
+        return chunks;
+    }
+    let mut start = from;
+    while start < to {
+        let end = (start + chunk).min(to);
+        chunks.push((start, end));
+        start = end;
+    }
+    chunks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn day(d: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 6, d, 0, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn test_plan_chunks_covers_range() {
+        let chunks = plan_chunks(day(1), day(3) + Duration::hours(6), Duration::days(1));
+        assert_eq!(
+            chunks,
+            vec![
+                (day(1), day(2)),
+                (day(2), day(3)),
+                (day(3), day(3) + Duration::hours(6)),
+            ]
+        );
+        assert!(plan_chunks(day(2), day(1), Duration::days(1)).is_empty());
+        assert!(plan_chunks(day(1), day(2), Duration::zero()).is_empty());
+    }
+
+    #[test]
+    fn test_resume_point() {
+        let mut checkpoint = BackfillCheckpoint::new("SOL", day(1), day(20), Duration::days(1));
+        checkpoint.advance(day(8), 2016);
+
+        // Rerun of the same "--days" a few hours later
+        assert_eq!(
+            checkpoint.resume_point(day(1) + Duration::hours(3), day(20) + Duration::hours(3)),
+            Some(day(8))
+        );
+        // A range starting before the checkpoint, or after what was done
+        assert_eq!(
+            checkpoint.resume_point(day(1) - Duration::days(5), day(20)),
+            None
+        );
+        assert_eq!(checkpoint.resume_point(day(10), day(20)), None);
+        // Nothing left to do in the requested range
+        assert_eq!(checkpoint.resume_point(day(2), day(7)), Some(day(7)));
+        assert!(!checkpoint.is_complete());
+        assert_eq!(checkpoint.stored, 2016);
+    }
+}
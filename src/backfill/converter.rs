@@ -2,7 +2,7 @@ use chrono::DateTime;
 use std::collections::BTreeMap;
 
 use crate::api::MarketChartData;
-use crate::models::Candle;
+use crate::models::{Candle, CandleProvenance};
 use crate::Result;
 
 const BUCKET_INTERVAL_SECS: i64 = 300; // 5 minutes
@@ -96,11 +96,12 @@ impl CandleConverter {
         let mut fill_timestamp = from + self.interval_secs;
 
         while fill_timestamp < to {
-            let interpolated = self.synthesize_candle(
+            let mut interpolated = self.synthesize_candle(
                 symbol,
                 fill_timestamp,
                 vec![last_price], // Flat candle: O=H=L=C=last_price
             );
+            interpolated.provenance = CandleProvenance::Interpolated;
             filled.push(interpolated);
             fill_timestamp += self.interval_secs;
         }
@@ -152,7 +153,7 @@ impl CandleConverter {
             let timestamp_secs = timestamp_ms / 1000;
             let bucket = (timestamp_secs / self.interval_secs) * self.interval_secs;
 
-            buckets.entry(bucket).or_insert_with(Vec::new).push(price);
+            buckets.entry(bucket).or_default().push(price);
         }
 
         buckets
@@ -173,6 +174,7 @@ impl CandleConverter {
             low,
             close,
             volume: 0.0, // As per research findings: CoinGecko returns 24h rolling volume
+            provenance: CandleProvenance::Backfilled,
         }
     }
 }
@@ -297,6 +299,10 @@ mod tests {
         assert_eq!(candles[2].open, 101.0);
         assert_eq!(candles[2].high, 101.0);
         assert_eq!(candles[2].low, 101.0);
+
+        // Only the filled candle is flagged as synthesized
+        assert_eq!(candles[2].provenance, CandleProvenance::Interpolated);
+        assert_eq!(candles[1].provenance, CandleProvenance::Backfilled);
     }
 
     #[test]
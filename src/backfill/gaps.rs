@@ -0,0 +1,355 @@
+use super::{backfill_window, BackfillStats, CandleValidator, HistoricalProvider};
+use crate::models::{Candle, CandleProvenance};
+use crate::persistence::RedisPersistence;
+use crate::Result;
+use chrono::{DateTime, Duration, Utc};
+
+const CANDLE_INTERVAL_SECS: i64 = 300;
+
+/// Missing 5-minute slots between two stored candles
+#[derive(Debug, Clone, PartialEq)]
+pub struct Gap {
+    /// Last candle before the gap
+    pub after: DateTime<Utc>,
+    /// First candle after the gap
+    pub before: DateTime<Utc>,
+}
+
+impl Gap {
+    /// Timestamps that would close the gap, one interval apart from `after`
+    ///
+    /// The last slot stays at least half an interval before `before`, so the
+    /// filled history passes `validate_candle_uniformity`.
+    pub fn missing_slots(&self) -> Vec<DateTime<Utc>> {
+        let interval = Duration::seconds(CANDLE_INTERVAL_SECS);
+        let last = self.before - interval / 2;
+        let mut slots = Vec::new();
+        let mut slot = self.after + interval;
+        while slot < last {
+            slots.push(slot);
+            slot += interval;
+        }
+        slots
+    }
+}
+
+/// Gaps that `validate_candle_uniformity` would reject (more than 1.5
+/// intervals between consecutive candles); `candles` must be sorted
+pub fn find_gaps(candles: &[Candle]) -> Vec<Gap> {
+    let max_gap = CANDLE_INTERVAL_SECS + CANDLE_INTERVAL_SECS / 2;
+    candles
+        .windows(2)
+        .filter(|w| (w[1].timestamp - w[0].timestamp).num_seconds() > max_gap)
+        .map(|w| Gap {
+            after: w[0].timestamp,
+            before: w[1].timestamp,
+        })
+        .collect()
+}
+
+/// Flat, zero-volume candles on the straight line between the closes around a gap
+pub fn interpolate_gap(gap: &Gap, after: &Candle, before: &Candle) -> Vec<Candle> {
+    let span = (gap.before - gap.after).num_seconds() as f64;
+    gap.missing_slots()
+        .into_iter()
+        .map(|timestamp| {
+            let weight = (timestamp - gap.after).num_seconds() as f64 / span;
+            let price = after.close + (before.close - after.close) * weight;
+            Candle {
+                token: after.token.clone(),
+                timestamp,
+                open: price,
+                high: price,
+                low: price,
+                close: price,
+                volume: 0.0,
+                provenance: CandleProvenance::Interpolated,
+            }
+        })
+        .collect()
+}
+
+/// Gaps in a token's stored history over the last `hours`
+pub async fn scan_gaps(
+    persistence: &mut RedisPersistence,
+    token: &str,
+    hours: u64,
+) -> Result<Vec<Gap>> {
+    let candles = persistence.load_candles(token, hours).await?;
+    Ok(find_gaps(&candles))
+}
+
+/// Outcome of a gap repair
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GapRepairStats {
+    pub gaps_found: usize,
+    pub missing_slots: usize,
+    /// Candles stored from historical providers
+    pub filled_from_providers: usize,
+    /// Candles stored by interpolation
+    pub interpolated: usize,
+    /// Gaps providers could not be asked about (errors)
+    pub provider_failures: usize,
+    /// Gaps still open afterwards
+    pub remaining_gaps: usize,
+}
+
+/// Fill the gaps in a token's last `hours` of history
+///
+/// Each gap is fetched from `provider` when one is given; whatever is still
+/// missing afterwards is linearly interpolated only if `interpolate` is set.
+/// Provider candles go through `backfill_window`, so their volume is on the
+/// live series' per-interval scale (or 0.0 when the provider has none).
+pub async fn repair_gaps<P: HistoricalProvider>(
+    symbol: &str,
+    mint_address: &str,
+    hours: u64,
+    interpolate: bool,
+    provider: Option<&P>,
+    persistence: &mut RedisPersistence,
+) -> Result<GapRepairStats> {
+    let mut stats = GapRepairStats::default();
+    let gaps = scan_gaps(persistence, symbol, hours).await?;
+    stats.gaps_found = gaps.len();
+    stats.missing_slots = gaps.iter().map(|g| g.missing_slots().len()).sum();
+
+    for gap in gaps {
+        if let Some(provider) = provider {
+            let mut window = BackfillStats::default();
+            match backfill_window(
+                symbol,
+                mint_address,
+                (gap.after, gap.before),
+                false,
+                provider,
+                persistence,
+                &mut window,
+            )
+            .await
+            {
+                Ok(()) => stats.filled_from_providers += window.stored_new,
+                Err(e) => {
+                    tracing::warn!(
+                        "Could not fetch {} gap {} - {}: {}",
+                        symbol,
+                        gap.after,
+                        gap.before,
+                        e
+                    );
+                    stats.provider_failures += 1;
+                }
+            }
+        }
+
+        // What the provider left open (buckets without trades, or no provider)
+        let candles = persistence
+            .load_candles_between(symbol, Some(gap.after), Some(gap.before))
+            .await?;
+        let remaining = find_gaps(&candles);
+        if !interpolate {
+            stats.remaining_gaps += remaining.len();
+            continue;
+        }
+
+        let validator = CandleValidator::new();
+        let mut filled = Vec::new();
+        for gap in &remaining {
+            let around = (
+                candles.iter().find(|c| c.timestamp == gap.after),
+                candles.iter().find(|c| c.timestamp == gap.before),
+            );
+            if let (Some(after), Some(before)) = around {
+                filled.extend(
+                    interpolate_gap(gap, after, before)
+                        .into_iter()
+                        .filter(|c| validator.validate(c).is_ok()),
+                );
+            }
+        }
+        if !filled.is_empty() {
+            persistence.save_candles(symbol, &filled).await?;
+            stats.interpolated += filled.len();
+        }
+    }
+
+    if stats.gaps_found > 0 {
+        tracing::info!(
+            "{}: {} gaps ({} slots) - {} candles from providers, {} interpolated, {} gaps left",
+            symbol,
+            stats.gaps_found,
+            stats.missing_slots,
+            stats.filled_from_providers,
+            stats.interpolated,
+            stats.remaining_gaps
+        );
+    }
+
+    Ok(stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backfill::{interval_volume_candles, ProviderHistory, ProviderKind};
+    use crate::strategy::signals::{validate_candle_uniformity, SignalConfig};
+    use chrono::TimeZone;
+
+    fn candle(seconds: i64, close: f64) -> Candle {
+        Candle {
+            token: "SOL".to_string(),
+            timestamp: Utc.with_ymd_and_hms(2024, 6, 1, 0, 0, 0).unwrap()
+                + Duration::seconds(seconds),
+            open: close,
+            high: close,
+            low: close,
+            close,
+            volume: 10.0,
+            provenance: CandleProvenance::Live,
+        }
+    }
+
+    #[test]
+    fn test_find_gaps() {
+        // Live snapshots drift a few seconds; one tick missed, then three
+        let candles = vec![
+            candle(0, 1.0),
+            candle(303, 1.0),
+            candle(901, 1.0),
+            candle(1200, 1.0),
+            candle(2400, 1.0),
+        ];
+
+        let gaps = find_gaps(&candles);
+        assert_eq!(gaps.len(), 2);
+        assert_eq!(gaps[0].after, candles[1].timestamp);
+        assert_eq!(gaps[0].missing_slots().len(), 1);
+        assert_eq!(gaps[1].missing_slots().len(), 3);
+        assert!(find_gaps(&candles[..2]).is_empty());
+    }
+
+    #[test]
+    fn test_interpolated_gap_passes_uniformity_check() {
+        let after = candle(0, 10.0);
+        let before = candle(1300, 23.0);
+        let gap = &find_gaps(&[after.clone(), before.clone()])[0];
+
+        let filled = interpolate_gap(gap, &after, &before);
+        assert_eq!(filled.len(), 3);
+        assert_eq!(filled[0].close, 10.0 + 13.0 * 300.0 / 1300.0);
+        assert!(filled.iter().all(|c| c.volume == 0.0 && c.token == "SOL"));
+        assert!(filled
+            .iter()
+            .all(|c| c.provenance == CandleProvenance::Interpolated));
+
+        let mut history = vec![after];
+        history.extend(filled);
+        history.push(before);
+        assert!(validate_candle_uniformity(&history, 300).is_ok());
+        assert!(find_gaps(&history).is_empty());
+    }
+
+    /// Gap candles a provider would return, with `volume` as it reports it
+    fn provider_fill(provider: ProviderKind, gap: &Gap, volume: f64) -> Vec<Candle> {
+        interval_volume_candles(ProviderHistory {
+            provider,
+            fetched_points: gap.missing_slots().len(),
+            candles: gap
+                .missing_slots()
+                .into_iter()
+                .map(|timestamp| Candle {
+                    timestamp,
+                    volume,
+                    provenance: CandleProvenance::Backfilled,
+                    ..candle(0, 1.0)
+                })
+                .collect(),
+        })
+    }
+
+    #[test]
+    fn test_repaired_live_series_keeps_volume_scale() {
+        // Live ticks carry ~$10 per 5 minutes; four ticks were missed
+        let live: Vec<Candle> = (0..12)
+            .filter(|i| !(4..8).contains(i))
+            .map(|i| candle(i * 300, 1.0))
+            .collect();
+        let gap = find_gaps(&live)[0].clone();
+
+        // OHLCV bucket volume is already per-interval: no false spike
+        let mut repaired = live.clone();
+        repaired.extend(provider_fill(ProviderKind::GeckoTerminal, &gap, 12.0));
+        repaired.sort_by_key(|c| c.timestamp);
+        assert!(find_gaps(&repaired).is_empty());
+        let volumes: Vec<f64> = repaired.iter().map(|c| c.volume).collect();
+        let avg = volumes.iter().sum::<f64>() / volumes.len() as f64;
+        let max = volumes.iter().cloned().fold(f64::MIN, f64::max);
+        assert!(max / avg < SignalConfig::default().volume_threshold);
+
+        // A rolling 24h figure would be ~288x the live scale; it is stored as unknown
+        let filled = provider_fill(ProviderKind::CoinGecko, &gap, 10.0 * 288.0);
+        assert!(filled.iter().all(|c| c.volume == 0.0));
+    }
+
+    /// Provider that returns fixed candles inside the requested window
+    struct FixedProvider(ProviderKind, Vec<Candle>);
+
+    impl HistoricalProvider for FixedProvider {
+        async fn fetch_history(
+            &self,
+            _symbol: &str,
+            _mint_address: &str,
+            from: DateTime<Utc>,
+            to: DateTime<Utc>,
+        ) -> Result<ProviderHistory> {
+            let candles: Vec<Candle> = self
+                .1
+                .iter()
+                .filter(|c| c.timestamp >= from && c.timestamp <= to)
+                .cloned()
+                .collect();
+            Ok(ProviderHistory {
+                provider: self.0,
+                fetched_points: candles.len(),
+                candles,
+            })
+        }
+    }
+
+    #[tokio::test]
+    #[ignore] // Requires Redis running
+    async fn test_repair_gaps_keeps_live_volume_scale() {
+        let mut persistence = RedisPersistence::new("redis://127.0.0.1:6379")
+            .await
+            .expect("Failed to connect to Redis");
+        let token = "TEST_GAP_VOLUME";
+        let _ = persistence.cleanup_old(token, 0).await;
+
+        let start = Utc::now() - Duration::hours(1);
+        let at = |i: i64| Candle {
+            token: token.to_string(),
+            timestamp: start + Duration::seconds(i * CANDLE_INTERVAL_SECS),
+            ..candle(0, 1.0)
+        };
+        let live: Vec<Candle> = (0..12).filter(|i| !(4..8).contains(i)).map(at).collect();
+        persistence.save_candles(token, &live).await.unwrap();
+
+        let buckets = (4..8)
+            .map(|i| Candle {
+                volume: 12.0,
+                provenance: CandleProvenance::Backfilled,
+                ..at(i)
+            })
+            .collect();
+        let provider = FixedProvider(ProviderKind::GeckoTerminal, buckets);
+        let stats = repair_gaps(token, "mint", 2, false, Some(&provider), &mut persistence)
+            .await
+            .unwrap();
+        assert_eq!(stats.filled_from_providers, 4);
+
+        let repaired = persistence.load_candles(token, 2).await.unwrap();
+        assert_eq!(repaired.len(), 12);
+        assert!(repaired.iter().all(|c| (10.0..=12.0).contains(&c.volume)));
+
+        let _ = persistence.cleanup_old(token, 0).await;
+    }
+}
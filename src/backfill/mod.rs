@@ -1,61 +1,255 @@
+pub mod checkpoint;
 pub mod converter;
+pub mod gaps;
+pub mod providers;
 pub mod validator;
 
-use crate::api::CoinGeckoClient;
+use crate::models::Candle;
 use crate::persistence::RedisPersistence;
 use crate::Result;
+use chrono::{DateTime, Duration, Utc};
 
+pub use checkpoint::{plan_chunks, BackfillCheckpoint};
 pub use converter::CandleConverter;
+pub use gaps::{find_gaps, repair_gaps, scan_gaps, Gap, GapRepairStats};
+pub use providers::{HistoricalProvider, Provider, ProviderChain, ProviderHistory, ProviderKind};
 pub use validator::CandleValidator;
 
 /// Statistics from a backfill operation
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, Default, PartialEq)]
 pub struct BackfillStats {
+    /// Provider that supplied the candles (the first one, for chunked runs)
+    pub provider: Option<ProviderKind>,
     pub fetched_points: usize,
     pub converted_candles: usize,
     pub skipped_existing: usize,
     pub stored_new: usize,
     pub validation_failures: usize,
+    /// Windows fetched by this run
+    pub chunks: usize,
+    /// Where a chunked run picked up an interrupted one
+    pub resumed_from: Option<DateTime<Utc>>,
 }
 
 /// Backfill historical data for a token
-pub async fn backfill_token(
+///
+/// `provider` is usually a [`ProviderChain`], which falls back to the next
+/// provider when one fails or has no data.
+pub async fn backfill_token<P: HistoricalProvider>(
     symbol: &str,
     mint_address: &str,
     days: u32,
     force_overwrite: bool,
-    coingecko: &CoinGeckoClient,
+    provider: &P,
     persistence: &mut RedisPersistence,
 ) -> Result<BackfillStats> {
     tracing::info!("Backfilling {} days of data for {}", days, symbol);
 
-    let mut stats = BackfillStats {
-        fetched_points: 0,
-        converted_candles: 0,
-        skipped_existing: 0,
-        stored_new: 0,
-        validation_failures: 0,
+    let mut stats = BackfillStats::default();
+    let to = Utc::now();
+    let from = to - Duration::days(days as i64);
+    backfill_window(
+        symbol,
+        mint_address,
+        (from, to),
+        force_overwrite,
+        provider,
+        persistence,
+        &mut stats,
+    )
+    .await?;
+
+    if stats.stored_new > 0 {
+        tracing::info!(
+            "✓ Stored {} new candles for {} (skipped {}, failed validation {})",
+            stats.stored_new,
+            symbol,
+            stats.skipped_existing,
+            stats.validation_failures
+        );
+    } else {
+        tracing::info!("No new candles to store for {}", symbol);
+    }
+
+    Ok(stats)
+}
+
+/// A long-range backfill fetched in fixed windows, with progress saved in
+/// Redis after every window
+#[derive(Debug, Clone)]
+pub struct ChunkedBackfill {
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    /// Window size; one day keeps most providers at 5-minute resolution
+    pub chunk: Duration,
+    pub force_overwrite: bool,
+    /// Ignore any saved checkpoint and start again from `from`
+    pub restart: bool,
+}
+
+impl ChunkedBackfill {
+    pub fn new(from: DateTime<Utc>, to: DateTime<Utc>) -> Self {
+        Self {
+            from,
+            to,
+            chunk: Duration::days(1),
+            force_overwrite: false,
+            restart: false,
+        }
+    }
+
+    pub fn with_chunk(mut self, chunk: Duration) -> Self {
+        self.chunk = chunk;
+        self
+    }
+
+    pub fn with_force_overwrite(mut self, force: bool) -> Self {
+        self.force_overwrite = force;
+        self
+    }
+
+    pub fn with_restart(mut self, restart: bool) -> Self {
+        self.restart = restart;
+        self
+    }
+}
+
+/// Backfill `plan.from..plan.to` one window at a time
+///
+/// Resumes after the last completed window when a checkpoint from an
+/// interrupted run covers the start of the range. A failed window stops the
+/// run with everything before it stored and checkpointed.
+pub async fn backfill_range<P: HistoricalProvider>(
+    symbol: &str,
+    mint_address: &str,
+    plan: &ChunkedBackfill,
+    provider: &P,
+    persistence: &mut RedisPersistence,
+) -> Result<BackfillStats> {
+    if plan.chunk <= Duration::zero() {
+        return Err("Backfill chunk size must be positive".into());
+    }
+    if plan.from >= plan.to {
+        return Err(format!(
+            "Backfill range is empty ({} is not before {})",
+            plan.from, plan.to
+        )
+        .into());
+    }
+
+    let mut stats = BackfillStats::default();
+    let saved = if plan.restart {
+        None
+    } else {
+        persistence.load_backfill_checkpoint(symbol).await?
+    };
+
+    let mut checkpoint = match saved {
+        Some(saved) => match saved.resume_point(plan.from, plan.to) {
+            Some(resume_at) => {
+                tracing::info!(
+                    "Resuming {} backfill at {} ({} candles stored earlier)",
+                    symbol,
+                    resume_at,
+                    saved.stored
+                );
+                stats.resumed_from = Some(resume_at);
+                BackfillCheckpoint {
+                    to: plan.to,
+                    chunk_minutes: plan.chunk.num_minutes(),
+                    ..saved
+                }
+            }
+            None => BackfillCheckpoint::new(symbol, plan.from, plan.to, plan.chunk),
+        },
+        None => BackfillCheckpoint::new(symbol, plan.from, plan.to, plan.chunk),
     };
 
-    // Find CoinGecko coin_id
-    let coin_id = coingecko.find_coin_id(symbol, mint_address).await?;
-    tracing::debug!("Found coin_id: {} for {}", coin_id, symbol);
+    let start = checkpoint.completed_until.max(plan.from);
+    let chunks = plan_chunks(start, plan.to, plan.chunk);
+    tracing::info!(
+        "Backfilling {} from {} to {} in {} windows",
+        symbol,
+        start,
+        plan.to,
+        chunks.len()
+    );
+
+    for (i, (window_start, window_end)) in chunks.iter().copied().enumerate() {
+        let stored_before = stats.stored_new;
+        // Windows are half-open so the boundary candle is fetched once
+        backfill_window(
+            symbol,
+            mint_address,
+            (window_start, window_end - Duration::seconds(1)),
+            plan.force_overwrite,
+            provider,
+            persistence,
+            &mut stats,
+        )
+        .await
+        .map_err(|e| {
+            format!(
+                "Backfill of {} stopped at {} (rerun to resume): {}",
+                symbol, window_start, e
+            )
+        })?;
+        stats.chunks += 1;
+
+        checkpoint.advance(window_end, stats.stored_new - stored_before);
+        persistence.save_backfill_checkpoint(&checkpoint).await?;
+        tracing::info!(
+            "{} window {}/{} done up to {} ({} stored so far)",
+            symbol,
+            i + 1,
+            chunks.len(),
+            window_end,
+            checkpoint.stored
+        );
+    }
 
-    // Fetch market chart data
-    let market_data = coingecko.get_market_chart(&coin_id, days).await?;
-    stats.fetched_points = market_data.prices.len();
-    tracing::debug!("Fetched {} price points", stats.fetched_points);
+    Ok(stats)
+}
 
-    // Convert to candles
-    let converter = CandleConverter::new();
-    let candles = converter.convert_to_candles(symbol, market_data)?;
-    stats.converted_candles = candles.len();
-    tracing::debug!("Converted to {} candles", stats.converted_candles);
+/// Fetch, validate and store one window, adding to `stats`
+async fn backfill_window<P: HistoricalProvider>(
+    symbol: &str,
+    mint_address: &str,
+    (from, to): (DateTime<Utc>, DateTime<Utc>),
+    force_overwrite: bool,
+    provider: &P,
+    persistence: &mut RedisPersistence,
+    stats: &mut BackfillStats,
+) -> Result<()> {
+    // Fetch uniform 5-minute candles
+    let history = provider
+        .fetch_history(symbol, mint_address, from, to)
+        .await?;
+    stats.provider.get_or_insert(history.provider);
+    stats.fetched_points += history.fetched_points;
+    let provider_kind = history.provider;
+    let fetched_points = history.fetched_points;
+    let candles = interval_volume_candles(history);
+    stats.converted_candles += candles.len();
+    tracing::debug!(
+        "Fetched {} points from {}, {} candles",
+        fetched_points,
+        provider_kind,
+        candles.len()
+    );
+    if !provider_kind.has_volume() && !candles.is_empty() {
+        tracing::warn!(
+            "{} has no per-candle volume - volume features stay disabled for {}",
+            provider_kind,
+            symbol
+        );
+    }
 
     // Get existing timestamps for overlap detection (if not forcing overwrite)
     let existing_timestamps = if !force_overwrite {
+        let margin = Duration::seconds(60);
         persistence
-            .load_candles(symbol, days as u64 * 24)
+            .load_candles_between(symbol, Some(from - margin), Some(to + margin))
             .await?
             .into_iter()
             .map(|c| c.timestamp)
@@ -81,15 +275,9 @@ pub async fn backfill_token(
         }
 
         // Check for overlap (within 60 seconds)
-        if !force_overwrite {
-            let is_duplicate = existing_timestamps
-                .iter()
-                .any(|&ts| (candle.timestamp - ts).num_seconds().abs() < 60);
-
-            if is_duplicate {
-                stats.skipped_existing += 1;
-                continue;
-            }
+        if !force_overwrite && has_nearby(&existing_timestamps, &candle) {
+            stats.skipped_existing += 1;
+            continue;
         }
 
         candles_to_store.push(candle);
@@ -98,24 +286,94 @@ pub async fn backfill_token(
     // Store candles
     if !candles_to_store.is_empty() {
         persistence.save_candles(symbol, &candles_to_store).await?;
-        stats.stored_new = candles_to_store.len();
-        tracing::info!(
-            "✓ Stored {} new candles for {} (skipped {}, failed validation {})",
-            stats.stored_new,
-            symbol,
-            stats.skipped_existing,
-            stats.validation_failures
-        );
-    } else {
-        tracing::info!("No new candles to store for {}", symbol);
+        stats.stored_new += candles_to_store.len();
     }
 
-    Ok(stats)
+    Ok(())
+}
+
+/// A provider's candles with volume in the stored unit: USD traded during each
+/// 5-minute candle (see [`Candle::volume`])
+///
+/// OHLCV providers already report per-bucket USD volume. Anything else (e.g.
+/// CoinGecko's rolling 24h volume) is not comparable with the live series and
+/// is stored as 0.0, meaning unknown.
+fn interval_volume_candles(history: ProviderHistory) -> Vec<Candle> {
+    let mut candles = history.candles;
+    if !history.provider.has_volume() {
+        for candle in &mut candles {
+            candle.volume = 0.0;
+        }
+    }
+    candles
+}
+
+/// Whether a stored timestamp lies within 60 seconds of `candle` (`existing` is sorted)
+fn has_nearby(existing: &[DateTime<Utc>], candle: &Candle) -> bool {
+    let idx = existing.partition_point(|&ts| ts < candle.timestamp);
+    let near = |ts: &DateTime<Utc>| (candle.timestamp - *ts).num_seconds().abs() < 60;
+    existing.get(idx).is_some_and(near)
+        || idx
+            .checked_sub(1)
+            .and_then(|i| existing.get(i))
+            .is_some_and(near)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::api::CoinGeckoClient;
+    use crate::models::CandleProvenance;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_has_nearby() {
+        let t0 = Utc.with_ymd_and_hms(2024, 6, 1, 0, 0, 0).unwrap();
+        let existing = vec![t0, t0 + Duration::minutes(5), t0 + Duration::minutes(10)];
+        let candle = |seconds: i64| Candle {
+            token: "SOL".to_string(),
+            timestamp: t0 + Duration::seconds(seconds),
+            open: 1.0,
+            high: 1.0,
+            low: 1.0,
+            close: 1.0,
+            volume: 0.0,
+            provenance: CandleProvenance::Live,
+        };
+
+        assert!(has_nearby(&existing, &candle(0)));
+        assert!(has_nearby(&existing, &candle(330)));
+        assert!(has_nearby(&existing, &candle(-30)));
+        assert!(!has_nearby(&existing, &candle(150)));
+        assert!(!has_nearby(&existing, &candle(700)));
+        assert!(!has_nearby(&[], &candle(0)));
+    }
+
+    #[test]
+    fn test_interval_volume_candles() {
+        let t0 = Utc.with_ymd_and_hms(2024, 6, 1, 0, 0, 0).unwrap();
+        let history = |provider, volume| ProviderHistory {
+            provider,
+            fetched_points: 1,
+            candles: vec![Candle {
+                token: "SOL".to_string(),
+                timestamp: t0,
+                open: 1.0,
+                high: 1.0,
+                low: 1.0,
+                close: 1.0,
+                volume,
+                provenance: CandleProvenance::Backfilled,
+            }],
+        };
+
+        // Per-bucket OHLCV volume is stored as is
+        let candles = interval_volume_candles(history(ProviderKind::GeckoTerminal, 1500.0));
+        assert_eq!(candles[0].volume, 1500.0);
+        // A rolling 24h figure is not per-interval volume
+        let candles = interval_volume_candles(history(ProviderKind::CoinGecko, 4.2e8));
+        assert_eq!(candles[0].volume, 0.0);
+    }
 
     /// Integration test: Full backfill flow for SOL
     #[tokio::test]
@@ -161,7 +419,7 @@ mod tests {
 
         // Verify data in Redis
         let loaded = redis.load_candles("TEST_SOL", 24).await.unwrap();
-        assert!(loaded.len() > 0, "Should have data in Redis");
+        assert!(!loaded.is_empty(), "Should have data in Redis");
 
         // Cleanup
         let _ = redis.cleanup_old("TEST_SOL", 0).await;
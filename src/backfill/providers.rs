@@ -0,0 +1,456 @@
+use crate::api::{BirdeyeClient, CoinGeckoClient, GeckoTerminalClient};
+use crate::backfill::CandleConverter;
+use crate::models::{Candle, CandleProvenance};
+use crate::Result;
+use chrono::{DateTime, Duration, Utc};
+use std::fmt;
+use std::future::Future;
+use std::str::FromStr;
+
+const CANDLE_INTERVAL_SECS: i64 = 300;
+
+/// Historical data providers, best data first
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProviderKind {
+    /// Birdeye OHLCV (premium API key required)
+    Birdeye,
+    /// GeckoTerminal pool OHLCV (free, no key)
+    GeckoTerminal,
+    /// CoinGecko market chart (irregular prices, no per-candle volume)
+    CoinGecko,
+}
+
+impl ProviderKind {
+    pub const ALL: [ProviderKind; 3] = [Self::Birdeye, Self::GeckoTerminal, Self::CoinGecko];
+
+    /// Default priority: Birdeye OHLCV is premium-only (the bot's free key
+    /// would just fail), so it has to be opted into via `BACKFILL_PROVIDERS`
+    pub const DEFAULT: [ProviderKind; 2] = [Self::GeckoTerminal, Self::CoinGecko];
+
+    /// Priority order from `BACKFILL_PROVIDERS` (comma-separated), or [`Self::DEFAULT`]
+    pub fn from_env() -> Vec<ProviderKind> {
+        let Ok(value) = std::env::var("BACKFILL_PROVIDERS") else {
+            return Self::DEFAULT.to_vec();
+        };
+        value
+            .split(',')
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .filter_map(|s| match s.parse() {
+                Ok(kind) => Some(kind),
+                Err(e) => {
+                    tracing::warn!("Ignoring BACKFILL_PROVIDERS entry: {}", e);
+                    None
+                }
+            })
+            .collect()
+    }
+
+    /// Whether candles carry real traded volume
+    pub fn has_volume(&self) -> bool {
+        !matches!(self, Self::CoinGecko)
+    }
+}
+
+impl fmt::Display for ProviderKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Birdeye => write!(f, "birdeye"),
+            Self::GeckoTerminal => write!(f, "geckoterminal"),
+            Self::CoinGecko => write!(f, "coingecko"),
+        }
+    }
+}
+
+impl FromStr for ProviderKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "birdeye" => Ok(Self::Birdeye),
+            "geckoterminal" | "gecko-terminal" | "dex" => Ok(Self::GeckoTerminal),
+            "coingecko" => Ok(Self::CoinGecko),
+            _ => Err(format!(
+                "Unknown historical provider '{}' (expected birdeye, geckoterminal or coingecko)",
+                s
+            )),
+        }
+    }
+}
+
+/// Candles fetched by one provider
+#[derive(Debug, Clone)]
+pub struct ProviderHistory {
+    pub provider: ProviderKind,
+    /// Raw points/candles returned before conversion and gap filling
+    pub fetched_points: usize,
+    /// Uniform 5-minute candles, oldest first (volume as the provider reports
+    /// it; `backfill_window` converts it to per-interval USD)
+    pub candles: Vec<Candle>,
+}
+
+/// Source of historical 5-minute candles for backfill
+pub trait HistoricalProvider {
+    /// Candles for `mint_address` between `from` and `to`
+    fn fetch_history(
+        &self,
+        symbol: &str,
+        mint_address: &str,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> impl Future<Output = Result<ProviderHistory>> + Send;
+}
+
+impl HistoricalProvider for CoinGeckoClient {
+    async fn fetch_history(
+        &self,
+        symbol: &str,
+        mint_address: &str,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<ProviderHistory> {
+        let coin_id = self.find_coin_id(symbol, mint_address).await?;
+        tracing::debug!("Found coin_id: {} for {}", coin_id, symbol);
+
+        let market_data = self
+            .get_market_chart_range(&coin_id, from.timestamp(), to.timestamp())
+            .await?;
+        let fetched_points = market_data.prices.len();
+
+        let candles = CandleConverter::new()
+            .convert_to_candles(symbol, market_data)?
+            .into_iter()
+            .filter(|c| c.timestamp >= from && c.timestamp <= to)
+            .collect();
+
+        Ok(ProviderHistory {
+            provider: ProviderKind::CoinGecko,
+            fetched_points,
+            candles,
+        })
+    }
+}
+
+impl HistoricalProvider for GeckoTerminalClient {
+    async fn fetch_history(
+        &self,
+        symbol: &str,
+        mint_address: &str,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<ProviderHistory> {
+        let pool = self.find_top_pool(mint_address).await?;
+        tracing::debug!("Using GeckoTerminal pool {} for {}", pool, symbol);
+
+        let candles = self
+            .get_ohlcv(symbol, &pool, mint_address, from, to)
+            .await?;
+        Ok(ProviderHistory {
+            provider: ProviderKind::GeckoTerminal,
+            fetched_points: candles.len(),
+            candles: fill_missing_intervals(candles),
+        })
+    }
+}
+
+impl HistoricalProvider for BirdeyeClient {
+    async fn fetch_history(
+        &self,
+        symbol: &str,
+        mint_address: &str,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<ProviderHistory> {
+        let candles = self.get_ohlcv(symbol, mint_address, from, to).await?;
+        Ok(ProviderHistory {
+            provider: ProviderKind::Birdeye,
+            fetched_points: candles.len(),
+            candles: fill_missing_intervals(candles),
+        })
+    }
+}
+
+/// One configured provider client
+#[derive(Clone)]
+pub enum Provider {
+    Birdeye(BirdeyeClient),
+    GeckoTerminal(GeckoTerminalClient),
+    CoinGecko(CoinGeckoClient),
+}
+
+impl Provider {
+    pub fn kind(&self) -> ProviderKind {
+        match self {
+            Self::Birdeye(_) => ProviderKind::Birdeye,
+            Self::GeckoTerminal(_) => ProviderKind::GeckoTerminal,
+            Self::CoinGecko(_) => ProviderKind::CoinGecko,
+        }
+    }
+}
+
+impl HistoricalProvider for Provider {
+    async fn fetch_history(
+        &self,
+        symbol: &str,
+        mint_address: &str,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<ProviderHistory> {
+        match self {
+            Self::Birdeye(client) => client.fetch_history(symbol, mint_address, from, to).await,
+            Self::GeckoTerminal(client) => {
+                client.fetch_history(symbol, mint_address, from, to).await
+            }
+            Self::CoinGecko(client) => client.fetch_history(symbol, mint_address, from, to).await,
+        }
+    }
+}
+
+/// Providers tried in priority order until one returns candles
+pub struct ProviderChain<P = Provider> {
+    providers: Vec<P>,
+}
+
+impl<P> ProviderChain<P> {
+    pub fn new(providers: Vec<P>) -> Self {
+        Self { providers }
+    }
+
+    pub fn providers(&self) -> &[P] {
+        &self.providers
+    }
+}
+
+impl ProviderChain {
+    /// Build the requested providers (in the given order), skipping any whose
+    /// API key is missing: `BIRDEYE_API_KEY` for Birdeye, `COINGECKO_API_KEY`
+    /// for CoinGecko. GeckoTerminal needs no key.
+    pub async fn from_env(kinds: &[ProviderKind]) -> Result<Self> {
+        let mut providers = Vec::new();
+        for kind in kinds {
+            match kind {
+                ProviderKind::Birdeye => match std::env::var("BIRDEYE_API_KEY") {
+                    Ok(key) => providers.push(Provider::Birdeye(BirdeyeClient::new(key))),
+                    Err(_) => tracing::warn!("BIRDEYE_API_KEY not set - skipping Birdeye"),
+                },
+                ProviderKind::GeckoTerminal => {
+                    providers.push(Provider::GeckoTerminal(GeckoTerminalClient::new()))
+                }
+                ProviderKind::CoinGecko => match std::env::var("COINGECKO_API_KEY") {
+                    Ok(key) => match CoinGeckoClient::new(key).await {
+                        Ok(client) => providers.push(Provider::CoinGecko(client)),
+                        Err(e) => {
+                            tracing::warn!("Failed to initialize CoinGecko client: {}", e)
+                        }
+                    },
+                    Err(_) => tracing::warn!("COINGECKO_API_KEY not set - skipping CoinGecko"),
+                },
+            }
+        }
+
+        if providers.is_empty() {
+            return Err("No historical data provider available".into());
+        }
+        Ok(Self::new(providers))
+    }
+}
+
+impl<P: HistoricalProvider + Sync> HistoricalProvider for ProviderChain<P> {
+    async fn fetch_history(
+        &self,
+        symbol: &str,
+        mint_address: &str,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<ProviderHistory> {
+        let mut errors = Vec::new();
+        let mut empty = None;
+        let mut failed = false;
+        for provider in &self.providers {
+            match provider.fetch_history(symbol, mint_address, from, to).await {
+                Ok(history) if !history.candles.is_empty() => {
+                    tracing::info!(
+                        "Fetched {} candles for {} from {}",
+                        history.candles.len(),
+                        symbol,
+                        history.provider
+                    );
+                    return Ok(history);
+                }
+                Ok(history) => {
+                    tracing::debug!("{} returned no candles for {}", history.provider, symbol);
+                    errors.push(format!("{}: no candles", history.provider));
+                    empty = Some(history);
+                }
+                Err(e) => {
+                    tracing::warn!("Provider failed for {}: {}", symbol, e);
+                    errors.push(e.to_string());
+                    failed = true;
+                }
+            }
+        }
+
+        // Every provider answered but none has data for the window (e.g. before
+        // the token existed): nothing to store, not a failure
+        if let (Some(history), false) = (empty, failed) {
+            return Ok(history);
+        }
+
+        Err(format!(
+            "No provider returned candles for {} ({})",
+            symbol,
+            errors.join("; ")
+        )
+        .into())
+    }
+}
+
+/// Insert flat, zero-volume candles at the previous close where an OHLCV
+/// provider skipped 5-minute buckets without trades
+pub fn fill_missing_intervals(candles: Vec<Candle>) -> Vec<Candle> {
+    let mut filled: Vec<Candle> = Vec::with_capacity(candles.len());
+    for candle in candles {
+        if let Some(prev) = filled.last().cloned() {
+            let mut timestamp = prev.timestamp + Duration::seconds(CANDLE_INTERVAL_SECS);
+            while timestamp < candle.timestamp {
+                filled.push(Candle {
+                    token: prev.token.clone(),
+                    timestamp,
+                    open: prev.close,
+                    high: prev.close,
+                    low: prev.close,
+                    close: prev.close,
+                    volume: 0.0,
+                    provenance: CandleProvenance::Interpolated,
+                });
+                timestamp += Duration::seconds(CANDLE_INTERVAL_SECS);
+            }
+        }
+        filled.push(candle);
+    }
+    filled
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn candle(minutes: i64, close: f64, volume: f64) -> Candle {
+        Candle {
+            token: "SOL".to_string(),
+            timestamp: Utc.with_ymd_and_hms(2024, 6, 1, 0, 0, 0).unwrap()
+                + Duration::minutes(minutes),
+            open: close,
+            high: close,
+            low: close,
+            close,
+            volume,
+            provenance: CandleProvenance::Live,
+        }
+    }
+
+    /// Provider that fails, returns nothing, or returns fixed candles
+    enum MockProvider {
+        Failing,
+        Empty(ProviderKind),
+        Candles(ProviderKind, Vec<Candle>),
+    }
+
+    impl HistoricalProvider for MockProvider {
+        async fn fetch_history(
+            &self,
+            _symbol: &str,
+            _mint_address: &str,
+            _from: DateTime<Utc>,
+            _to: DateTime<Utc>,
+        ) -> Result<ProviderHistory> {
+            match self {
+                Self::Failing => Err("401 Unauthorized".into()),
+                Self::Empty(kind) => Ok(ProviderHistory {
+                    provider: *kind,
+                    fetched_points: 0,
+                    candles: vec![],
+                }),
+                Self::Candles(kind, candles) => Ok(ProviderHistory {
+                    provider: *kind,
+                    fetched_points: candles.len(),
+                    candles: candles.clone(),
+                }),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_chain_falls_back_in_priority_order() {
+        let chain = ProviderChain::new(vec![
+            MockProvider::Failing,
+            MockProvider::Empty(ProviderKind::GeckoTerminal),
+            MockProvider::Candles(ProviderKind::CoinGecko, vec![candle(0, 1.0, 0.0)]),
+            MockProvider::Candles(ProviderKind::Birdeye, vec![candle(0, 2.0, 5.0)]),
+        ]);
+
+        let history = chain
+            .fetch_history("SOL", "mint", Utc::now(), Utc::now())
+            .await
+            .unwrap();
+        assert_eq!(history.provider, ProviderKind::CoinGecko);
+        assert_eq!(history.candles[0].close, 1.0);
+    }
+
+    #[tokio::test]
+    async fn test_chain_reports_every_failure() {
+        let chain = ProviderChain::new(vec![
+            MockProvider::Failing,
+            MockProvider::Empty(ProviderKind::GeckoTerminal),
+        ]);
+
+        let err = chain
+            .fetch_history("SOL", "mint", Utc::now(), Utc::now())
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("401 Unauthorized"), "{}", err);
+        assert!(err.contains("geckoterminal: no candles"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn test_chain_with_no_data_anywhere_is_empty_not_an_error() {
+        let chain = ProviderChain::new(vec![
+            MockProvider::Empty(ProviderKind::GeckoTerminal),
+            MockProvider::Empty(ProviderKind::CoinGecko),
+        ]);
+
+        let history = chain
+            .fetch_history("SOL", "mint", Utc::now(), Utc::now())
+            .await
+            .unwrap();
+        assert!(history.candles.is_empty());
+    }
+
+    #[test]
+    fn test_fill_missing_intervals() {
+        let filled = fill_missing_intervals(vec![candle(0, 10.0, 100.0), candle(15, 12.0, 50.0)]);
+
+        assert_eq!(filled.len(), 4);
+        assert_eq!(filled[1].timestamp, candle(5, 0.0, 0.0).timestamp);
+        assert_eq!(filled[1].close, 10.0);
+        assert_eq!(filled[1].volume, 0.0);
+        assert_eq!(filled[3].close, 12.0);
+        assert_eq!(filled[3].volume, 50.0);
+    }
+
+    #[test]
+    fn test_provider_kind_from_str() {
+        assert_eq!(
+            "GeckoTerminal".parse::<ProviderKind>().unwrap(),
+            ProviderKind::GeckoTerminal
+        );
+        assert_eq!(
+            "birdeye".parse::<ProviderKind>().unwrap(),
+            ProviderKind::Birdeye
+        );
+        assert!("kraken".parse::<ProviderKind>().is_err());
+        assert!(!ProviderKind::CoinGecko.has_volume());
+    }
+}
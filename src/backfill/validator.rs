@@ -103,6 +103,7 @@ impl Default for CandleValidator {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::models::CandleProvenance;
     use chrono::Duration;
 
     fn make_valid_candle() -> Candle {
@@ -114,6 +115,7 @@ mod tests {
             low: 99.0,
             close: 101.0,
             volume: 1000000.0,
+            provenance: CandleProvenance::Live,
         }
     }
 
@@ -0,0 +1,221 @@
+use crate::backtest::metrics::BacktestMetrics;
+use crate::backtest::portfolio::PortfolioBacktestRunner;
+use crate::models::Candle;
+use crate::strategy::Strategy;
+use crate::Result;
+use std::collections::HashMap;
+use std::io::Write;
+use std::num::NonZeroUsize;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+/// One backtest to run: a runner configuration, a strategy and its candles
+///
+/// Jobs borrow their runner and strategy, so a parameter sweep can build one
+/// runner/strategy per parameter set and share each across many jobs.
+pub struct BacktestJob<'a> {
+    pub name: String,
+    pub runner: &'a PortfolioBacktestRunner,
+    pub strategy: &'a dyn Strategy,
+    pub series: HashMap<String, Vec<Candle>>,
+}
+
+impl<'a> BacktestJob<'a> {
+    pub fn new(
+        name: impl Into<String>,
+        runner: &'a PortfolioBacktestRunner,
+        strategy: &'a dyn Strategy,
+        series: HashMap<String, Vec<Candle>>,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            runner,
+            strategy,
+            series,
+        }
+    }
+}
+
+/// Outcome of one job, in the position the job was submitted
+pub struct BatchResult {
+    pub name: String,
+    pub metrics: Result<BacktestMetrics>,
+}
+
+/// Runs backtest jobs concurrently on a pool of worker threads
+///
+/// Workers pull the next job from a shared counter, so long and short jobs
+/// balance out, and results always come back in submission order regardless
+/// of which thread finished first.
+#[derive(Debug, Clone)]
+pub struct BatchExecutor {
+    threads: usize,
+    progress: bool,
+}
+
+impl Default for BatchExecutor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BatchExecutor {
+    /// One worker per CPU core, or `BACKTEST_THREADS` if set
+    pub fn new() -> Self {
+        let threads = std::env::var("BACKTEST_THREADS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or_else(|| {
+                thread::available_parallelism()
+                    .map(NonZeroUsize::get)
+                    .unwrap_or(1)
+            });
+        Self {
+            threads: threads.max(1),
+            progress: false,
+        }
+    }
+
+    pub fn with_threads(mut self, threads: usize) -> Self {
+        self.threads = threads.max(1);
+        self
+    }
+
+    /// Show a `done/total` counter on stderr while jobs run
+    pub fn with_progress(mut self, progress: bool) -> Self {
+        self.progress = progress;
+        self
+    }
+
+    pub fn threads(&self) -> usize {
+        self.threads
+    }
+
+    /// Run every job and return their results in submission order
+    pub fn run(&self, jobs: Vec<BacktestJob<'_>>) -> Vec<BatchResult> {
+        let metrics = self.map(&jobs, |job| job.runner.run(job.strategy, &job.series));
+        jobs.into_iter()
+            .zip(metrics)
+            .map(|(job, metrics)| BatchResult {
+                name: job.name,
+                metrics,
+            })
+            .collect()
+    }
+
+    /// Apply `f` to every item across the worker threads, keeping input order
+    pub fn map<T, R, F>(&self, items: &[T], f: F) -> Vec<R>
+    where
+        T: Sync,
+        R: Send,
+        F: Fn(&T) -> R + Sync,
+    {
+        let total = items.len();
+        let workers = self.threads.min(total);
+        let done = AtomicUsize::new(0);
+
+        let results = if workers <= 1 {
+            items
+                .iter()
+                .map(|item| {
+                    let result = f(item);
+                    self.report_progress(&done, total);
+                    result
+                })
+                .collect()
+        } else {
+            let next = AtomicUsize::new(0);
+            let slots: Mutex<Vec<Option<R>>> = Mutex::new((0..total).map(|_| None).collect());
+
+            thread::scope(|scope| {
+                for _ in 0..workers {
+                    scope.spawn(|| loop {
+                        let index = next.fetch_add(1, Ordering::Relaxed);
+                        let Some(item) = items.get(index) else {
+                            break;
+                        };
+                        let result = f(item);
+                        slots.lock().expect("batch results lock poisoned")[index] = Some(result);
+                        self.report_progress(&done, total);
+                    });
+                }
+            });
+
+            slots
+                .into_inner()
+                .expect("batch results lock poisoned")
+                .into_iter()
+                .map(|slot| slot.expect("every batch job produces a result"))
+                .collect()
+        };
+
+        if self.progress && total > 0 {
+            eprintln!();
+        }
+        results
+    }
+
+    fn report_progress(&self, done: &AtomicUsize, total: usize) {
+        if !self.progress {
+            return;
+        }
+        let finished = done.fetch_add(1, Ordering::Relaxed) + 1;
+        eprint!(
+            "\r   ⏳ {}/{} backtests done ({} workers)",
+            finished, total, self.threads
+        );
+        let _ = std::io::stderr().flush();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backtest::synthetic::{MarketScenario, SyntheticDataGenerator};
+    use crate::risk::CircuitBreakers;
+    use crate::strategy::buy_and_hold::BuyAndHoldStrategy;
+
+    fn series(seed: u64, scenario: MarketScenario) -> HashMap<String, Vec<Candle>> {
+        let candles = SyntheticDataGenerator::new(seed).generate(scenario, 200, 5);
+        HashMap::from([("SYNTH".to_string(), candles)])
+    }
+
+    #[test]
+    fn test_map_keeps_input_order() {
+        let items: Vec<u64> = (0..200).collect();
+        let squares = BatchExecutor::new().with_threads(8).map(&items, |i| i * i);
+        assert_eq!(squares, items.iter().map(|i| i * i).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_parallel_results_match_sequential() {
+        let runner = PortfolioBacktestRunner::new(10000.0, CircuitBreakers::default());
+        let strategy = BuyAndHoldStrategy::new();
+        let jobs = || {
+            MarketScenario::ALL
+                .iter()
+                .enumerate()
+                .map(|(i, scenario)| {
+                    BacktestJob::new(
+                        scenario.to_string(),
+                        &runner,
+                        &strategy,
+                        series(i as u64, *scenario),
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let sequential = BatchExecutor::new().with_threads(1).run(jobs());
+        let parallel = BatchExecutor::new().with_threads(4).run(jobs());
+
+        assert_eq!(sequential.len(), MarketScenario::ALL.len());
+        for (a, b) in sequential.iter().zip(&parallel) {
+            assert_eq!(a.name, b.name);
+            let (a, b) = (a.metrics.as_ref().unwrap(), b.metrics.as_ref().unwrap());
+            assert_eq!(a.total_pnl, b.total_pnl);
+            assert_eq!(a.trades.len(), b.trades.len());
+        }
+    }
+}
@@ -0,0 +1,303 @@
+use crate::backtest::equity::EquityCurve;
+use crate::models::Candle;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+/// Name of the equal-weighted buy-and-hold benchmark of the backtested tokens
+pub const BUY_AND_HOLD: &str = "buy-and-hold";
+
+/// Timestamped benchmark prices (or basket values)
+pub type BenchmarkSeries = Vec<(DateTime<Utc>, f64)>;
+
+/// How a backtest did relative to a passive benchmark over the same candles
+///
+/// Returns are per-candle simple returns matched on timestamp; alpha, tracking
+/// error and the information ratio are annualized (risk-free rate of 0).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BenchmarkStats {
+    pub name: String,
+    /// Benchmark total return over the period (%)
+    pub return_pct: f64,
+    /// Strategy total return minus benchmark total return (percentage points)
+    pub excess_return_pct: f64,
+    /// Annualized CAPM alpha (0.10 = 10%/yr)
+    pub alpha: f64,
+    pub beta: f64,
+    pub correlation: f64,
+    /// Annualized standard deviation of active returns
+    pub tracking_error: f64,
+    pub information_ratio: f64,
+}
+
+impl BenchmarkStats {
+    /// Compare `curve` to a benchmark price (or value) series
+    ///
+    /// Only timestamps present in both are used. Returns `None` with fewer than
+    /// three matched samples.
+    pub fn compare(
+        name: &str,
+        curve: &EquityCurve,
+        benchmark: &[(DateTime<Utc>, f64)],
+    ) -> Option<Self> {
+        let prices: HashMap<DateTime<Utc>, f64> = benchmark.iter().copied().collect();
+        let matched: Vec<(f64, f64)> = curve
+            .points
+            .iter()
+            .filter_map(|p| prices.get(&p.timestamp).map(|b| (p.value, *b)))
+            .filter(|(value, price)| *value > 0.0 && *price > 0.0)
+            .collect();
+        if matched.len() < 3 {
+            return None;
+        }
+
+        let (strategy, bench): (Vec<f64>, Vec<f64>) = matched
+            .windows(2)
+            .map(|w| (w[1].0 / w[0].0 - 1.0, w[1].1 / w[0].1 - 1.0))
+            .unzip();
+
+        let periods_per_year = curve.periods_per_year();
+        let mean_strategy = mean(&strategy);
+        let mean_bench = mean(&bench);
+        let var_bench = variance(&bench, mean_bench);
+        let var_strategy = variance(&strategy, mean_strategy);
+        let covariance = strategy
+            .iter()
+            .zip(&bench)
+            .map(|(s, b)| (s - mean_strategy) * (b - mean_bench))
+            .sum::<f64>()
+            / strategy.len() as f64;
+
+        let beta = if var_bench > 0.0 {
+            covariance / var_bench
+        } else {
+            0.0
+        };
+        let correlation = if var_bench > 0.0 && var_strategy > 0.0 {
+            covariance / (var_bench * var_strategy).sqrt()
+        } else {
+            0.0
+        };
+
+        let active: Vec<f64> = strategy.iter().zip(&bench).map(|(s, b)| s - b).collect();
+        let mean_active = mean(&active);
+        let active_std = variance(&active, mean_active).sqrt();
+        let tracking_error = active_std * periods_per_year.sqrt();
+        let information_ratio = if active_std > 0.0 {
+            mean_active / active_std * periods_per_year.sqrt()
+        } else {
+            0.0
+        };
+
+        let (first, last) = (matched[0], matched[matched.len() - 1]);
+        let strategy_return_pct = (last.0 / first.0 - 1.0) * 100.0;
+        let return_pct = (last.1 / first.1 - 1.0) * 100.0;
+
+        Some(Self {
+            name: name.to_string(),
+            return_pct,
+            excess_return_pct: strategy_return_pct - return_pct,
+            alpha: (mean_strategy - beta * mean_bench) * periods_per_year,
+            beta,
+            correlation,
+            tracking_error,
+            information_ratio,
+        })
+    }
+}
+
+/// Closes of one token as a benchmark series
+pub fn close_series(candles: &[Candle]) -> BenchmarkSeries {
+    candles.iter().map(|c| (c.timestamp, c.close)).collect()
+}
+
+/// Equal-weighted buy-and-hold of every token, bought at the first common candle
+///
+/// Only timestamps every token trades on are kept. Each token's weight is its
+/// close relative to its close at the first of them (where every close is
+/// positive), so the series starts at 1.0 even when histories start at
+/// different times.
+pub fn equal_weight_series(series: &BTreeMap<String, Vec<Candle>>) -> BenchmarkSeries {
+    let mut by_time: BTreeMap<DateTime<Utc>, Vec<f64>> = BTreeMap::new();
+    for candles in series.values() {
+        for candle in candles {
+            by_time
+                .entry(candle.timestamp)
+                .or_default()
+                .push(candle.close);
+        }
+    }
+
+    let common: Vec<(DateTime<Utc>, Vec<f64>)> = by_time
+        .into_iter()
+        .filter(|(_, closes)| closes.len() == series.len())
+        .skip_while(|(_, closes)| closes.iter().any(|&c| c <= 0.0))
+        .collect();
+    let Some((_, base)) = common.first().cloned() else {
+        return Vec::new();
+    };
+
+    common
+        .into_iter()
+        .map(|(timestamp, closes)| {
+            let relatives: Vec<f64> = closes.iter().zip(&base).map(|(c, b)| c / b).collect();
+            (timestamp, mean(&relatives))
+        })
+        .collect()
+}
+
+fn mean(values: &[f64]) -> f64 {
+    if values.is_empty() {
+        return 0.0;
+    }
+    values.iter().sum::<f64>() / values.len() as f64
+}
+
+fn variance(values: &[f64], mean: f64) -> f64 {
+    if values.is_empty() {
+        return 0.0;
+    }
+    values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / values.len() as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::CandleProvenance;
+    use chrono::Duration;
+
+    fn times(count: usize) -> Vec<DateTime<Utc>> {
+        let start = Utc::now();
+        (0..count)
+            .map(|i| start + Duration::minutes(5 * i as i64))
+            .collect()
+    }
+
+    fn curve(timestamps: &[DateTime<Utc>], values: &[f64]) -> EquityCurve {
+        let mut curve = EquityCurve::new();
+        for (t, v) in timestamps.iter().zip(values) {
+            curve.push(*t, *v, 0.0);
+        }
+        curve
+    }
+
+    #[test]
+    fn test_leveraged_benchmark_has_beta_two_and_no_alpha() {
+        let ts = times(50);
+        let bench_returns: Vec<f64> = (0..49)
+            .map(|i| ((i * 7 % 11) as f64 - 5.0) / 1000.0)
+            .collect();
+
+        let mut bench = vec![100.0];
+        let mut values = vec![10_000.0];
+        for r in &bench_returns {
+            bench.push(bench.last().unwrap() * (1.0 + r));
+            values.push(values.last().unwrap() * (1.0 + 2.0 * r));
+        }
+
+        let series: Vec<_> = ts.iter().copied().zip(bench.iter().copied()).collect();
+        let stats = BenchmarkStats::compare("SOL", &curve(&ts, &values), &series).unwrap();
+
+        assert!((stats.beta - 2.0).abs() < 1e-9, "beta {}", stats.beta);
+        assert!(stats.alpha.abs() < 1e-6, "alpha {}", stats.alpha);
+        assert!((stats.correlation - 1.0).abs() < 1e-9);
+        let strategy_return = (values[49] / values[0] - 1.0) * 100.0;
+        assert!((stats.excess_return_pct - (strategy_return - stats.return_pct)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_constant_outperformance_is_pure_alpha() {
+        let ts = times(30);
+        let bench: Vec<f64> = (0..30).map(|i| 100.0 + (i % 3) as f64).collect();
+        let mut values = vec![1_000.0];
+        for w in bench.windows(2) {
+            values.push(values.last().unwrap() * (w[1] / w[0] + 0.001));
+        }
+
+        let series: Vec<_> = ts.iter().copied().zip(bench).collect();
+        let stats = BenchmarkStats::compare("B&H", &curve(&ts, &values), &series).unwrap();
+
+        assert!((stats.beta - 1.0).abs() < 1e-9);
+        assert!(stats.alpha > 0.0);
+        // Active return is a constant 0.1%: no tracking error
+        assert!(stats.tracking_error < 1e-9);
+        assert!(stats.excess_return_pct > 0.0);
+    }
+
+    #[test]
+    fn test_needs_overlapping_samples() {
+        let ts = times(10);
+        let values = vec![100.0; 10];
+        let other: Vec<_> = times(10)
+            .into_iter()
+            .map(|t| (t + Duration::seconds(1), 1.0))
+            .collect();
+        assert!(BenchmarkStats::compare("SOL", &curve(&ts, &values), &other).is_none());
+    }
+
+    #[test]
+    fn test_equal_weight_series_starts_at_one() {
+        let ts = times(3);
+        let candle = |t: DateTime<Utc>, close: f64| Candle {
+            token: String::new(),
+            timestamp: t,
+            open: close,
+            high: close,
+            low: close,
+            close,
+            volume: 0.0,
+            provenance: CandleProvenance::Live,
+        };
+        let series = BTreeMap::from([
+            (
+                "AAA".to_string(),
+                vec![
+                    candle(ts[0], 10.0),
+                    candle(ts[1], 20.0),
+                    candle(ts[2], 10.0),
+                ],
+            ),
+            (
+                "BBB".to_string(),
+                vec![candle(ts[0], 1.0), candle(ts[2], 3.0)],
+            ),
+        ]);
+
+        let basket = equal_weight_series(&series);
+        assert_eq!(basket, vec![(ts[0], 1.0), (ts[2], 2.0)]);
+    }
+
+    #[test]
+    fn test_equal_weight_series_with_staggered_starts() {
+        let ts = times(4);
+        let candle = |t: DateTime<Utc>, close: f64| Candle {
+            token: String::new(),
+            timestamp: t,
+            open: close,
+            high: close,
+            low: close,
+            close,
+            volume: 0.0,
+            provenance: CandleProvenance::Live,
+        };
+        // AAA doubles before BBB's history begins; the basket is bought at ts[2]
+        let series = BTreeMap::from([
+            (
+                "AAA".to_string(),
+                vec![
+                    candle(ts[0], 10.0),
+                    candle(ts[1], 15.0),
+                    candle(ts[2], 20.0),
+                    candle(ts[3], 30.0),
+                ],
+            ),
+            (
+                "BBB".to_string(),
+                vec![candle(ts[2], 4.0), candle(ts[3], 2.0)],
+            ),
+        ]);
+
+        let basket = equal_weight_series(&series);
+        assert_eq!(basket, vec![(ts[2], 1.0), (ts[3], 1.0)]);
+    }
+}
@@ -0,0 +1,295 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// Minutes in a (24/7 crypto) year
+const MINUTES_PER_YEAR: f64 = 365.0 * 24.0 * 60.0;
+
+/// Portfolio value marked to market at one candle
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct EquityPoint {
+    pub timestamp: DateTime<Utc>,
+    pub value: f64,
+    /// Market value of open positions
+    pub exposure: f64,
+}
+
+/// Drawdown statistics from an equity curve
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct DrawdownStats {
+    /// Deepest drop from a peak in dollars
+    pub max_drawdown: f64,
+    /// Deepest drop from a peak in percent (may come from a different drawdown)
+    pub max_drawdown_pct: f64,
+    /// Longest time spent below a previous peak (unrecovered drawdowns run to the end)
+    pub max_duration_minutes: i64,
+}
+
+/// Equity curve sampled each candle
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct EquityCurve {
+    pub points: Vec<EquityPoint>,
+}
+
+impl EquityCurve {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, timestamp: DateTime<Utc>, value: f64, exposure: f64) {
+        self.points.push(EquityPoint {
+            timestamp,
+            value,
+            exposure,
+        });
+    }
+
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    /// Simple returns between consecutive samples
+    pub fn returns(&self) -> Vec<f64> {
+        self.points
+            .windows(2)
+            .filter(|w| w[0].value > 0.0)
+            .map(|w| w[1].value / w[0].value - 1.0)
+            .collect()
+    }
+
+    /// Sampling interval in minutes (median spacing, robust to gaps)
+    fn interval_minutes(&self) -> Option<f64> {
+        let mut spacings: Vec<i64> = self
+            .points
+            .windows(2)
+            .map(|w| (w[1].timestamp - w[0].timestamp).num_minutes())
+            .filter(|m| *m > 0)
+            .collect();
+        if spacings.is_empty() {
+            return None;
+        }
+        spacings.sort_unstable();
+        Some(spacings[spacings.len() / 2] as f64)
+    }
+
+    /// Number of samples per year, for annualizing per-candle statistics
+    pub fn periods_per_year(&self) -> f64 {
+        self.interval_minutes()
+            .map(|interval| MINUTES_PER_YEAR / interval)
+            .unwrap_or(0.0)
+    }
+
+    /// Annualized Sharpe ratio (risk-free rate of 0)
+    pub fn sharpe_ratio(&self) -> f64 {
+        let returns = self.returns();
+        if returns.len() < 2 {
+            return 0.0;
+        }
+        let mean = mean(&returns);
+        let variance =
+            returns.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / returns.len() as f64;
+        let std_dev = variance.sqrt();
+
+        if std_dev > 0.0 {
+            mean / std_dev * self.periods_per_year().sqrt()
+        } else {
+            0.0
+        }
+    }
+
+    /// Annualized Sortino ratio (downside deviation below 0)
+    pub fn sortino_ratio(&self) -> f64 {
+        let returns = self.returns();
+        if returns.len() < 2 {
+            return 0.0;
+        }
+        let mean = mean(&returns);
+        let downside =
+            returns.iter().map(|r| r.min(0.0).powi(2)).sum::<f64>() / returns.len() as f64;
+        let downside_dev = downside.sqrt();
+
+        if downside_dev > 0.0 {
+            mean / downside_dev * self.periods_per_year().sqrt()
+        } else {
+            0.0
+        }
+    }
+
+    /// Compound annual growth rate (0.25 = 25%/yr)
+    pub fn annualized_return(&self) -> f64 {
+        let (Some(first), Some(last)) = (self.points.first(), self.points.last()) else {
+            return 0.0;
+        };
+        let minutes = (last.timestamp - first.timestamp).num_minutes() as f64;
+        if minutes <= 0.0 || first.value <= 0.0 || last.value <= 0.0 {
+            return 0.0;
+        }
+        (last.value / first.value).powf(MINUTES_PER_YEAR / minutes) - 1.0
+    }
+
+    /// Calmar ratio: annualized return over max drawdown
+    pub fn calmar_ratio(&self) -> f64 {
+        let max_dd = self.drawdown().max_drawdown_pct / 100.0;
+        if max_dd > 0.0 {
+            self.annualized_return() / max_dd
+        } else {
+            0.0
+        }
+    }
+
+    /// Max drawdown depth and duration, marked to market each candle
+    pub fn drawdown(&self) -> DrawdownStats {
+        let Some(first) = self.points.first() else {
+            return DrawdownStats::default();
+        };
+
+        let mut stats = DrawdownStats::default();
+        let mut peak = first.value;
+        let mut peak_time = first.timestamp;
+
+        for point in &self.points {
+            if point.value >= peak {
+                peak = point.value;
+                peak_time = point.timestamp;
+                continue;
+            }
+
+            let drawdown = peak - point.value;
+            stats.max_drawdown = stats.max_drawdown.max(drawdown);
+            if peak > 0.0 {
+                stats.max_drawdown_pct = stats.max_drawdown_pct.max(drawdown / peak * 100.0);
+            }
+            let duration = (point.timestamp - peak_time).num_minutes();
+            stats.max_duration_minutes = stats.max_duration_minutes.max(duration);
+        }
+
+        stats
+    }
+
+    /// Percentage of samples with at least one open position
+    pub fn time_in_market_pct(&self) -> f64 {
+        if self.points.is_empty() {
+            return 0.0;
+        }
+        let invested = self.points.iter().filter(|p| p.exposure > 0.0).count();
+        invested as f64 / self.points.len() as f64 * 100.0
+    }
+
+    /// Average portfolio value over the curve
+    pub fn average_value(&self) -> f64 {
+        if self.points.is_empty() {
+            return 0.0;
+        }
+        self.points.iter().map(|p| p.value).sum::<f64>() / self.points.len() as f64
+    }
+}
+
+fn mean(values: &[f64]) -> f64 {
+    values.iter().sum::<f64>() / values.len() as f64
+}
+
+/// Lowest low and highest high seen while each position was open
+#[derive(Debug, Clone, Default)]
+pub struct ExcursionTracker {
+    extremes: HashMap<Uuid, (f64, f64)>,
+}
+
+impl ExcursionTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Widen a position's range with a candle's low/high
+    pub fn observe(&mut self, position_id: Uuid, low: f64, high: f64) {
+        let (min_low, max_high) = self.extremes.entry(position_id).or_insert((low, high));
+        *min_low = min_low.min(low);
+        *max_high = max_high.max(high);
+    }
+
+    /// (MAE %, MFE %) relative to `entry_price`; MAE is <= 0, MFE >= 0
+    pub fn excursion_pct(&self, position_id: &Uuid, entry_price: f64) -> Option<(f64, f64)> {
+        let (low, high) = self.extremes.get(position_id)?;
+        if entry_price <= 0.0 {
+            return None;
+        }
+        let mae = ((low / entry_price - 1.0) * 100.0).min(0.0);
+        let mfe = ((high / entry_price - 1.0) * 100.0).max(0.0);
+        Some((mae, mfe))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn curve(values: &[f64], exposures: &[f64]) -> EquityCurve {
+        let start = Utc::now();
+        let mut curve = EquityCurve::new();
+        for (i, (value, exposure)) in values.iter().zip(exposures).enumerate() {
+            curve.push(start + Duration::minutes(5 * i as i64), *value, *exposure);
+        }
+        curve
+    }
+
+    #[test]
+    fn test_drawdown_depth_and_duration() {
+        let curve = curve(&[100.0, 110.0, 99.0, 105.0, 112.0, 108.0], &[0.0; 6]);
+        let dd = curve.drawdown();
+
+        assert!((dd.max_drawdown - 11.0).abs() < 1e-9);
+        assert!((dd.max_drawdown_pct - 10.0).abs() < 1e-9);
+        // Below the 110 peak from t=5 until the new high at t=20
+        assert_eq!(dd.max_duration_minutes, 10);
+    }
+
+    #[test]
+    fn test_drawdown_dollar_and_pct_maxima_tracked_separately() {
+        // 50% drop from $100, then a larger $200 (only 20%) drop from $1,000
+        let curve = curve(&[100.0, 50.0, 1000.0, 800.0], &[0.0; 4]);
+        let dd = curve.drawdown();
+
+        assert!((dd.max_drawdown - 200.0).abs() < 1e-9);
+        assert!((dd.max_drawdown_pct - 50.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_sharpe_is_annualized() {
+        let curve = curve(&[100.0, 101.0, 100.5, 101.5, 101.0, 102.0], &[0.0; 6]);
+        let returns = curve.returns();
+        let mean = returns.iter().sum::<f64>() / returns.len() as f64;
+        let std =
+            (returns.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / returns.len() as f64).sqrt();
+
+        // 5-minute candles: 105,120 periods per year
+        assert!((curve.periods_per_year() - 105_120.0).abs() < 1e-9);
+        let expected = mean / std * 105_120f64.sqrt();
+        assert!((curve.sharpe_ratio() - expected).abs() < 1e-9);
+        assert!(curve.sortino_ratio() > curve.sharpe_ratio());
+    }
+
+    #[test]
+    fn test_time_in_market() {
+        let curve = curve(&[100.0; 4], &[0.0, 50.0, 50.0, 0.0]);
+        assert!((curve.time_in_market_pct() - 50.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_excursions() {
+        let mut tracker = ExcursionTracker::new();
+        let id = Uuid::new_v4();
+        tracker.observe(id, 98.0, 103.0);
+        tracker.observe(id, 95.0, 101.0);
+        tracker.observe(id, 97.0, 110.0);
+
+        let (mae, mfe) = tracker.excursion_pct(&id, 100.0).unwrap();
+        assert!((mae + 5.0).abs() < 1e-9);
+        assert!((mfe - 10.0).abs() < 1e-9);
+        assert!(tracker.excursion_pct(&Uuid::new_v4(), 100.0).is_none());
+    }
+}
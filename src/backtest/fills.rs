@@ -0,0 +1,371 @@
+use crate::models::Candle;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// When a decision made on candle N gets filled
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExecutionTiming {
+    /// Fill at the close of the signal candle (optimistic, no latency)
+    SameCandleClose,
+    /// Fill at the open of the next candle (one interval of latency)
+    NextCandleOpen,
+}
+
+impl std::str::FromStr for ExecutionTiming {
+    type Err = String;
+
+    /// Parse `next-open`/`next_candle_open` or `close`/`same_candle_close`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().replace('-', "_").as_str() {
+            "next_candle_open" | "next_open" | "next" => Ok(Self::NextCandleOpen),
+            "same_candle_close" | "close" | "same" => Ok(Self::SameCandleClose),
+            other => Err(format!(
+                "Unknown execution timing '{}' (expected next-open or close)",
+                other
+            )),
+        }
+    }
+}
+
+/// Costs and slippage applied to simulated swaps
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FillModel {
+    /// DEX swap fee per side (0.0025 = 0.25%)
+    pub swap_fee_pct: f64,
+    /// Flat priority + network fee per swap (USD)
+    pub priority_fee_usd: f64,
+    /// Slippage applied to every swap regardless of size (0.001 = 0.1%)
+    pub base_slippage_pct: f64,
+    /// Pool liquidity (USD) for size-dependent slippage; `None` = base slippage only
+    pub liquidity_usd: Option<f64>,
+    /// Pool liquidity as a multiple of each candle's volume (USD), overriding
+    /// `liquidity_usd`, so slippage explodes when volume dries up (rugs, drains)
+    pub liquidity_volume_ratio: Option<f64>,
+    /// Candles trading less than this (USD) can't fill any swap (0 = always fill)
+    pub min_fill_volume_usd: f64,
+    pub timing: ExecutionTiming,
+}
+
+impl Default for FillModel {
+    fn default() -> Self {
+        Self {
+            swap_fee_pct: 0.0025,   // 0.25% (typical Raydium/Orca pool)
+            priority_fee_usd: 0.02, // Priority fee + base fee
+            base_slippage_pct: 0.001,
+            liquidity_usd: None,
+            liquidity_volume_ratio: None,
+            min_fill_volume_usd: 0.0,
+            timing: ExecutionTiming::NextCandleOpen,
+        }
+    }
+}
+
+/// Side of a simulated swap
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FillSide {
+    Buy,
+    Sell,
+}
+
+/// Result of filling an order
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Fill {
+    /// Execution price after slippage
+    pub price: f64,
+    /// Swap fee + priority fee (USD)
+    pub fee: f64,
+    /// Cost of slippage versus the reference price (USD)
+    pub slippage: f64,
+}
+
+impl FillModel {
+    /// Fill at the reference price with no costs (the old backtest behavior)
+    pub fn frictionless() -> Self {
+        Self {
+            swap_fee_pct: 0.0,
+            priority_fee_usd: 0.0,
+            base_slippage_pct: 0.0,
+            liquidity_usd: None,
+            liquidity_volume_ratio: None,
+            min_fill_volume_usd: 0.0,
+            timing: ExecutionTiming::SameCandleClose,
+        }
+    }
+
+    /// Check that costs are finite and not negative, and that pool liquidity
+    /// (when set) is positive
+    pub fn validate(&self) -> Result<(), String> {
+        for (name, value) in [
+            ("swap_fee_pct", self.swap_fee_pct),
+            ("priority_fee_usd", self.priority_fee_usd),
+            ("base_slippage_pct", self.base_slippage_pct),
+            ("min_fill_volume_usd", self.min_fill_volume_usd),
+        ] {
+            if !value.is_finite() || value < 0.0 {
+                return Err(format!(
+                    "Invalid fill model {}: {} (expected a non-negative number)",
+                    name, value
+                ));
+            }
+        }
+        for (name, value) in [
+            ("liquidity_usd", self.liquidity_usd),
+            ("liquidity_volume_ratio", self.liquidity_volume_ratio),
+        ] {
+            if let Some(value) = value.filter(|v| !v.is_finite() || *v <= 0.0) {
+                return Err(format!(
+                    "Invalid fill model {}: {} (expected a positive number)",
+                    name, value
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Slippage fraction for an order of `order_usd`
+    ///
+    /// Size impact follows a constant-product pool where each side holds half of
+    /// the liquidity: impact = order / (reserve + order).
+    pub fn slippage_pct(&self, order_usd: f64) -> f64 {
+        let liquidity = self.liquidity_usd.filter(|l| *l > 0.0);
+        self.base_slippage_pct + price_impact_pct(order_usd, liquidity)
+    }
+
+    /// Pool liquidity (USD) while `candle` trades
+    pub fn liquidity_at(&self, candle: &Candle) -> Option<f64> {
+        match self.liquidity_volume_ratio {
+            Some(ratio) => Some(candle.volume.max(0.0) * ratio),
+            None => self.liquidity_usd,
+        }
+    }
+
+    /// Whether any swap can execute during `candle`
+    pub fn can_fill(&self, candle: &Candle) -> bool {
+        candle.volume >= self.min_fill_volume_usd
+    }
+
+    /// Fill `quantity` tokens against a reference (mid) price
+    pub fn fill(&self, side: FillSide, reference_price: f64, quantity: f64) -> Fill {
+        let notional = reference_price * quantity;
+        self.fill_with_slippage(side, reference_price, quantity, self.slippage_pct(notional))
+    }
+
+    /// Fill during `candle`, against the liquidity it leaves (`None` = no fill)
+    pub fn fill_at(
+        &self,
+        side: FillSide,
+        reference_price: f64,
+        quantity: f64,
+        candle: &Candle,
+    ) -> Option<Fill> {
+        if !self.can_fill(candle) {
+            return None;
+        }
+        let notional = reference_price * quantity;
+        let slippage_pct =
+            self.base_slippage_pct + price_impact_pct(notional, self.liquidity_at(candle));
+        Some(self.fill_with_slippage(side, reference_price, quantity, slippage_pct))
+    }
+
+    /// Fill a stop or breaker sell at its trigger price during `candle`
+    ///
+    /// The trigger level already stands in for the spread, so only the size
+    /// impact against the pool applies, which is what turns a stop into a 90%
+    /// loss when liquidity has been pulled.
+    pub fn stop_fill(&self, trigger_price: f64, quantity: f64, candle: &Candle) -> Fill {
+        let notional = trigger_price * quantity;
+        let slippage_pct = price_impact_pct(notional, self.liquidity_at(candle));
+        self.fill_with_slippage(FillSide::Sell, trigger_price, quantity, slippage_pct)
+    }
+
+    fn fill_with_slippage(
+        &self,
+        side: FillSide,
+        reference_price: f64,
+        quantity: f64,
+        slippage_pct: f64,
+    ) -> Fill {
+        let slippage_pct = slippage_pct.min(0.99);
+        let price = match side {
+            FillSide::Buy => reference_price * (1.0 + slippage_pct),
+            FillSide::Sell => reference_price * (1.0 - slippage_pct),
+        };
+
+        Fill {
+            price,
+            fee: self.fee(price * quantity),
+            slippage: (price - reference_price).abs() * quantity,
+        }
+    }
+
+    /// Fees for a swap of `notional` USD
+    pub fn fee(&self, notional: f64) -> f64 {
+        notional * self.swap_fee_pct + self.priority_fee_usd
+    }
+}
+
+/// Constant-product price impact of `order_usd` against `liquidity` (USD)
+///
+/// An empty pool absorbs the whole order (impact 100%, capped at fill time).
+fn price_impact_pct(order_usd: f64, liquidity: Option<f64>) -> f64 {
+    match liquidity {
+        Some(liquidity) if order_usd > 0.0 && liquidity >= 0.0 => {
+            let reserve = liquidity / 2.0;
+            order_usd / (reserve + order_usd)
+        }
+        _ => 0.0,
+    }
+}
+
+/// Costs paid on one round trip
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct TradeCosts {
+    pub fees: f64,
+    pub slippage: f64,
+}
+
+impl TradeCosts {
+    pub fn total(&self) -> f64 {
+        self.fees + self.slippage
+    }
+}
+
+/// Costs per position, accumulated over entry and exit fills
+#[derive(Debug, Clone, Default)]
+pub struct CostLedger {
+    costs: HashMap<Uuid, TradeCosts>,
+}
+
+impl CostLedger {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record(&mut self, position_id: Uuid, fee: f64, slippage: f64) {
+        let costs = self.costs.entry(position_id).or_default();
+        costs.fees += fee;
+        costs.slippage += slippage;
+    }
+
+    pub fn get(&self, position_id: &Uuid) -> TradeCosts {
+        self.costs.get(position_id).copied().unwrap_or_default()
+    }
+
+    pub fn into_inner(self) -> HashMap<Uuid, TradeCosts> {
+        self.costs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::CandleProvenance;
+
+    #[test]
+    fn test_frictionless_fill_is_reference_price() {
+        let fill = FillModel::frictionless().fill(FillSide::Buy, 100.0, 5.0);
+        assert_eq!(fill.price, 100.0);
+        assert_eq!(fill.fee, 0.0);
+        assert_eq!(fill.slippage, 0.0);
+    }
+
+    #[test]
+    fn test_fees_and_base_slippage() {
+        let model = FillModel::default();
+
+        let buy = model.fill(FillSide::Buy, 100.0, 5.0);
+        assert!((buy.price - 100.1).abs() < 1e-9);
+        assert!((buy.slippage - 0.5).abs() < 1e-9);
+        // 0.25% of $500.50 + $0.02
+        assert!((buy.fee - (500.5 * 0.0025 + 0.02)).abs() < 1e-9);
+
+        let sell = model.fill(FillSide::Sell, 100.0, 5.0);
+        assert!((sell.price - 99.9).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_slippage_grows_with_size_relative_to_liquidity() {
+        let model = FillModel {
+            base_slippage_pct: 0.0,
+            liquidity_usd: Some(100_000.0),
+            ..Default::default()
+        };
+
+        // $500 into a $50k reserve: 500 / 50500 ≈ 0.99%
+        let small = model.slippage_pct(500.0);
+        assert!((small - 500.0 / 50_500.0).abs() < 1e-12);
+
+        let large = model.slippage_pct(5_000.0);
+        assert!(large > small * 8.0);
+    }
+
+    fn candle(volume: f64) -> Candle {
+        Candle {
+            token: "RUG".to_string(),
+            timestamp: chrono::Utc::now(),
+            open: 1.0,
+            high: 1.0,
+            low: 1.0,
+            close: 1.0,
+            volume,
+            provenance: CandleProvenance::Live,
+        }
+    }
+
+    #[test]
+    fn test_liquidity_follows_candle_volume() {
+        let model = FillModel {
+            base_slippage_pct: 0.0,
+            liquidity_usd: Some(1_000_000.0),
+            liquidity_volume_ratio: Some(2.0),
+            ..Default::default()
+        };
+
+        // $500 against 2 x $50k volume behaves like a $100k pool
+        let liquid = model.fill_at(FillSide::Sell, 100.0, 5.0, &candle(50_000.0));
+        assert!((liquid.unwrap().price - 100.0 * (1.0 - 500.0 / 50_500.0)).abs() < 1e-9);
+
+        // A drained pool fills at a 90%+ loss instead of the stop price
+        let drained = model.stop_fill(100.0, 5.0, &candle(50.0));
+        assert!(drained.price < 10.0, "{}", drained.price);
+        assert!((drained.slippage - (100.0 - drained.price) * 5.0).abs() < 1e-9);
+
+        // An empty pool takes everything, capped at 99%
+        let empty = model.stop_fill(100.0, 5.0, &candle(0.0));
+        assert!((empty.price - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_no_fill_below_volume_floor() {
+        let model = FillModel {
+            min_fill_volume_usd: 1_000.0,
+            ..Default::default()
+        };
+        assert!(model.can_fill(&candle(1_000.0)));
+        assert!(model
+            .fill_at(FillSide::Sell, 1.0, 10.0, &candle(999.0))
+            .is_none());
+
+        // Without volume-linked liquidity, stops ignore the (absent) pool size
+        let stop = model.stop_fill(100.0, 1.0, &candle(0.0));
+        assert_eq!(stop.price, 100.0);
+        assert_eq!(stop.slippage, 0.0);
+    }
+
+    #[test]
+    fn test_cost_ledger_accumulates_per_position() {
+        let mut ledger = CostLedger::new();
+        let id = Uuid::new_v4();
+        ledger.record(id, 1.0, 0.5);
+        ledger.record(id, 1.5, 0.25);
+
+        let costs = ledger.get(&id);
+        assert_eq!(costs.fees, 2.5);
+        assert_eq!(costs.slippage, 0.75);
+        assert_eq!(costs.total(), 3.25);
+        assert_eq!(ledger.get(&Uuid::new_v4()), TradeCosts::default());
+    }
+}
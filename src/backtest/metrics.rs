@@ -1,39 +1,69 @@
+use crate::backtest::benchmark::BenchmarkStats;
+use crate::backtest::equity::{EquityCurve, ExcursionTracker};
+use crate::backtest::fills::TradeCosts;
 use crate::execution::position_manager::Position;
+use crate::risk::CircuitBreakerEvent;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use uuid::Uuid;
 
 /// Record of a single trade for analysis
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TradeRecord {
+    pub position_id: Uuid,
+    pub token: String,
     pub entry_time: DateTime<Utc>,
     pub exit_time: DateTime<Utc>,
     pub entry_price: f64,
     pub exit_price: f64,
     pub quantity: f64,
-    pub pnl: f64,
+    pub pnl: f64, // Net of fees and slippage
     pub pnl_pct: f64,
+    pub gross_pnl: f64, // Before fees and slippage
+    pub costs: f64,
     pub holding_period_minutes: i64,
+    pub mae_pct: f64, // Maximum adverse excursion (worst low vs entry, <= 0)
+    pub mfe_pct: f64, // Maximum favorable excursion (best high vs entry, >= 0)
 }
 
 impl TradeRecord {
     pub fn from_position(position: &Position) -> Option<Self> {
+        Self::from_position_with_costs(position, TradeCosts::default())
+    }
+
+    /// Build a record where `realized_pnl` already includes slippage (fills at
+    /// slipped prices) but not fees
+    pub fn from_position_with_costs(position: &Position, costs: TradeCosts) -> Option<Self> {
         if let (Some(exit_price), Some(exit_time), Some(realized_pnl)) = (
             position.exit_price,
             position.exit_time,
             position.realized_pnl,
         ) {
             let holding_period = (exit_time - position.entry_time).num_minutes();
-            let pnl_pct = ((exit_price - position.entry_price) / position.entry_price) * 100.0;
+            let pnl = realized_pnl - costs.fees;
+            let cost_basis = position.entry_price * position.quantity;
+            let pnl_pct = if cost_basis > 0.0 {
+                (pnl / cost_basis) * 100.0
+            } else {
+                0.0
+            };
 
             Some(Self {
+                position_id: position.id,
+                token: position.token.clone(),
                 entry_time: position.entry_time,
                 exit_time,
                 entry_price: position.entry_price,
                 exit_price,
                 quantity: position.quantity,
-                pnl: realized_pnl,
+                pnl,
                 pnl_pct,
+                gross_pnl: pnl + costs.total(),
+                costs: costs.total(),
                 holding_period_minutes: holding_period,
+                mae_pct: 0.0,
+                mfe_pct: 0.0,
             })
         } else {
             None
@@ -41,11 +71,33 @@ impl TradeRecord {
     }
 }
 
+/// Per-token summary of a multi-token backtest
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TokenSummary {
+    pub trades: usize,
+    pub winning_trades: usize,
+    pub pnl: f64,
+}
+
+impl TokenSummary {
+    pub fn win_rate(&self) -> f64 {
+        if self.trades == 0 {
+            0.0
+        } else {
+            (self.winning_trades as f64 / self.trades as f64) * 100.0
+        }
+    }
+}
+
 /// Complete backtest performance metrics
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BacktestMetrics {
     // P&L Metrics
-    pub total_pnl: f64,
+    pub total_pnl: f64, // Net of costs
+    pub gross_pnl: f64,
+    pub total_fees: f64,
+    pub total_slippage: f64,
+    pub total_costs: f64,
     pub total_return_pct: f64,
     pub initial_portfolio_value: f64,
     pub final_portfolio_value: f64,
@@ -61,12 +113,24 @@ pub struct BacktestMetrics {
     pub avg_loss: f64,
     pub largest_win: f64,
     pub largest_loss: f64,
+    #[serde(deserialize_with = "null_as_infinity")]
     pub profit_factor: f64, // Total wins / Total losses
 
-    // Risk Metrics
+    // Risk Metrics (marked to market each candle once an equity curve is attached)
     pub max_drawdown: f64,
     pub max_drawdown_pct: f64,
-    pub sharpe_ratio: f64,
+    pub max_drawdown_duration_minutes: i64,
+    pub sharpe_ratio: f64, // Annualized
+    pub sortino_ratio: f64,
+    #[serde(deserialize_with = "null_as_infinity")]
+    pub calmar_ratio: f64,
+    #[serde(deserialize_with = "null_as_infinity")]
+    pub annualized_return_pct: f64,
+    pub trade_sharpe_ratio: f64, // Per-trade returns, not annualized
+
+    // Exposure
+    pub time_in_market_pct: f64,
+    pub turnover: f64, // Traded notional / average portfolio value
 
     // Holding Period
     pub avg_holding_period_minutes: f64,
@@ -74,23 +138,64 @@ pub struct BacktestMetrics {
     pub min_holding_period_minutes: i64,
 
     // Circuit Breakers
-    pub circuit_breaker_hits: usize,
+    pub circuit_breaker_events: Vec<CircuitBreakerEvent>,
+
+    // Execution failures (no liquidity to fill)
+    #[serde(default)]
+    pub failed_exits: usize,
+    #[serde(default)]
+    pub written_off_positions: usize,
+
+    // Benchmarks over the same candles (token buy-and-hold, SOL, ...)
+    #[serde(default)]
+    pub benchmarks: Vec<BenchmarkStats>,
 
     // Trade Records
     pub trades: Vec<TradeRecord>,
+    pub equity_curve: EquityCurve,
+}
+
+/// JSON has no infinity: serde_json writes it as `null`, so read `null` back as +inf
+fn null_as_infinity<'de, D>(deserializer: D) -> Result<f64, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    Ok(Option::<f64>::deserialize(deserializer)?.unwrap_or(f64::INFINITY))
 }
 
 impl BacktestMetrics {
-    /// Calculate metrics from completed positions
+    /// Calculate metrics from completed positions (no trading costs)
     pub fn from_positions(
         positions: Vec<Position>,
         initial_portfolio_value: f64,
         final_portfolio_value: f64,
-        circuit_breaker_hits: usize,
+        circuit_breaker_events: Vec<CircuitBreakerEvent>,
+    ) -> Self {
+        Self::from_positions_with_costs(
+            positions,
+            &HashMap::new(),
+            initial_portfolio_value,
+            final_portfolio_value,
+            circuit_breaker_events,
+        )
+    }
+
+    /// Calculate metrics from completed positions and their fill costs
+    pub fn from_positions_with_costs(
+        positions: Vec<Position>,
+        costs: &HashMap<Uuid, TradeCosts>,
+        initial_portfolio_value: f64,
+        final_portfolio_value: f64,
+        circuit_breaker_events: Vec<CircuitBreakerEvent>,
     ) -> Self {
         let trades: Vec<TradeRecord> = positions
             .iter()
-            .filter_map(TradeRecord::from_position)
+            .filter_map(|p| {
+                TradeRecord::from_position_with_costs(
+                    p,
+                    costs.get(&p.id).copied().unwrap_or_default(),
+                )
+            })
             .collect();
 
         let total_trades = trades.len();
@@ -99,12 +204,20 @@ impl BacktestMetrics {
             return Self::empty(
                 initial_portfolio_value,
                 final_portfolio_value,
-                circuit_breaker_hits,
+                circuit_breaker_events,
             );
         }
 
         // P&L calculations
         let total_pnl: f64 = trades.iter().map(|t| t.pnl).sum();
+        let gross_pnl: f64 = trades.iter().map(|t| t.gross_pnl).sum();
+        let closed_costs: Vec<TradeCosts> = positions
+            .iter()
+            .filter(|p| p.exit_price.is_some())
+            .map(|p| costs.get(&p.id).copied().unwrap_or_default())
+            .collect();
+        let total_fees: f64 = closed_costs.iter().map(|c| c.fees).sum();
+        let total_slippage: f64 = closed_costs.iter().map(|c| c.slippage).sum();
         let total_return_pct =
             ((final_portfolio_value - initial_portfolio_value) / initial_portfolio_value) * 100.0;
 
@@ -162,8 +275,8 @@ impl BacktestMetrics {
         let (max_drawdown, max_drawdown_pct) =
             Self::calculate_drawdown(&trades, initial_portfolio_value);
 
-        // Sharpe ratio (simplified - using trade returns)
-        let sharpe_ratio = Self::calculate_sharpe_ratio(&trades);
+        // Per-trade Sharpe (time-based ratios need an equity curve)
+        let trade_sharpe_ratio = Self::calculate_sharpe_ratio(&trades);
 
         // Holding periods
         let holding_periods: Vec<i64> = trades.iter().map(|t| t.holding_period_minutes).collect();
@@ -178,6 +291,10 @@ impl BacktestMetrics {
 
         Self {
             total_pnl,
+            gross_pnl,
+            total_fees,
+            total_slippage,
+            total_costs: total_fees + total_slippage,
             total_return_pct,
             initial_portfolio_value,
             final_portfolio_value,
@@ -192,12 +309,23 @@ impl BacktestMetrics {
             profit_factor,
             max_drawdown,
             max_drawdown_pct,
-            sharpe_ratio,
+            max_drawdown_duration_minutes: 0,
+            sharpe_ratio: 0.0,
+            sortino_ratio: 0.0,
+            calmar_ratio: 0.0,
+            annualized_return_pct: 0.0,
+            trade_sharpe_ratio,
+            time_in_market_pct: 0.0,
+            turnover: 0.0,
             avg_holding_period_minutes,
             max_holding_period_minutes,
             min_holding_period_minutes,
-            circuit_breaker_hits,
+            circuit_breaker_events,
+            failed_exits: 0,
+            written_off_positions: 0,
+            benchmarks: vec![],
             trades,
+            equity_curve: EquityCurve::new(),
         }
     }
 
@@ -205,10 +333,14 @@ impl BacktestMetrics {
     fn empty(
         initial_portfolio_value: f64,
         final_portfolio_value: f64,
-        circuit_breaker_hits: usize,
+        circuit_breaker_events: Vec<CircuitBreakerEvent>,
     ) -> Self {
         Self {
             total_pnl: 0.0,
+            gross_pnl: 0.0,
+            total_fees: 0.0,
+            total_slippage: 0.0,
+            total_costs: 0.0,
             total_return_pct: 0.0,
             initial_portfolio_value,
             final_portfolio_value,
@@ -223,13 +355,101 @@ impl BacktestMetrics {
             profit_factor: 0.0,
             max_drawdown: 0.0,
             max_drawdown_pct: 0.0,
+            max_drawdown_duration_minutes: 0,
             sharpe_ratio: 0.0,
+            sortino_ratio: 0.0,
+            calmar_ratio: 0.0,
+            annualized_return_pct: 0.0,
+            trade_sharpe_ratio: 0.0,
+            time_in_market_pct: 0.0,
+            turnover: 0.0,
             avg_holding_period_minutes: 0.0,
             max_holding_period_minutes: 0,
             min_holding_period_minutes: 0,
-            circuit_breaker_hits,
+            circuit_breaker_events,
+            failed_exits: 0,
+            written_off_positions: 0,
+            benchmarks: vec![],
             trades: vec![],
+            equity_curve: EquityCurve::new(),
+        }
+    }
+
+    /// Attach a per-candle equity curve and derive the time-based metrics
+    ///
+    /// Drawdown is re-measured on the marked-to-market curve, so it includes
+    /// unrealized losses that the trade-by-trade estimate misses.
+    pub fn with_equity_curve(mut self, curve: EquityCurve) -> Self {
+        if curve.len() >= 2 {
+            let drawdown = curve.drawdown();
+            self.max_drawdown = drawdown.max_drawdown;
+            self.max_drawdown_pct = drawdown.max_drawdown_pct;
+            self.max_drawdown_duration_minutes = drawdown.max_duration_minutes;
+            self.sharpe_ratio = curve.sharpe_ratio();
+            self.sortino_ratio = curve.sortino_ratio();
+            self.calmar_ratio = curve.calmar_ratio();
+            self.annualized_return_pct = curve.annualized_return() * 100.0;
+            self.time_in_market_pct = curve.time_in_market_pct();
+
+            let traded_notional: f64 = self
+                .trades
+                .iter()
+                .map(|t| (t.entry_price + t.exit_price) * t.quantity)
+                .sum();
+            let average_value = curve.average_value();
+            self.turnover = if average_value > 0.0 {
+                traded_notional / average_value
+            } else {
+                0.0
+            };
+        }
+        self.equity_curve = curve;
+        self
+    }
+
+    /// Record exits that couldn't fill and positions written off at the end
+    pub fn with_execution_failures(mut self, failed_exits: usize, written_off: usize) -> Self {
+        self.failed_exits = failed_exits;
+        self.written_off_positions = written_off;
+        self
+    }
+
+    /// Compare the equity curve to a benchmark series (skipped without overlap)
+    pub fn with_benchmark(mut self, name: &str, series: &[(DateTime<Utc>, f64)]) -> Self {
+        if let Some(stats) = BenchmarkStats::compare(name, &self.equity_curve, series) {
+            self.benchmarks.push(stats);
+        }
+        self
+    }
+
+    /// Benchmark stats by name
+    pub fn benchmark(&self, name: &str) -> Option<&BenchmarkStats> {
+        self.benchmarks.iter().find(|b| b.name == name)
+    }
+
+    /// Fill in per-trade MAE/MFE from tracked price extremes
+    pub fn with_excursions(mut self, excursions: &ExcursionTracker) -> Self {
+        for trade in &mut self.trades {
+            if let Some((mae, mfe)) =
+                excursions.excursion_pct(&trade.position_id, trade.entry_price)
+            {
+                trade.mae_pct = mae;
+                trade.mfe_pct = mfe;
+            }
+        }
+        self
+    }
+
+    /// Average (MAE %, MFE %) across trades
+    pub fn average_excursions(&self) -> (f64, f64) {
+        if self.trades.is_empty() {
+            return (0.0, 0.0);
         }
+        let n = self.trades.len() as f64;
+        (
+            self.trades.iter().map(|t| t.mae_pct).sum::<f64>() / n,
+            self.trades.iter().map(|t| t.mfe_pct).sum::<f64>() / n,
+        )
     }
 
     /// Calculate maximum drawdown from trades
@@ -290,6 +510,39 @@ impl BacktestMetrics {
         }
     }
 
+    /// Trades and P&L grouped by token (sorted by symbol)
+    pub fn by_token(&self) -> BTreeMap<String, TokenSummary> {
+        let mut summaries: BTreeMap<String, TokenSummary> = BTreeMap::new();
+        for trade in &self.trades {
+            let summary = summaries.entry(trade.token.clone()).or_default();
+            summary.trades += 1;
+            summary.pnl += trade.pnl;
+            if trade.pnl > 0.0 {
+                summary.winning_trades += 1;
+            }
+        }
+        summaries
+    }
+
+    /// Print per-token P&L (for multi-token backtests)
+    pub fn print_token_breakdown(&self) {
+        println!("📋 BY TOKEN");
+        println!(
+            "  {:<12} {:>8} {:>8} {:>12}",
+            "Token", "Trades", "Win%", "P&L"
+        );
+        for (token, summary) in self.by_token() {
+            println!(
+                "  {:<12} {:>8} {:>8.1} {:>12.2}",
+                token,
+                summary.trades,
+                summary.win_rate(),
+                summary.pnl
+            );
+        }
+        println!();
+    }
+
     /// Print a formatted report to stdout
     pub fn print_report(&self) {
         println!("\n╔═══════════════════════════════════════════════════════╗");
@@ -309,6 +562,13 @@ impl BacktestMetrics {
             "  Total P&L:             ${:.2} ({:+.2}%)",
             self.total_pnl, self.total_return_pct
         );
+        if self.total_costs > 0.0 {
+            println!("  Gross P&L:             ${:.2}", self.gross_pnl);
+            println!(
+                "  Costs:                 ${:.2} (fees ${:.2}, slippage ${:.2})",
+                self.total_costs, self.total_fees, self.total_slippage
+            );
+        }
 
         println!("\n📈 TRADE STATISTICS");
         println!("  Total Trades:          {}", self.total_trades);
@@ -331,7 +591,28 @@ impl BacktestMetrics {
                 "  Max Drawdown:          ${:.2} ({:.2}%)",
                 self.max_drawdown, self.max_drawdown_pct
             );
-            println!("  Sharpe Ratio:          {:.2}", self.sharpe_ratio);
+            if !self.equity_curve.is_empty() {
+                println!(
+                    "  Drawdown Duration:     {:.1} hours",
+                    self.max_drawdown_duration_minutes as f64 / 60.0
+                );
+                println!(
+                    "  Sharpe Ratio:          {:.2} (annualized)",
+                    self.sharpe_ratio
+                );
+                println!("  Sortino Ratio:         {:.2}", self.sortino_ratio);
+                println!(
+                    "  Calmar Ratio:          {:.2} ({:+.1}% annualized)",
+                    self.calmar_ratio, self.annualized_return_pct
+                );
+            }
+            println!("  Per-Trade Sharpe:      {:.2}", self.trade_sharpe_ratio);
+
+            println!("\n📐 EXPOSURE");
+            println!("  Time in Market:        {:.1}%", self.time_in_market_pct);
+            println!("  Turnover:              {:.2}x", self.turnover);
+            let (mae, mfe) = self.average_excursions();
+            println!("  Avg MAE / MFE:         {:.2}% / {:+.2}%", mae, mfe);
 
             println!("\n⏱️  HOLDING PERIODS");
             println!(
@@ -354,8 +635,44 @@ impl BacktestMetrics {
         println!("\n🛑 CIRCUIT BREAKERS");
         println!(
             "  Triggered:             {} times",
-            self.circuit_breaker_hits
+            self.circuit_breaker_events.len()
         );
+        for event in &self.circuit_breaker_events {
+            println!(
+                "  {} {:?} (value ${:.2}, drawdown {:.1}%, daily P&L ${:.2}, {} losses in a row)",
+                event.timestamp.format("%Y-%m-%d %H:%M"),
+                event.trip,
+                event.portfolio_value,
+                event.drawdown_pct() * 100.0,
+                event.daily_pnl,
+                event.consecutive_losses
+            );
+        }
+
+        if !self.benchmarks.is_empty() {
+            println!("\n📏 BENCHMARKS");
+            println!(
+                "  {:<14} {:>9} {:>9} {:>8} {:>6} {:>6}",
+                "Benchmark", "Return%", "Excess%", "Alpha%", "Beta", "IR"
+            );
+            for b in &self.benchmarks {
+                println!(
+                    "  {:<14} {:>+9.2} {:>+9.2} {:>+8.1} {:>6.2} {:>6.2}",
+                    b.name,
+                    b.return_pct,
+                    b.excess_return_pct,
+                    b.alpha * 100.0,
+                    b.beta,
+                    b.information_ratio
+                );
+            }
+        }
+
+        if self.failed_exits > 0 || self.written_off_positions > 0 {
+            println!("\n🚫 EXECUTION FAILURES");
+            println!("  Failed Exits:          {}", self.failed_exits);
+            println!("  Written Off:           {}", self.written_off_positions);
+        }
 
         println!("\n═══════════════════════════════════════════════════════\n");
     }
@@ -399,7 +716,7 @@ mod tests {
             create_test_position(-30.0, 90), // $30 loss
         ];
 
-        let metrics = BacktestMetrics::from_positions(positions, 10000.0, 10120.0, 0);
+        let metrics = BacktestMetrics::from_positions(positions, 10000.0, 10120.0, vec![]);
 
         assert_eq!(metrics.total_trades, 3);
         assert_eq!(metrics.winning_trades, 2);
@@ -411,7 +728,7 @@ mod tests {
     #[test]
     fn test_metrics_with_no_trades() {
         let positions = vec![];
-        let metrics = BacktestMetrics::from_positions(positions, 10000.0, 10000.0, 0);
+        let metrics = BacktestMetrics::from_positions(positions, 10000.0, 10000.0, vec![]);
 
         assert_eq!(metrics.total_trades, 0);
         assert_eq!(metrics.win_rate, 0.0);
@@ -426,12 +743,64 @@ mod tests {
             create_test_position(-50.0, 60), // $50 loss
         ];
 
-        let metrics = BacktestMetrics::from_positions(positions, 10000.0, 10250.0, 0);
+        let metrics = BacktestMetrics::from_positions(positions, 10000.0, 10250.0, vec![]);
 
         // Profit factor = Total wins / Total losses = 300 / 50 = 6.0
         assert!((metrics.profit_factor - 6.0).abs() < 0.01);
     }
 
+    #[test]
+    fn test_costs_split_gross_and_net() {
+        let winner = create_test_position(100.0, 60);
+        let loser = create_test_position(-30.0, 60);
+
+        let mut costs = HashMap::new();
+        costs.insert(
+            winner.id,
+            TradeCosts {
+                fees: 2.0,
+                slippage: 1.0,
+            },
+        );
+        costs.insert(
+            loser.id,
+            TradeCosts {
+                fees: 1.0,
+                slippage: 0.5,
+            },
+        );
+
+        let metrics = BacktestMetrics::from_positions_with_costs(
+            vec![winner, loser],
+            &costs,
+            10000.0,
+            10067.0,
+            vec![],
+        );
+
+        // Net = realized P&L (already slipped) - fees
+        assert!((metrics.total_pnl - 67.0).abs() < 1e-9);
+        assert!((metrics.total_costs - 4.5).abs() < 1e-9);
+        assert!((metrics.gross_pnl - (metrics.total_pnl + metrics.total_costs)).abs() < 1e-9);
+        assert!((metrics.trades[0].pnl - 98.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_json_round_trip_keeps_infinite_profit_factor() {
+        let positions = vec![create_test_position(100.0, 60)];
+        let metrics = BacktestMetrics::from_positions(positions, 10000.0, 10100.0, vec![]);
+        assert_eq!(metrics.profit_factor, f64::INFINITY);
+
+        let json = serde_json::to_string(&metrics).unwrap();
+        let restored: BacktestMetrics = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.profit_factor, f64::INFINITY);
+        assert_eq!(restored.total_trades, 1);
+        assert_eq!(
+            restored.trades[0].position_id,
+            metrics.trades[0].position_id
+        );
+    }
+
     #[test]
     fn test_drawdown_calculation() {
         let positions = vec![
@@ -440,7 +809,7 @@ mod tests {
             create_test_position(50.0, 60),   // Back to 9950
         ];
 
-        let metrics = BacktestMetrics::from_positions(positions, 10000.0, 9950.0, 0);
+        let metrics = BacktestMetrics::from_positions(positions, 10000.0, 9950.0, vec![]);
 
         assert!((metrics.max_drawdown - 200.0).abs() < 0.01);
     }
@@ -1,7 +1,27 @@
+pub mod batch;
+pub mod benchmark;
+pub mod equity;
+pub mod fills;
 pub mod metrics;
+pub mod monte_carlo;
+pub mod portfolio;
+pub mod price_models;
+pub mod registry;
+pub mod report;
 pub mod runner;
+pub mod spec;
 pub mod synthetic;
 
-pub use metrics::{BacktestMetrics, TradeRecord};
+pub use batch::{BacktestJob, BatchExecutor, BatchResult};
+pub use benchmark::BenchmarkStats;
+pub use equity::{EquityCurve, EquityPoint};
+pub use fills::{ExecutionTiming, FillModel, TradeCosts};
+pub use metrics::{BacktestMetrics, TokenSummary, TradeRecord};
+pub use monte_carlo::{MonteCarloConfig, MonteCarloResult, Resampling};
+pub use portfolio::{AlignedSeries, PortfolioBacktestRunner};
+pub use price_models::{ModelKind, PriceModel, SyntheticModel};
+pub use registry::{BacktestRun, BacktestRunSummary, DataRange};
+pub use report::{BacktestReport, ReportFormat};
 pub use runner::BacktestRunner;
-pub use synthetic::{MarketScenario, SyntheticDataGenerator};
+pub use spec::{BacktestSpec, StrategyKind};
+pub use synthetic::{MarketScenario, SyntheticDataGenerator, SyntheticSource};
@@ -0,0 +1,388 @@
+use crate::backtest::batch::BatchExecutor;
+use crate::backtest::metrics::TradeRecord;
+use crate::backtest::portfolio::PortfolioBacktestRunner;
+use crate::backtest::synthetic::{SyntheticDataGenerator, SyntheticSource};
+use crate::strategy::Strategy;
+use crate::Result;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+
+/// Percentiles shown in reports
+pub const REPORT_PERCENTILES: [f64; 5] = [5.0, 25.0, 50.0, 75.0, 95.0];
+
+/// Settings shared by every robustness analysis
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MonteCarloConfig {
+    /// Resampled paths (or seeds) per analysis
+    pub iterations: usize,
+    pub seed: u64,
+    /// A path is ruined once its drawdown reaches this percentage
+    pub ruin_drawdown_pct: f64,
+}
+
+impl Default for MonteCarloConfig {
+    fn default() -> Self {
+        Self {
+            iterations: 1000,
+            seed: 42,
+            ruin_drawdown_pct: 50.0,
+        }
+    }
+}
+
+/// How trade sequences are resampled
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Resampling {
+    /// Draw trades with replacement (varies both outcome and ordering)
+    Bootstrap,
+    /// Permute the same trades (same final P&L, different path and drawdown)
+    Shuffle,
+}
+
+impl fmt::Display for Resampling {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Bootstrap => write!(f, "bootstrap"),
+            Self::Shuffle => write!(f, "shuffle"),
+        }
+    }
+}
+
+/// Sorted sample of one outcome across paths
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Distribution {
+    samples: Vec<f64>,
+}
+
+impl Distribution {
+    pub fn new(mut samples: Vec<f64>) -> Self {
+        samples.retain(|s| s.is_finite());
+        samples.sort_by(|a, b| a.total_cmp(b));
+        Self { samples }
+    }
+
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    /// Linearly interpolated percentile (`p` in 0..=100)
+    pub fn percentile(&self, p: f64) -> f64 {
+        match self.samples.len() {
+            0 => 0.0,
+            1 => self.samples[0],
+            n => {
+                let rank = (p.clamp(0.0, 100.0) / 100.0) * (n - 1) as f64;
+                let lower = rank.floor() as usize;
+                let upper = rank.ceil() as usize;
+                let weight = rank - lower as f64;
+                self.samples[lower] * (1.0 - weight) + self.samples[upper] * weight
+            }
+        }
+    }
+
+    pub fn mean(&self) -> f64 {
+        if self.samples.is_empty() {
+            return 0.0;
+        }
+        self.samples.iter().sum::<f64>() / self.samples.len() as f64
+    }
+
+    pub fn min(&self) -> f64 {
+        self.samples.first().copied().unwrap_or(0.0)
+    }
+
+    pub fn max(&self) -> f64 {
+        self.samples.last().copied().unwrap_or(0.0)
+    }
+
+    /// Fraction of samples strictly below `threshold`
+    pub fn fraction_below(&self, threshold: f64) -> f64 {
+        if self.samples.is_empty() {
+            return 0.0;
+        }
+        let count = self.samples.partition_point(|s| *s < threshold);
+        count as f64 / self.samples.len() as f64
+    }
+}
+
+/// Spread of outcomes across resampled paths or seeds
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MonteCarloResult {
+    /// What was varied (e.g. "bootstrap", "shuffle", "seeds 0..100 (uptrend)")
+    pub method: String,
+    pub iterations: usize,
+    /// Total return per path (%)
+    pub returns_pct: Distribution,
+    /// Max drawdown per path (%)
+    pub max_drawdowns_pct: Distribution,
+    /// Share of paths whose drawdown reached `ruin_drawdown_pct`
+    pub ruin_probability: f64,
+    pub ruin_drawdown_pct: f64,
+}
+
+impl MonteCarloResult {
+    fn from_paths(method: String, paths: &[PathOutcome], ruin_drawdown_pct: f64) -> Self {
+        let ruined = paths
+            .iter()
+            .filter(|p| p.max_drawdown_pct >= ruin_drawdown_pct)
+            .count();
+        Self {
+            method,
+            iterations: paths.len(),
+            returns_pct: Distribution::new(paths.iter().map(|p| p.return_pct).collect()),
+            max_drawdowns_pct: Distribution::new(
+                paths.iter().map(|p| p.max_drawdown_pct).collect(),
+            ),
+            ruin_probability: if paths.is_empty() {
+                0.0
+            } else {
+                ruined as f64 / paths.len() as f64
+            },
+            ruin_drawdown_pct,
+        }
+    }
+
+    /// Probability that a path ends with a loss
+    pub fn loss_probability(&self) -> f64 {
+        self.returns_pct.fraction_below(0.0)
+    }
+
+    pub fn print_report(&self) {
+        println!(
+            "\n🎲 MONTE CARLO: {} ({} paths)",
+            self.method, self.iterations
+        );
+        print!("  {:<16}", "Percentile");
+        for p in REPORT_PERCENTILES {
+            print!(" {:>9}", format!("p{}", p));
+        }
+        println!(" {:>9}", "mean");
+
+        for (label, distribution) in [
+            ("Return %", &self.returns_pct),
+            ("Max drawdown %", &self.max_drawdowns_pct),
+        ] {
+            print!("  {:<16}", label);
+            for p in REPORT_PERCENTILES {
+                print!(" {:>9.2}", distribution.percentile(p));
+            }
+            println!(" {:>9.2}", distribution.mean());
+        }
+
+        println!(
+            "  P(loss): {:.1}%  |  P(ruin, drawdown >= {:.0}%): {:.1}%",
+            self.loss_probability() * 100.0,
+            self.ruin_drawdown_pct,
+            self.ruin_probability * 100.0
+        );
+    }
+}
+
+/// Return and drawdown of one simulated path
+#[derive(Debug, Clone, Copy)]
+struct PathOutcome {
+    return_pct: f64,
+    max_drawdown_pct: f64,
+}
+
+/// Replay trade P&Ls (in dollars, closed trades only) on `initial_value`
+fn replay_pnls(pnls: impl Iterator<Item = f64>, initial_value: f64) -> PathOutcome {
+    let mut value = initial_value;
+    let mut peak = initial_value;
+    let mut max_drawdown_pct: f64 = 0.0;
+
+    for pnl in pnls {
+        value += pnl;
+        peak = peak.max(value);
+        if peak > 0.0 {
+            max_drawdown_pct = max_drawdown_pct.max((peak - value) / peak * 100.0);
+        }
+    }
+
+    PathOutcome {
+        return_pct: (value - initial_value) / initial_value * 100.0,
+        max_drawdown_pct,
+    }
+}
+
+/// Resample a backtest's trade sequence into `config.iterations` alternative paths
+///
+/// Trade P&Ls are applied in dollars to `initial_value` (no compounding), so
+/// drawdowns are measured between closed trades, not marked to market.
+pub fn resample_trades(
+    trades: &[TradeRecord],
+    initial_value: f64,
+    method: Resampling,
+    config: &MonteCarloConfig,
+) -> MonteCarloResult {
+    let mut rng = StdRng::seed_from_u64(config.seed);
+    let pnls: Vec<f64> = trades.iter().map(|t| t.pnl).collect();
+
+    let paths: Vec<PathOutcome> = if pnls.is_empty() {
+        Vec::new()
+    } else {
+        (0..config.iterations)
+            .map(|_| match method {
+                Resampling::Bootstrap => replay_pnls(
+                    (0..pnls.len()).map(|_| pnls[rng.gen_range(0..pnls.len())]),
+                    initial_value,
+                ),
+                Resampling::Shuffle => {
+                    let mut order = pnls.clone();
+                    order.shuffle(&mut rng);
+                    replay_pnls(order.into_iter(), initial_value)
+                }
+            })
+            .collect()
+    };
+
+    MonteCarloResult::from_paths(method.to_string(), &paths, config.ruin_drawdown_pct)
+}
+
+/// Backtest `strategy` on `config.iterations` synthetic paths of `source`
+/// (seeds `config.seed..config.seed + iterations`)
+///
+/// Drawdowns come from each run's mark-to-market equity curve. Seeds run in
+/// parallel on `executor`; results are collected in seed order, so the
+/// distribution doesn't depend on the thread count.
+pub fn seed_sweep<S: Strategy + ?Sized>(
+    executor: &BatchExecutor,
+    runner: &PortfolioBacktestRunner,
+    strategy: &S,
+    source: &SyntheticSource,
+    num_candles: usize,
+    config: &MonteCarloConfig,
+) -> Result<MonteCarloResult> {
+    let seeds = config.seed..config.seed + config.iterations as u64;
+    let seed_list: Vec<u64> = seeds.clone().collect();
+    let outcomes = executor.map(&seed_list, |seed| {
+        let mut generator = SyntheticDataGenerator::new(*seed);
+        let candles = generator.generate_source(source, None, num_candles, 5);
+        let series = HashMap::from([("SYNTH".to_string(), candles)]);
+        runner.run(strategy, &series).map(|metrics| PathOutcome {
+            return_pct: metrics.total_return_pct,
+            max_drawdown_pct: metrics.max_drawdown_pct,
+        })
+    });
+    let paths = outcomes.into_iter().collect::<Result<Vec<_>>>()?;
+
+    Ok(MonteCarloResult::from_paths(
+        format!("seeds {}..{} ({})", seeds.start, seeds.end, source.name()),
+        &paths,
+        config.ruin_drawdown_pct,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backtest::synthetic::MarketScenario;
+    use crate::risk::CircuitBreakers;
+    use crate::strategy::buy_and_hold::BuyAndHoldStrategy;
+    use chrono::Utc;
+    use uuid::Uuid;
+
+    fn trade(pnl: f64) -> TradeRecord {
+        TradeRecord {
+            position_id: Uuid::new_v4(),
+            token: "SOL".to_string(),
+            entry_time: Utc::now(),
+            exit_time: Utc::now(),
+            entry_price: 100.0,
+            exit_price: 100.0,
+            quantity: 1.0,
+            pnl,
+            pnl_pct: 0.0,
+            gross_pnl: pnl,
+            costs: 0.0,
+            holding_period_minutes: 5,
+            mae_pct: 0.0,
+            mfe_pct: 0.0,
+        }
+    }
+
+    #[test]
+    fn test_percentiles_interpolate() {
+        let distribution = Distribution::new(vec![4.0, 1.0, 3.0, 2.0, 5.0, f64::NAN]);
+        assert_eq!(distribution.len(), 5);
+        assert_eq!(distribution.percentile(0.0), 1.0);
+        assert_eq!(distribution.percentile(50.0), 3.0);
+        assert_eq!(distribution.percentile(100.0), 5.0);
+        assert!((distribution.percentile(25.0) - 2.0).abs() < 1e-12);
+        assert!((distribution.percentile(10.0) - 1.4).abs() < 1e-12);
+        assert_eq!(distribution.fraction_below(2.0), 0.2);
+    }
+
+    #[test]
+    fn test_shuffle_keeps_total_return_but_varies_drawdown() {
+        let trades: Vec<TradeRecord> = [300.0, -200.0, 150.0, -400.0, 500.0, -100.0]
+            .into_iter()
+            .map(trade)
+            .collect();
+        let config = MonteCarloConfig {
+            iterations: 200,
+            ..Default::default()
+        };
+
+        let result = resample_trades(&trades, 10000.0, Resampling::Shuffle, &config);
+        assert_eq!(result.iterations, 200);
+        // Net P&L is +250 on every permutation
+        assert!((result.returns_pct.min() - 2.5).abs() < 1e-9);
+        assert!((result.returns_pct.max() - 2.5).abs() < 1e-9);
+        assert!(result.max_drawdowns_pct.max() > result.max_drawdowns_pct.min());
+        assert_eq!(result.ruin_probability, 0.0);
+    }
+
+    #[test]
+    fn test_bootstrap_spreads_returns_and_detects_ruin() {
+        let trades: Vec<TradeRecord> = [2000.0, -3000.0, 1000.0, -2500.0]
+            .into_iter()
+            .map(trade)
+            .collect();
+        let config = MonteCarloConfig {
+            iterations: 500,
+            seed: 7,
+            ruin_drawdown_pct: 50.0,
+        };
+
+        let result = resample_trades(&trades, 10000.0, Resampling::Bootstrap, &config);
+        assert!(result.returns_pct.min() < result.returns_pct.max());
+        assert!(result.ruin_probability > 0.0 && result.ruin_probability < 1.0);
+        assert!(result.loss_probability() > 0.5);
+
+        // Same seed, same paths
+        let again = resample_trades(&trades, 10000.0, Resampling::Bootstrap, &config);
+        assert_eq!(result, again);
+    }
+
+    #[test]
+    fn test_seed_sweep_runs_one_backtest_per_seed() {
+        let runner = PortfolioBacktestRunner::new(10000.0, CircuitBreakers::default());
+        let config = MonteCarloConfig {
+            iterations: 5,
+            ..Default::default()
+        };
+
+        let result = seed_sweep(
+            &BatchExecutor::new(),
+            &runner,
+            &BuyAndHoldStrategy,
+            &SyntheticSource::Scenario(MarketScenario::Volatile),
+            200,
+            &config,
+        )
+        .unwrap();
+        assert_eq!(result.iterations, 5);
+        assert_eq!(result.method, "seeds 42..47 (volatile)");
+        assert!(result.returns_pct.min() < result.returns_pct.max());
+    }
+}
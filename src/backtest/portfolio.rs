@@ -0,0 +1,931 @@
+use crate::backtest::benchmark::{
+    close_series, equal_weight_series, BenchmarkSeries, BUY_AND_HOLD,
+};
+use crate::backtest::equity::{EquityCurve, ExcursionTracker};
+use crate::backtest::fills::{CostLedger, ExecutionTiming, FillModel, FillSide};
+use crate::backtest::metrics::BacktestMetrics;
+use crate::execution::{ExecutionAction, Executor, ExitReason, PositionManager};
+use crate::models::Candle;
+use crate::risk::{
+    BookPosition, CircuitBreakerTrip, CircuitBreakers, CorrelationDecision, CorrelationLimits,
+    PositionSizing,
+};
+use crate::strategy::signals::validate_candle_uniformity;
+use crate::strategy::Strategy;
+use crate::Result;
+use chrono::{DateTime, Utc};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::sync::{Arc, Mutex};
+use uuid::Uuid;
+
+/// Spacing of the candles the backtest assumes (5 minutes, like the live loop)
+const CANDLE_INTERVAL_SECS: u64 = 300;
+
+/// Candle series aligned on the timestamps shared by every token
+#[derive(Debug, Clone)]
+pub struct AlignedSeries {
+    pub timestamps: Vec<DateTime<Utc>>,
+    /// Token -> candles, one per timestamp (same length as `timestamps`)
+    pub candles: BTreeMap<String, Vec<Candle>>,
+}
+
+impl AlignedSeries {
+    /// Keep only the timestamps present in every series
+    ///
+    /// Tokens are ordered by symbol so replays are deterministic.
+    pub fn align(series: &HashMap<String, Vec<Candle>>) -> Result<Self> {
+        if series.is_empty() {
+            return Err("No candle series to align".into());
+        }
+
+        let mut common: Option<BTreeSet<DateTime<Utc>>> = None;
+        for candles in series.values() {
+            let timestamps: BTreeSet<DateTime<Utc>> = candles.iter().map(|c| c.timestamp).collect();
+            common = Some(match common {
+                Some(existing) => existing.intersection(&timestamps).copied().collect(),
+                None => timestamps,
+            });
+        }
+        let timestamps: Vec<DateTime<Utc>> = common.unwrap_or_default().into_iter().collect();
+
+        let candles = series
+            .iter()
+            .map(|(token, candles)| {
+                let by_ts: HashMap<DateTime<Utc>, &Candle> =
+                    candles.iter().map(|c| (c.timestamp, c)).collect();
+                let aligned = timestamps.iter().map(|ts| by_ts[ts].clone()).collect();
+                (token.clone(), aligned)
+            })
+            .collect();
+
+        Ok(Self {
+            timestamps,
+            candles,
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.timestamps.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.timestamps.is_empty()
+    }
+}
+
+/// Orders that couldn't execute because the pool had no liquidity
+#[derive(Debug, Clone, Copy, Default)]
+struct ExecutionFailures {
+    /// Exits (stops, sells, flattens) that found no fill
+    exits: usize,
+    /// Positions still unsellable at the end of the run, closed at zero
+    written_off: usize,
+}
+
+/// Backtests several tokens at once with shared capital and risk limits
+///
+/// Mirrors the live trading loop: one `PositionManager`, one set of circuit
+/// breakers, every token evaluated on each tick.
+pub struct PortfolioBacktestRunner {
+    initial_portfolio_value: f64,
+    circuit_breakers: CircuitBreakers,
+    sizing: PositionSizing,
+    correlation_limits: Option<CorrelationLimits>,
+    fill_model: FillModel,
+    /// Extra benchmark series (e.g. SOL) besides the tokens' own buy-and-hold
+    benchmarks: Vec<(String, BenchmarkSeries)>,
+}
+
+impl PortfolioBacktestRunner {
+    pub fn new(initial_portfolio_value: f64, circuit_breakers: CircuitBreakers) -> Self {
+        Self {
+            initial_portfolio_value,
+            circuit_breakers,
+            sizing: PositionSizing::default(),
+            correlation_limits: None,
+            fill_model: FillModel::default(),
+            benchmarks: Vec::new(),
+        }
+    }
+
+    /// Set fees, slippage and execution timing (default: `FillModel::default()`)
+    pub fn with_fill_model(mut self, fill_model: FillModel) -> Self {
+        self.fill_model = fill_model;
+        self
+    }
+
+    /// Set the position sizing method used by the simulated executor
+    pub fn with_sizing(mut self, sizing: PositionSizing) -> Self {
+        self.sizing = sizing;
+        self
+    }
+
+    /// Apply correlation/concentration limits to new entries (off by default)
+    pub fn with_correlation_limits(mut self, limits: CorrelationLimits) -> Self {
+        self.correlation_limits = Some(limits);
+        self
+    }
+
+    /// Also compare every run against buy-and-hold of `candles` (e.g. SOL)
+    pub fn with_benchmark(mut self, name: &str, candles: &[Candle]) -> Self {
+        self.benchmarks
+            .push((name.to_string(), close_series(candles)));
+        self
+    }
+
+    /// Runner and strategy parameters, as recorded in the backtest registry
+    pub fn config<S: Strategy + ?Sized>(&self, strategy: &S) -> serde_json::Value {
+        serde_json::json!({
+            "initial_portfolio_value": self.initial_portfolio_value,
+            "circuit_breakers": self.circuit_breakers,
+            "sizing": self.sizing,
+            "correlation_limits": self.correlation_limits,
+            "fill_model": self.fill_model,
+            "benchmarks": self.benchmarks.iter().map(|(name, _)| name).collect::<Vec<_>>(),
+            "strategy": strategy.parameters(),
+        })
+    }
+
+    /// Replay all series tick by tick on their common timestamps
+    pub fn run<S: Strategy + ?Sized>(
+        &self,
+        strategy: &S,
+        series: &HashMap<String, Vec<Candle>>,
+    ) -> Result<BacktestMetrics> {
+        let aligned = AlignedSeries::align(series)?;
+        let samples_needed = strategy.samples_needed(5); // Assume 5 min intervals
+
+        if aligned.len() < samples_needed {
+            return Err(format!(
+                "Not enough aligned candles for portfolio backtest. Need {}, got {}",
+                samples_needed,
+                aligned.len()
+            )
+            .into());
+        }
+
+        tracing::info!(
+            "Starting portfolio backtest: {} tokens, {} aligned candles",
+            aligned.candles.len(),
+            aligned.len()
+        );
+
+        let position_manager = Arc::new(Mutex::new(PositionManager::new(
+            self.initial_portfolio_value,
+            self.circuit_breakers.clone(),
+        )));
+        let mut executor = Executor::new(position_manager.clone()).with_sizing(self.sizing.clone());
+        let mut ledger = CostLedger::new();
+        let mut equity = EquityCurve::new();
+        let mut excursions = ExcursionTracker::new();
+        let mut pending: Vec<(String, ExecutionAction)> = Vec::new();
+        let mut failures = ExecutionFailures::default();
+
+        for i in samples_needed..aligned.len() {
+            let prices: HashMap<String, f64> = aligned
+                .candles
+                .iter()
+                .map(|(token, candles)| (token.clone(), candles[i].close))
+                .collect();
+
+            {
+                let mut pm = position_manager.lock().unwrap();
+                pm.set_time(aligned.timestamps[i]);
+
+                // Orders decided on the previous candle fill at this candle's open
+                for (token, action) in pending.drain(..) {
+                    let bar = &aligned.candles[&token][i];
+                    self.fill_action(
+                        &mut pm,
+                        &mut ledger,
+                        &mut failures,
+                        &token,
+                        action,
+                        bar,
+                        bar.open,
+                    );
+                }
+
+                // Exits and circuit breakers first, across the whole book.
+                // Stops are checked against each candle's high/low, not just the close
+                let bars: HashMap<String, Candle> = aligned
+                    .candles
+                    .iter()
+                    .map(|(token, candles)| (token.clone(), candles[i].clone()))
+                    .collect();
+                let held: Vec<(Uuid, String)> = pm
+                    .open_positions()
+                    .iter()
+                    .map(|p| (p.id, p.token.clone()))
+                    .collect();
+                self.check_stop_exits(&mut pm, &mut ledger, &mut failures, &bars);
+
+                // Track excursions; a stopped-out position never saw this candle's
+                // high (the low comes first), only the path from the open to its fill
+                for (position_id, token) in held {
+                    let bar = &bars[&token];
+                    let exit_price = pm
+                        .all_positions()
+                        .iter()
+                        .find(|p| p.id == position_id)
+                        .and_then(|p| p.exit_price);
+                    match exit_price {
+                        Some(exit) => excursions.observe(position_id, exit, bar.open),
+                        None => excursions.observe(position_id, bar.low, bar.high),
+                    }
+                }
+
+                // A flatten sells into each pool like a stop; illiquid tokens stay stuck
+                let exit_prices = self.breaker_exit_prices(&pm, &bars);
+                if let Ok(Some((event, closed_ids))) =
+                    pm.check_circuit_breakers_with_exits(&prices, &exit_prices)
+                {
+                    tracing::debug!("Circuit breaker {:?} at {}", event.trip, event.timestamp);
+                    self.charge_exit_costs(&mut pm, &mut ledger, &closed_ids, &prices);
+                    if event.trip == CircuitBreakerTrip::MaxDrawdown
+                        && self.circuit_breakers.actions.flatten_on_max_drawdown
+                    {
+                        failures.exits += pm.open_positions().len();
+                    }
+                }
+            }
+
+            // Shared lookback windows for this tick
+            let windows: HashMap<&str, &[Candle]> = aligned
+                .candles
+                .iter()
+                .map(|(token, candles)| (token.as_str(), &candles[i - samples_needed..=i]))
+                .collect();
+
+            for token in aligned.candles.keys() {
+                let lookback = windows[token.as_str()];
+
+                // Same data quality gate as the live engine: no signals across gaps
+                if validate_candle_uniformity(lookback, CANDLE_INTERVAL_SECS).is_err() {
+                    continue;
+                }
+
+                let signal = match strategy.generate_signal(lookback) {
+                    Ok(signal) => signal,
+                    Err(e) => {
+                        tracing::warn!("Failed to generate signal for {}: {}", token, e);
+                        continue;
+                    }
+                };
+
+                let decision = match executor.process_signal_with_candles(&signal, token, lookback)
+                {
+                    Ok(decision) => decision,
+                    Err(e) => {
+                        tracing::warn!("Failed to process signal for {}: {}", token, e);
+                        continue;
+                    }
+                };
+
+                let mut pm = position_manager.lock().unwrap();
+                let action = match decision.action {
+                    ExecutionAction::Execute { quantity } => {
+                        let quantity = self
+                            .apply_correlation_limits(&pm, &pending, token, quantity, &aligned, i);
+                        if quantity <= 0.0 {
+                            continue;
+                        }
+                        ExecutionAction::Execute { quantity }
+                    }
+                    ExecutionAction::Skip => continue,
+                    close => close,
+                };
+
+                match self.fill_model.timing {
+                    ExecutionTiming::SameCandleClose => {
+                        let bar = &aligned.candles[token][i];
+                        self.fill_action(
+                            &mut pm,
+                            &mut ledger,
+                            &mut failures,
+                            token,
+                            action,
+                            bar,
+                            bar.close,
+                        );
+                    }
+                    ExecutionTiming::NextCandleOpen => pending.push((token.clone(), action)),
+                }
+            }
+
+            let pm = position_manager.lock().unwrap();
+            let exposure: f64 = pm
+                .open_positions()
+                .iter()
+                .filter_map(|p| prices.get(&p.token).map(|price| p.quantity * price))
+                .sum();
+            let value = pm
+                .portfolio_value(&prices)
+                .unwrap_or(self.initial_portfolio_value);
+            equity.push(aligned.timestamps[i], value, exposure);
+        }
+
+        // Close any remaining open positions at their final prices
+        // (orders still pending after the last candle are dropped). A position
+        // that can't be sold on the last candle is written off at zero
+        let final_bars: HashMap<String, Candle> = aligned
+            .candles
+            .iter()
+            .map(|(token, candles)| (token.clone(), candles[candles.len() - 1].clone()))
+            .collect();
+        let final_prices: HashMap<String, f64> = final_bars
+            .iter()
+            .map(|(token, bar)| (token.clone(), bar.close))
+            .collect();
+
+        let mut pm = position_manager.lock().unwrap();
+        let open: Vec<(Uuid, String)> = pm
+            .open_positions()
+            .iter()
+            .map(|p| (p.id, p.token.clone()))
+            .collect();
+        for (position_id, token) in open {
+            let bar = &final_bars[&token];
+            if !self.fill_model.can_fill(bar) {
+                tracing::debug!("Writing off {} position: no liquidity to exit", token);
+                if pm
+                    .close_position(position_id, 0.0, ExitReason::Manual)
+                    .is_ok()
+                {
+                    failures.written_off += 1;
+                }
+                continue;
+            }
+            let close = ExecutionAction::Close {
+                position_id,
+                exit_reason: ExitReason::Manual,
+            };
+            self.fill_action(
+                &mut pm,
+                &mut ledger,
+                &mut failures,
+                &token,
+                close,
+                bar,
+                bar.close,
+            );
+        }
+
+        let final_portfolio_value = pm
+            .portfolio_value(&final_prices)
+            .unwrap_or(self.initial_portfolio_value);
+
+        // The last mark should include the costs of the closing fills
+        if let Some(last) = equity.points.last_mut() {
+            last.value = final_portfolio_value;
+        }
+
+        let mut metrics = BacktestMetrics::from_positions_with_costs(
+            pm.all_positions().to_vec(),
+            &ledger.into_inner(),
+            self.initial_portfolio_value,
+            final_portfolio_value,
+            pm.breaker_events().to_vec(),
+        )
+        .with_equity_curve(equity)
+        .with_excursions(&excursions)
+        .with_execution_failures(failures.exits, failures.written_off)
+        .with_benchmark(BUY_AND_HOLD, &equal_weight_series(&aligned.candles));
+        for (name, series) in &self.benchmarks {
+            metrics = metrics.with_benchmark(name, series);
+        }
+
+        tracing::info!(
+            "Portfolio backtest complete: {} trades, P&L: ${:.2} ({:.2}%)",
+            metrics.total_trades,
+            metrics.total_pnl,
+            metrics.total_return_pct
+        );
+
+        Ok(metrics)
+    }
+
+    /// Execute an order through the fill model at `reference_price` during `bar`
+    ///
+    /// Orders on candles without enough volume don't fill: entries are dropped
+    /// and exits count as failed (the position stays open).
+    #[allow(clippy::too_many_arguments)]
+    fn fill_action(
+        &self,
+        pm: &mut PositionManager,
+        ledger: &mut CostLedger,
+        failures: &mut ExecutionFailures,
+        token: &str,
+        action: ExecutionAction,
+        bar: &Candle,
+        reference_price: f64,
+    ) {
+        match action {
+            ExecutionAction::Execute { quantity } => {
+                let Some(fill) =
+                    self.fill_model
+                        .fill_at(FillSide::Buy, reference_price, quantity, bar)
+                else {
+                    tracing::debug!("No liquidity to buy {} at {}", token, bar.timestamp);
+                    return;
+                };
+                match pm.open_position(token.to_string(), fill.price, quantity) {
+                    Ok(position_id) => {
+                        pm.record_fee(fill.fee);
+                        ledger.record(position_id, fill.fee, fill.slippage);
+                    }
+                    Err(e) => tracing::debug!("Failed to open position: {}", e),
+                }
+            }
+            ExecutionAction::Close {
+                position_id,
+                exit_reason,
+            } => {
+                let Some(quantity) = pm
+                    .open_positions()
+                    .iter()
+                    .find(|p| p.id == position_id)
+                    .map(|p| p.quantity)
+                else {
+                    return; // Already closed (e.g., stopped out while the order was pending)
+                };
+                let Some(fill) =
+                    self.fill_model
+                        .fill_at(FillSide::Sell, reference_price, quantity, bar)
+                else {
+                    tracing::debug!("No liquidity to sell {} at {}", token, bar.timestamp);
+                    failures.exits += 1;
+                    return;
+                };
+                if pm
+                    .close_position(position_id, fill.price, exit_reason)
+                    .is_ok()
+                {
+                    pm.record_fee(fill.fee);
+                    ledger.record(position_id, fill.fee, fill.slippage);
+                }
+            }
+            ExecutionAction::Skip => {}
+        }
+    }
+
+    /// Stop, trailing and time exits against each token's candle
+    ///
+    /// Stops trigger at their level (or the gapped open) and then sell into the
+    /// pool, paying fees plus size impact. Positions whose candle can't fill
+    /// stay open; a stop that was hit but couldn't sell counts as a failed exit.
+    fn check_stop_exits(
+        &self,
+        pm: &mut PositionManager,
+        ledger: &mut CostLedger,
+        failures: &mut ExecutionFailures,
+        bars: &HashMap<String, Candle>,
+    ) {
+        let open: Vec<(Uuid, String, f64, f64)> = pm
+            .open_positions()
+            .iter()
+            .map(|p| {
+                let level = p.take_profit.unwrap_or(0.0).max(p.stop_loss);
+                (p.id, p.token.clone(), p.quantity, level)
+            })
+            .collect();
+
+        for (position_id, token, quantity, level) in open {
+            let Some(bar) = bars.get(&token) else {
+                continue;
+            };
+            if !self.fill_model.can_fill(bar) {
+                if bar.low <= level {
+                    failures.exits += 1;
+                }
+                continue;
+            }
+
+            match pm.should_exit_intrabar(position_id, bar) {
+                Ok(Some((reason, trigger_price))) => {
+                    let fill = self.fill_model.stop_fill(trigger_price, quantity, bar);
+                    if pm.close_position(position_id, fill.price, reason).is_ok() {
+                        pm.record_fee(fill.fee);
+                        ledger.record(position_id, fill.fee, fill.slippage);
+                    }
+                }
+                Ok(None) => {}
+                Err(e) => tracing::debug!("Failed to check exit for {}: {}", token, e),
+            }
+        }
+    }
+
+    /// Prices a breaker flatten would get: each token's close minus the size
+    /// impact of selling the position (tokens that can't fill are left out)
+    fn breaker_exit_prices(
+        &self,
+        pm: &PositionManager,
+        bars: &HashMap<String, Candle>,
+    ) -> HashMap<String, f64> {
+        let held: HashMap<&str, f64> = pm
+            .open_positions()
+            .iter()
+            .map(|p| (p.token.as_str(), p.quantity))
+            .collect();
+
+        bars.iter()
+            .filter(|(_, bar)| self.fill_model.can_fill(bar))
+            .map(|(token, bar)| {
+                let price = match held.get(token.as_str()) {
+                    Some(quantity) => self.fill_model.stop_fill(bar.close, *quantity, bar).price,
+                    None => bar.close,
+                };
+                (token.clone(), price)
+            })
+            .collect()
+    }
+
+    /// Charge swap fees and price impact on positions closed by a breaker flatten
+    fn charge_exit_costs(
+        &self,
+        pm: &mut PositionManager,
+        ledger: &mut CostLedger,
+        ids: &[Uuid],
+        prices: &HashMap<String, f64>,
+    ) {
+        for id in ids {
+            let exit = pm
+                .all_positions()
+                .iter()
+                .find(|p| p.id == *id)
+                .and_then(|p| {
+                    p.exit_price
+                        .map(|price| (price, p.quantity, prices.get(&p.token).copied()))
+                });
+            if let Some((price, quantity, mark)) = exit {
+                let fee = self.fill_model.fee(price * quantity);
+                let slippage = mark.map_or(0.0, |mark| (mark - price).max(0.0) * quantity);
+                pm.record_fee(fee);
+                ledger.record(*id, fee, slippage);
+            }
+        }
+    }
+
+    /// Scale a new entry by the correlation check (quantity 0 = blocked)
+    ///
+    /// Correlations use the last `period` returns up to tick `i`, independent of
+    /// the strategy's own lookback. Entries still waiting to fill count as part
+    /// of the book so two clones can't both slip in on the same candle.
+    fn apply_correlation_limits(
+        &self,
+        pm: &PositionManager,
+        pending: &[(String, ExecutionAction)],
+        token: &str,
+        quantity: f64,
+        aligned: &AlignedSeries,
+        i: usize,
+    ) -> f64 {
+        let Some(limits) = &self.correlation_limits else {
+            return quantity;
+        };
+
+        let start = (i + 1).saturating_sub(limits.period + 1);
+        let window = |symbol: &str| aligned.candles.get(symbol).map(|c| &c[start..=i]);
+
+        let pending_entries = pending.iter().filter_map(|(token, action)| match action {
+            ExecutionAction::Execute { quantity } => Some((token.as_str(), *quantity)),
+            _ => None,
+        });
+        let book: Vec<BookPosition> = pm
+            .open_positions()
+            .into_iter()
+            .map(|p| (p.token.as_str(), p.quantity))
+            .chain(pending_entries)
+            .filter_map(|(token, quantity)| {
+                let candles = window(token)?;
+                Some(BookPosition {
+                    token,
+                    value: quantity * candles.last()?.close,
+                    candles,
+                })
+            })
+            .collect();
+
+        let Some(candidate) = window(token) else {
+            return quantity;
+        };
+        let price = candidate[candidate.len() - 1].close;
+        let portfolio_value = pm.trading_state().portfolio_value;
+
+        match limits.check(candidate, quantity * price, &book, portfolio_value) {
+            CorrelationDecision::Allow => quantity,
+            CorrelationDecision::Downsize { factor, .. } => quantity * factor,
+            CorrelationDecision::Block { reason } => {
+                tracing::debug!("Entry for {} blocked: {}", token, reason);
+                0.0
+            }
+        }
+    }
+
+    /// Run the portfolio backtest and print report (with per-token breakdown)
+    pub fn run_and_report<S: Strategy + ?Sized>(
+        &self,
+        strategy: &S,
+        series: &HashMap<String, Vec<Candle>>,
+        scenario_name: &str,
+    ) -> Result<BacktestMetrics> {
+        let metrics = self.run(strategy, series)?;
+        self.print_report(strategy, series.len(), scenario_name, &metrics);
+
+        Ok(metrics)
+    }
+
+    /// Print the report of a finished run (e.g. one executed in a batch)
+    pub fn print_report<S: Strategy + ?Sized>(
+        &self,
+        strategy: &S,
+        tokens: usize,
+        scenario_name: &str,
+        metrics: &BacktestMetrics,
+    ) {
+        println!("\n🔬 Portfolio backtest: {}", scenario_name);
+        println!("   Strategy: {}", strategy.name());
+        println!("   Tokens: {}", tokens);
+        println!("   Initial Portfolio: ${:.2}", self.initial_portfolio_value);
+        println!("   Position Sizing: {}", self.sizing.name());
+
+        metrics.print_report();
+        metrics.print_token_breakdown();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backtest::synthetic::{MarketScenario, SyntheticDataGenerator};
+    use crate::models::Signal;
+    use chrono::Duration;
+
+    /// Buys every token as soon as it has `samples` candles of history
+    struct AlwaysBuy {
+        samples: usize,
+    }
+
+    impl Strategy for AlwaysBuy {
+        fn generate_signal(&self, _candles: &[Candle]) -> Result<Signal> {
+            Ok(Signal::Buy)
+        }
+
+        fn name(&self) -> &str {
+            "AlwaysBuy"
+        }
+
+        fn min_candles_required(&self) -> usize {
+            self.samples
+        }
+    }
+
+    fn series(
+        scenarios: &[(&str, MarketScenario, u64)],
+        count: usize,
+    ) -> HashMap<String, Vec<Candle>> {
+        let start = Utc::now() - Duration::minutes(5 * count as i64);
+        scenarios
+            .iter()
+            .map(|(token, scenario, seed)| {
+                let mut gen = SyntheticDataGenerator::new(*seed);
+                let candles = gen.generate_at(*scenario, start, count, 5);
+                (token.to_string(), candles)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_align_keeps_common_timestamps() {
+        let mut data = series(
+            &[
+                ("AAA", MarketScenario::Sideways, 1),
+                ("BBB", MarketScenario::Sideways, 2),
+            ],
+            100,
+        );
+        // Drop BBB's first 10 candles and one in the middle
+        let bbb = data.get_mut("BBB").unwrap();
+        bbb.drain(..10);
+        bbb.remove(40);
+
+        let aligned = AlignedSeries::align(&data).unwrap();
+        assert_eq!(aligned.len(), 89);
+        assert_eq!(aligned.candles["AAA"].len(), 89);
+        assert_eq!(
+            aligned.candles["AAA"][0].timestamp,
+            aligned.candles["BBB"][0].timestamp
+        );
+    }
+
+    #[test]
+    fn test_portfolio_shares_capital_across_tokens() {
+        let data = series(
+            &[
+                ("AAA", MarketScenario::Uptrend, 1),
+                ("BBB", MarketScenario::Downtrend, 2),
+                ("CCC", MarketScenario::Sideways, 3),
+            ],
+            300,
+        );
+
+        let runner = PortfolioBacktestRunner::new(10000.0, CircuitBreakers::default());
+        let metrics = runner.run(&AlwaysBuy { samples: 50 }, &data).unwrap();
+
+        // Every token is traded on the same shared capital
+        assert!(metrics.total_trades >= 3);
+        let tokens: BTreeSet<&str> = metrics.trades.iter().map(|t| t.token.as_str()).collect();
+        assert_eq!(tokens.len(), 3);
+
+        let breakdown = metrics.by_token();
+        let pnl_sum: f64 = breakdown.values().map(|s| s.pnl).sum();
+        assert!((pnl_sum - metrics.total_pnl).abs() < 1e-6);
+        assert!((metrics.final_portfolio_value - (10000.0 + metrics.total_pnl)).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_correlation_limits_block_clones() {
+        // Two identical series: the second entry is perfectly correlated with the first
+        let data = series(
+            &[
+                ("AAA", MarketScenario::Volatile, 7),
+                ("BBB", MarketScenario::Volatile, 7),
+            ],
+            300,
+        );
+
+        let runner = PortfolioBacktestRunner::new(10000.0, CircuitBreakers::default())
+            .with_correlation_limits(CorrelationLimits::default());
+        let metrics = runner.run(&AlwaysBuy { samples: 50 }, &data).unwrap();
+
+        assert!(metrics.trades.iter().all(|t| t.token == "AAA"));
+
+        // Without limits both clones are bought
+        let runner = PortfolioBacktestRunner::new(10000.0, CircuitBreakers::default());
+        let metrics = runner.run(&AlwaysBuy { samples: 50 }, &data).unwrap();
+        assert!(metrics.trades.iter().any(|t| t.token == "BBB"));
+    }
+
+    #[test]
+    fn test_fill_costs_reduce_net_pnl() {
+        let data = series(&[("AAA", MarketScenario::Sideways, 3)], 300);
+        let strategy = AlwaysBuy { samples: 50 };
+
+        let frictionless = PortfolioBacktestRunner::new(10000.0, CircuitBreakers::default())
+            .with_fill_model(FillModel::frictionless())
+            .run(&strategy, &data)
+            .unwrap();
+        assert_eq!(frictionless.total_costs, 0.0);
+
+        let costed = PortfolioBacktestRunner::new(10000.0, CircuitBreakers::default())
+            .run(&strategy, &data)
+            .unwrap();
+        assert!(costed.total_fees > 0.0);
+        assert!(costed.total_slippage > 0.0);
+        assert!((costed.gross_pnl - costed.total_costs - costed.total_pnl).abs() < 1e-6);
+        assert!((costed.final_portfolio_value - (10000.0 + costed.total_pnl)).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_equity_curve_and_excursions() {
+        let data = series(
+            &[
+                ("AAA", MarketScenario::Volatile, 4),
+                ("BBB", MarketScenario::Sideways, 5),
+            ],
+            300,
+        );
+        let runner = PortfolioBacktestRunner::new(10000.0, CircuitBreakers::default());
+        let metrics = runner.run(&AlwaysBuy { samples: 50 }, &data).unwrap();
+
+        // One mark per simulated candle, ending at the final portfolio value
+        assert_eq!(metrics.equity_curve.len(), 300 - 50);
+        let last = metrics.equity_curve.points.last().unwrap();
+        assert!((last.value - metrics.final_portfolio_value).abs() < 1e-9);
+
+        assert!(metrics.time_in_market_pct > 0.0);
+        assert!(metrics.turnover > 0.0);
+        assert!(metrics.max_drawdown_pct >= 0.0);
+        for trade in &metrics.trades {
+            assert!(trade.mae_pct <= 0.0 && trade.mfe_pct >= 0.0);
+        }
+    }
+
+    #[test]
+    fn test_no_signals_across_data_gaps() {
+        let mut data = series(&[("AAA", MarketScenario::Sideways, 3)], 300);
+        // A 10-minute hole between candles 19 and 20: windows up to candle 69 span it
+        data.get_mut("AAA").unwrap().remove(20);
+
+        let runner = PortfolioBacktestRunner::new(10000.0, CircuitBreakers::default());
+        let metrics = runner.run(&AlwaysBuy { samples: 50 }, &data).unwrap();
+
+        let first_entry = metrics.trades.iter().map(|t| t.entry_time).min().unwrap();
+        assert!(first_entry > data["AAA"][70].timestamp);
+    }
+
+    #[test]
+    fn test_rug_pull_stop_fills_at_gapped_open() {
+        let data = series(&[("RUG", MarketScenario::RugPull, 1)], 500);
+        let rug_time = data["RUG"][350].timestamp;
+
+        let runner = PortfolioBacktestRunner::new(10000.0, CircuitBreakers::default());
+        let metrics = runner.run(&AlwaysBuy { samples: 300 }, &data).unwrap();
+
+        // The stop can't save the position: it fills at the collapsed open
+        let rugged = metrics
+            .trades
+            .iter()
+            .find(|t| t.exit_time == rug_time)
+            .expect("position held through the rug");
+        assert!(rugged.pnl_pct < -90.0, "{}", rugged.pnl_pct);
+        assert_eq!(metrics.failed_exits, 0);
+    }
+
+    #[test]
+    fn test_rug_pull_without_fills_leaves_position_stuck() {
+        let data = series(&[("RUG", MarketScenario::RugPull, 1)], 500);
+        let fills = FillModel {
+            min_fill_volume_usd: 10_000.0,
+            ..FillModel::default()
+        };
+
+        let runner = PortfolioBacktestRunner::new(10000.0, CircuitBreakers::default())
+            .with_fill_model(fills);
+        let metrics = runner.run(&AlwaysBuy { samples: 300 }, &data).unwrap();
+
+        // The stop keeps firing but nothing fills; the position is written off
+        assert!(metrics.failed_exits > 100, "{}", metrics.failed_exits);
+        assert_eq!(metrics.written_off_positions, 1);
+        let last = metrics.trades.last().unwrap();
+        assert_eq!(last.exit_price, 0.0);
+        assert!(last.pnl_pct <= -100.0);
+    }
+
+    #[test]
+    fn test_drained_pool_stop_pays_extreme_slippage() {
+        let data = series(&[("DRAIN", MarketScenario::LiquidityDrain, 1)], 500);
+        let fills = FillModel {
+            liquidity_volume_ratio: Some(1.0),
+            ..FillModel::default()
+        };
+        let breakers = CircuitBreakers {
+            max_consecutive_losses: 1000,
+            max_daily_trades: 1000,
+            ..CircuitBreakers::default()
+        };
+
+        let runner = PortfolioBacktestRunner::new(10000.0, breakers).with_fill_model(fills);
+        let metrics = runner.run(&AlwaysBuy { samples: 50 }, &data).unwrap();
+
+        // Late exits sell into an almost empty pool
+        let worst = metrics
+            .trades
+            .iter()
+            .map(|t| t.pnl_pct)
+            .fold(f64::INFINITY, f64::min);
+        assert!(worst < -90.0, "{}", worst);
+        assert!(metrics.total_slippage > 0.0);
+    }
+
+    #[test]
+    fn test_metrics_carry_benchmarks() {
+        let data = series(
+            &[
+                ("AAA", MarketScenario::Uptrend, 1),
+                ("BBB", MarketScenario::Sideways, 2),
+            ],
+            300,
+        );
+        let start = data["AAA"][0].timestamp;
+        let sol =
+            SyntheticDataGenerator::new(3).generate_at(MarketScenario::Volatile, start, 300, 5);
+
+        let runner = PortfolioBacktestRunner::new(10000.0, CircuitBreakers::default())
+            .with_benchmark("SOL", &sol);
+        let metrics = runner.run(&AlwaysBuy { samples: 50 }, &data).unwrap();
+
+        let names: Vec<&str> = metrics.benchmarks.iter().map(|b| b.name.as_str()).collect();
+        assert_eq!(names, vec![BUY_AND_HOLD, "SOL"]);
+
+        // Always invested in both tokens: tracks the basket closely
+        let basket = metrics.benchmark(BUY_AND_HOLD).unwrap();
+        assert!(basket.beta > 0.0);
+        assert!(basket.correlation > 0.5, "{}", basket.correlation);
+        assert!(
+            (metrics.total_return_pct - basket.return_pct - basket.excess_return_pct).abs() < 1.0
+        );
+    }
+
+    #[test]
+    fn test_requires_enough_aligned_data() {
+        let data = series(&[("AAA", MarketScenario::Sideways, 1)], 10);
+        let runner = PortfolioBacktestRunner::new(10000.0, CircuitBreakers::default());
+        assert!(runner
+            .run(
+                &crate::strategy::momentum::MomentumStrategy::default(),
+                &data
+            )
+            .is_err());
+    }
+}
@@ -0,0 +1,784 @@
+use crate::models::{Candle, CandleProvenance};
+use crate::Result;
+use chrono::{DateTime, Duration, Utc};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// Fewer candles than this can't support a meaningful fit
+pub const MIN_FIT_CANDLES: usize = 50;
+
+/// Returns further than this many robust standard deviations from the median
+/// are treated as jumps when fitting jump diffusion
+const JUMP_THRESHOLD_SIGMAS: f64 = 4.0;
+
+/// Return-generating process for model-based synthetic data
+///
+/// Every parameter is per candle and in log-return space (0.01 ≈ 1% per candle).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "model", rename_all = "snake_case")]
+pub enum PriceModel {
+    /// Geometric Brownian motion: constant drift and volatility
+    Gbm { drift: f64, volatility: f64 },
+
+    /// GARCH(1,1): `variance = omega + alpha * shock² + beta * variance`,
+    /// so calm and turbulent stretches cluster like real markets
+    Garch {
+        drift: f64,
+        omega: f64,
+        alpha: f64,
+        beta: f64,
+    },
+
+    /// Merton jump diffusion: GBM plus rare jumps (memecoin pumps and rugs)
+    JumpDiffusion {
+        drift: f64,
+        volatility: f64,
+        /// Chance of a jump on any candle
+        jump_probability: f64,
+        jump_mean: f64,
+        jump_volatility: f64,
+    },
+
+    /// Markov regime switching: each regime lasts `mean_duration` candles on
+    /// average, then hands over to the next one in the list (wrapping around)
+    RegimeSwitching { regimes: Vec<Regime> },
+}
+
+/// One state of a regime-switching model
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Regime {
+    pub name: String,
+    pub drift: f64,
+    pub volatility: f64,
+    pub mean_duration: f64,
+}
+
+/// Model families that can be generated and fitted
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ModelKind {
+    Gbm,
+    Garch,
+    JumpDiffusion,
+    RegimeSwitching,
+}
+
+impl ModelKind {
+    pub const ALL: [ModelKind; 4] = [
+        Self::Gbm,
+        Self::Garch,
+        Self::JumpDiffusion,
+        Self::RegimeSwitching,
+    ];
+}
+
+impl fmt::Display for ModelKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Gbm => "gbm",
+            Self::Garch => "garch",
+            Self::JumpDiffusion => "jump-diffusion",
+            Self::RegimeSwitching => "regime-switching",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for ModelKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let name = s.trim().to_lowercase().replace('_', "-");
+        match name.as_str() {
+            "jump" | "jumps" => return Ok(Self::JumpDiffusion),
+            "regime" | "regimes" => return Ok(Self::RegimeSwitching),
+            _ => {}
+        }
+        Self::ALL
+            .into_iter()
+            .find(|kind| kind.to_string() == name)
+            .ok_or_else(|| {
+                format!(
+                    "Unknown price model '{}' (expected gbm, garch, jump-diffusion or regime-switching)",
+                    s
+                )
+            })
+    }
+}
+
+/// Simulation state carried from one candle to the next
+#[derive(Debug, Clone, Copy)]
+struct ModelState {
+    variance: f64,
+    regime: usize,
+}
+
+impl PriceModel {
+    /// Memecoin-like defaults for 5-minute candles (about 1% volatility per candle)
+    pub fn preset(kind: ModelKind) -> Self {
+        match kind {
+            ModelKind::Gbm => Self::Gbm {
+                drift: 0.0,
+                volatility: 0.01,
+            },
+            ModelKind::Garch => Self::Garch {
+                drift: 0.0,
+                omega: 0.0001 * (1.0 - 0.1 - 0.85),
+                alpha: 0.1,
+                beta: 0.85,
+            },
+            ModelKind::JumpDiffusion => Self::JumpDiffusion {
+                drift: 0.0,
+                volatility: 0.008,
+                jump_probability: 0.005,
+                jump_mean: 0.0,
+                jump_volatility: 0.15,
+            },
+            ModelKind::RegimeSwitching => Self::RegimeSwitching {
+                regimes: vec![
+                    Regime {
+                        name: "bull".to_string(),
+                        drift: 0.002,
+                        volatility: 0.008,
+                        mean_duration: 150.0,
+                    },
+                    Regime {
+                        name: "crash".to_string(),
+                        drift: -0.01,
+                        volatility: 0.03,
+                        mean_duration: 20.0,
+                    },
+                    Regime {
+                        name: "chop".to_string(),
+                        drift: 0.0,
+                        volatility: 0.005,
+                        mean_duration: 200.0,
+                    },
+                ],
+            },
+        }
+    }
+
+    pub fn kind(&self) -> ModelKind {
+        match self {
+            Self::Gbm { .. } => ModelKind::Gbm,
+            Self::Garch { .. } => ModelKind::Garch,
+            Self::JumpDiffusion { .. } => ModelKind::JumpDiffusion,
+            Self::RegimeSwitching { .. } => ModelKind::RegimeSwitching,
+        }
+    }
+
+    /// Long-run standard deviation of per-candle returns
+    pub fn volatility(&self) -> f64 {
+        match self {
+            Self::Gbm { volatility, .. } => *volatility,
+            Self::Garch {
+                omega, alpha, beta, ..
+            } => (omega / (1.0 - alpha - beta).max(1e-6)).sqrt(),
+            Self::JumpDiffusion {
+                volatility,
+                jump_probability,
+                jump_mean,
+                jump_volatility,
+                ..
+            } => (volatility.powi(2)
+                + jump_probability * (jump_mean.powi(2) + jump_volatility.powi(2)))
+            .sqrt(),
+            Self::RegimeSwitching { regimes } => {
+                let total: f64 = regimes.iter().map(|r| r.mean_duration).sum();
+                if total <= 0.0 {
+                    return 0.0;
+                }
+                let variance: f64 = regimes
+                    .iter()
+                    .map(|r| r.volatility.powi(2) * r.mean_duration)
+                    .sum();
+                (variance / total).sqrt()
+            }
+        }
+    }
+
+    fn initial_state(&self) -> ModelState {
+        ModelState {
+            variance: self.volatility().powi(2),
+            regime: 0,
+        }
+    }
+
+    /// Draw the next log return; also returns the candle's conditional volatility
+    fn next_return<R: Rng + ?Sized>(&self, state: &mut ModelState, rng: &mut R) -> (f64, f64) {
+        match self {
+            Self::Gbm { drift, volatility } => {
+                (drift + volatility * standard_normal(rng), *volatility)
+            }
+            Self::Garch {
+                drift,
+                omega,
+                alpha,
+                beta,
+            } => {
+                let sigma = state.variance.sqrt();
+                let shock = sigma * standard_normal(rng);
+                state.variance = omega + alpha * shock.powi(2) + beta * state.variance;
+                (drift + shock, sigma)
+            }
+            Self::JumpDiffusion {
+                drift,
+                volatility,
+                jump_probability,
+                jump_mean,
+                jump_volatility,
+            } => {
+                let mut r = drift + volatility * standard_normal(rng);
+                let mut sigma = *volatility;
+                if rng.gen_bool(jump_probability.clamp(0.0, 1.0)) {
+                    let jump = jump_mean + jump_volatility * standard_normal(rng);
+                    r += jump;
+                    sigma = sigma.max(jump.abs());
+                }
+                (r, sigma)
+            }
+            Self::RegimeSwitching { regimes } => {
+                let Some(regime) = regimes.get(state.regime) else {
+                    return (0.0, 0.0);
+                };
+                let r = regime.drift + regime.volatility * standard_normal(rng);
+                if regime.mean_duration > 0.0 && rng.gen_bool((1.0 / regime.mean_duration).min(1.0))
+                {
+                    state.regime = (state.regime + 1) % regimes.len();
+                }
+                (r, regime.volatility)
+            }
+        }
+    }
+}
+
+/// Volume driven by the size of each candle's move
+///
+/// `volume = base * (1 + sensitivity * |return| / volatility) * lognormal(noise)`,
+/// so big candles (pumps, dumps) trade more, as on real DEXes.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VolumeModel {
+    pub base: f64,
+    pub sensitivity: f64,
+    /// Standard deviation of the log-normal volume noise
+    pub noise: f64,
+}
+
+impl Default for VolumeModel {
+    fn default() -> Self {
+        Self {
+            base: 1_000_000.0,
+            sensitivity: 1.0,
+            noise: 0.3,
+        }
+    }
+}
+
+impl VolumeModel {
+    fn sample<R: Rng + ?Sized>(&self, abs_return: f64, volatility: f64, rng: &mut R) -> f64 {
+        let scale = if volatility > 0.0 {
+            1.0 + self.sensitivity * abs_return / volatility
+        } else {
+            1.0
+        };
+        let noise = (self.noise * standard_normal(rng) - self.noise.powi(2) / 2.0).exp();
+        (self.base * scale * noise).max(0.0)
+    }
+}
+
+/// A price model plus starting price and volume behaviour
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SyntheticModel {
+    /// Label in reports (defaults to the model kind)
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(flatten)]
+    pub price: PriceModel,
+    #[serde(default = "default_start_price")]
+    pub start_price: f64,
+    #[serde(default)]
+    pub volume: VolumeModel,
+}
+
+fn default_start_price() -> f64 {
+    150.0
+}
+
+impl SyntheticModel {
+    /// Preset parameters for `kind` (see `PriceModel::preset`)
+    pub fn preset(kind: ModelKind) -> Self {
+        Self {
+            name: None,
+            price: PriceModel::preset(kind),
+            start_price: default_start_price(),
+            volume: VolumeModel::default(),
+        }
+    }
+
+    pub fn name(&self) -> String {
+        self.name
+            .clone()
+            .unwrap_or_else(|| self.price.kind().to_string())
+    }
+
+    /// Fit `kind` to a real candle series (e.g. loaded from Redis)
+    ///
+    /// Returns are close-to-close log returns. Volume is regressed on absolute
+    /// returns. The simulation starts from the series' first close.
+    pub fn fit(kind: ModelKind, candles: &[Candle]) -> Result<Self> {
+        let closes: Vec<&Candle> = candles.iter().filter(|c| c.close > 0.0).collect();
+        if closes.len() < MIN_FIT_CANDLES {
+            return Err(format!(
+                "Need at least {} candles to fit a {} model, got {}",
+                MIN_FIT_CANDLES,
+                kind,
+                closes.len()
+            )
+            .into());
+        }
+
+        let returns: Vec<f64> = closes
+            .windows(2)
+            .map(|w| (w[1].close / w[0].close).ln())
+            .collect();
+        let volumes: Vec<f64> = closes[1..].iter().map(|c| c.volume).collect();
+
+        let price = match kind {
+            ModelKind::Gbm => fit_gbm(&returns),
+            ModelKind::Garch => fit_garch(&returns),
+            ModelKind::JumpDiffusion => fit_jump_diffusion(&returns),
+            ModelKind::RegimeSwitching => fit_regimes(&returns),
+        };
+        let volume = fit_volume(&returns, &volumes, price.volatility());
+
+        Ok(Self {
+            name: None,
+            price,
+            start_price: closes[0].close,
+            volume,
+        })
+    }
+
+    /// Simulate `num_candles` candles starting at `start_time`
+    pub fn generate<R: Rng + ?Sized>(
+        &self,
+        rng: &mut R,
+        start_time: DateTime<Utc>,
+        num_candles: usize,
+        interval_minutes: i64,
+    ) -> Vec<Candle> {
+        let mut candles = Vec::with_capacity(num_candles);
+        let mut state = self.price.initial_state();
+        let volatility = self.price.volatility();
+        let mut close = self.start_price;
+
+        for i in 0..num_candles {
+            let timestamp = start_time + Duration::minutes(i as i64 * interval_minutes);
+            let (r, sigma) = self.price.next_return(&mut state, rng);
+
+            let open = close;
+            close = open * r.exp();
+
+            // Wicks scale with the candle's own volatility
+            let high = open.max(close) * (0.5 * sigma * standard_normal(rng).abs()).exp();
+            let low = open.min(close) * (-0.5 * sigma * standard_normal(rng).abs()).exp();
+
+            candles.push(Candle {
+                token: "SYNTH".to_string(),
+                timestamp,
+                open,
+                high,
+                low,
+                close,
+                volume: self.volume.sample(r.abs(), volatility, rng),
+                provenance: CandleProvenance::Live,
+            });
+        }
+
+        candles
+    }
+}
+
+/// Standard normal draw (Box-Muller)
+fn standard_normal<R: Rng + ?Sized>(rng: &mut R) -> f64 {
+    let u1: f64 = 1.0 - rng.gen::<f64>(); // (0, 1], keeps ln finite
+    let u2: f64 = rng.gen();
+    (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos()
+}
+
+fn mean(values: &[f64]) -> f64 {
+    if values.is_empty() {
+        return 0.0;
+    }
+    values.iter().sum::<f64>() / values.len() as f64
+}
+
+fn std_dev(values: &[f64]) -> f64 {
+    if values.len() < 2 {
+        return 0.0;
+    }
+    let m = mean(values);
+    let variance = values.iter().map(|v| (v - m).powi(2)).sum::<f64>() / (values.len() - 1) as f64;
+    variance.sqrt()
+}
+
+fn median(values: &[f64]) -> f64 {
+    if values.is_empty() {
+        return 0.0;
+    }
+    let mut sorted = values.to_vec();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    let mid = sorted.len() / 2;
+    if sorted.len().is_multiple_of(2) {
+        (sorted[mid - 1] + sorted[mid]) / 2.0
+    } else {
+        sorted[mid]
+    }
+}
+
+fn fit_gbm(returns: &[f64]) -> PriceModel {
+    PriceModel::Gbm {
+        drift: mean(returns),
+        volatility: std_dev(returns),
+    }
+}
+
+/// Gaussian quasi-maximum likelihood over an (alpha, beta) grid, with omega
+/// pinned by variance targeting so the long-run volatility matches the sample
+fn fit_garch(returns: &[f64]) -> PriceModel {
+    let drift = mean(returns);
+    let shocks: Vec<f64> = returns.iter().map(|r| r - drift).collect();
+    let variance = std_dev(returns).powi(2).max(1e-12);
+
+    let log_likelihood = |alpha: f64, beta: f64| {
+        let omega = variance * (1.0 - alpha - beta);
+        let mut h = variance;
+        let mut ll = 0.0;
+        for shock in &shocks {
+            ll -= h.ln() + shock.powi(2) / h;
+            h = omega + alpha * shock.powi(2) + beta * h;
+        }
+        ll
+    };
+
+    let mut best = (0.0, 0.0, log_likelihood(0.0, 0.0));
+    for a in 1..=30 {
+        let alpha = a as f64 * 0.01;
+        for b in 0..=98 {
+            let beta = b as f64 * 0.01;
+            if alpha + beta >= 0.995 {
+                break;
+            }
+            let ll = log_likelihood(alpha, beta);
+            if ll > best.2 {
+                best = (alpha, beta, ll);
+            }
+        }
+    }
+
+    let (alpha, beta, _) = best;
+    PriceModel::Garch {
+        drift,
+        omega: variance * (1.0 - alpha - beta),
+        alpha,
+        beta,
+    }
+}
+
+/// Split returns into diffusion and jumps with a robust (MAD) threshold
+fn fit_jump_diffusion(returns: &[f64]) -> PriceModel {
+    let center = median(returns);
+    let deviations: Vec<f64> = returns.iter().map(|r| (r - center).abs()).collect();
+    let robust_sigma = 1.4826 * median(&deviations);
+    let threshold = JUMP_THRESHOLD_SIGMAS * robust_sigma;
+
+    let (jumps, diffusion): (Vec<f64>, Vec<f64>) = returns
+        .iter()
+        .partition(|r| robust_sigma > 0.0 && (*r - center).abs() > threshold);
+
+    let drift = mean(&diffusion);
+    PriceModel::JumpDiffusion {
+        drift,
+        volatility: std_dev(&diffusion),
+        jump_probability: jumps.len() as f64 / returns.len() as f64,
+        // Jumps are measured net of the diffusion drift they ride on
+        jump_mean: if jumps.is_empty() {
+            0.0
+        } else {
+            mean(&jumps) - drift
+        },
+        jump_volatility: std_dev(&jumps),
+    }
+}
+
+/// Two regimes ("calm", "turbulent") from rolling-window volatility above or
+/// below its median; durations come from the average run of same-regime windows
+fn fit_regimes(returns: &[f64]) -> PriceModel {
+    let window = (returns.len() / 20).max(12);
+    let windows: Vec<&[f64]> = returns.chunks(window).collect();
+    let vols: Vec<f64> = windows.iter().map(|w| std_dev(w)).collect();
+    let threshold = median(&vols);
+    let turbulent: Vec<bool> = vols.iter().map(|v| *v > threshold).collect();
+
+    let mut regimes = Vec::new();
+    for (name, flag) in [("calm", false), ("turbulent", true)] {
+        let pooled: Vec<f64> = windows
+            .iter()
+            .zip(&turbulent)
+            .filter(|(_, t)| **t == flag)
+            .flat_map(|(w, _)| w.iter().copied())
+            .collect();
+        if pooled.is_empty() {
+            continue;
+        }
+
+        // Average length of consecutive runs of this regime, in candles
+        let mut runs = Vec::new();
+        let mut current = 0usize;
+        for t in &turbulent {
+            if *t == flag {
+                current += 1;
+            } else if current > 0 {
+                runs.push(current as f64);
+                current = 0;
+            }
+        }
+        if current > 0 {
+            runs.push(current as f64);
+        }
+
+        regimes.push(Regime {
+            name: name.to_string(),
+            drift: mean(&pooled),
+            volatility: std_dev(&pooled),
+            mean_duration: mean(&runs) * window as f64,
+        });
+    }
+
+    PriceModel::RegimeSwitching { regimes }
+}
+
+/// Least-squares fit of `volume = a + b * |return|`, re-expressed as base and
+/// sensitivity relative to `volatility`
+fn fit_volume(returns: &[f64], volumes: &[f64], volatility: f64) -> VolumeModel {
+    let abs_returns: Vec<f64> = returns.iter().map(|r| r.abs()).collect();
+    let mean_abs = mean(&abs_returns);
+    let mean_volume = mean(volumes);
+    if mean_volume <= 0.0 {
+        return VolumeModel {
+            base: 0.0,
+            sensitivity: 0.0,
+            noise: 0.0,
+        };
+    }
+
+    let covariance: f64 = abs_returns
+        .iter()
+        .zip(volumes)
+        .map(|(r, v)| (r - mean_abs) * (v - mean_volume))
+        .sum();
+    let variance: f64 = abs_returns.iter().map(|r| (r - mean_abs).powi(2)).sum();
+    let slope = if variance > 0.0 {
+        (covariance / variance).max(0.0)
+    } else {
+        0.0
+    };
+    let base = (mean_volume - slope * mean_abs).max(mean_volume * 0.1);
+    let sensitivity = slope * volatility / base;
+
+    // Noise is whatever the regression leaves unexplained, in log space
+    let residuals: Vec<f64> = abs_returns
+        .iter()
+        .zip(volumes)
+        .filter(|(_, v)| **v > 0.0)
+        .map(|(r, v)| (v / (base + slope * r)).ln())
+        .collect();
+
+    VolumeModel {
+        base,
+        sensitivity,
+        noise: std_dev(&residuals),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn simulate(model: &SyntheticModel, count: usize) -> Vec<Candle> {
+        let mut rng = StdRng::seed_from_u64(42);
+        model.generate(&mut rng, Utc::now(), count, 5)
+    }
+
+    fn correlation(a: &[f64], b: &[f64]) -> f64 {
+        let (ma, mb) = (mean(a), mean(b));
+        let cov: f64 = a.iter().zip(b).map(|(x, y)| (x - ma) * (y - mb)).sum();
+        let va: f64 = a.iter().map(|x| (x - ma).powi(2)).sum();
+        let vb: f64 = b.iter().map(|y| (y - mb).powi(2)).sum();
+        cov / (va * vb).sqrt()
+    }
+
+    #[test]
+    fn test_generated_candles_are_consistent() {
+        for kind in ModelKind::ALL {
+            let candles = simulate(&SyntheticModel::preset(kind), 500);
+            assert_eq!(candles.len(), 500);
+            for pair in candles.windows(2) {
+                assert_eq!(pair[1].open, pair[0].close);
+                assert_eq!(
+                    (pair[1].timestamp - pair[0].timestamp).num_minutes(),
+                    5,
+                    "{}",
+                    kind
+                );
+            }
+            for c in &candles {
+                assert!(c.close > 0.0 && c.volume >= 0.0);
+                assert!(c.high >= c.open.max(c.close) && c.low <= c.open.min(c.close));
+            }
+        }
+    }
+
+    #[test]
+    fn test_volume_tracks_absolute_returns() {
+        let candles = simulate(&SyntheticModel::preset(ModelKind::JumpDiffusion), 2000);
+        let moves: Vec<f64> = candles
+            .iter()
+            .map(|c| (c.close / c.open).ln().abs())
+            .collect();
+        let volumes: Vec<f64> = candles.iter().map(|c| c.volume).collect();
+        assert!(correlation(&moves, &volumes) > 0.3);
+    }
+
+    #[test]
+    fn test_fit_gbm_recovers_parameters() {
+        let model = SyntheticModel {
+            price: PriceModel::Gbm {
+                drift: 0.001,
+                volatility: 0.02,
+            },
+            ..SyntheticModel::preset(ModelKind::Gbm)
+        };
+        let candles = simulate(&model, 5000);
+
+        let fitted = SyntheticModel::fit(ModelKind::Gbm, &candles).unwrap();
+        let PriceModel::Gbm { drift, volatility } = fitted.price else {
+            panic!("expected GBM, got {:?}", fitted.price);
+        };
+        assert!(
+            (volatility - 0.02).abs() < 0.001,
+            "volatility {}",
+            volatility
+        );
+        assert!((drift - 0.001).abs() < 0.001, "drift {}", drift);
+        assert_eq!(fitted.start_price, candles[0].close);
+        assert!(fitted.volume.sensitivity > 0.5);
+    }
+
+    #[test]
+    fn test_fit_garch_finds_volatility_clustering() {
+        let model = SyntheticModel {
+            price: PriceModel::Garch {
+                drift: 0.0,
+                omega: 0.0001 * 0.1,
+                alpha: 0.15,
+                beta: 0.75,
+            },
+            ..SyntheticModel::preset(ModelKind::Garch)
+        };
+        let candles = simulate(&model, 5000);
+
+        let fitted = SyntheticModel::fit(ModelKind::Garch, &candles).unwrap();
+        let PriceModel::Garch { alpha, beta, .. } = fitted.price else {
+            panic!("expected GARCH, got {:?}", fitted.price);
+        };
+        assert!((alpha - 0.15).abs() < 0.06, "alpha {}", alpha);
+        assert!(
+            (alpha + beta - 0.9).abs() < 0.06,
+            "persistence {}",
+            alpha + beta
+        );
+        assert!((fitted.price.volatility() - 0.01).abs() < 0.002);
+
+        // A GBM series has no clustering to find
+        let calm = simulate(&SyntheticModel::preset(ModelKind::Gbm), 5000);
+        let PriceModel::Garch { alpha, .. } =
+            SyntheticModel::fit(ModelKind::Garch, &calm).unwrap().price
+        else {
+            panic!("expected GARCH");
+        };
+        assert!(alpha < 0.05, "alpha {}", alpha);
+    }
+
+    #[test]
+    fn test_fit_jump_diffusion_separates_jumps() {
+        let model = SyntheticModel {
+            price: PriceModel::JumpDiffusion {
+                drift: 0.0,
+                volatility: 0.005,
+                jump_probability: 0.01,
+                jump_mean: -0.2,
+                jump_volatility: 0.05,
+            },
+            ..SyntheticModel::preset(ModelKind::JumpDiffusion)
+        };
+        let candles = simulate(&model, 10000);
+
+        let fitted = SyntheticModel::fit(ModelKind::JumpDiffusion, &candles).unwrap();
+        let PriceModel::JumpDiffusion {
+            volatility,
+            jump_probability,
+            jump_mean,
+            ..
+        } = fitted.price
+        else {
+            panic!("expected jump diffusion, got {:?}", fitted.price);
+        };
+        assert!(
+            (volatility - 0.005).abs() < 0.0005,
+            "volatility {}",
+            volatility
+        );
+        assert!(
+            (jump_probability - 0.01).abs() < 0.004,
+            "jump probability {}",
+            jump_probability
+        );
+        assert!((jump_mean + 0.2).abs() < 0.03, "jump mean {}", jump_mean);
+    }
+
+    #[test]
+    fn test_fit_regimes_separates_calm_and_turbulent() {
+        let candles = simulate(&SyntheticModel::preset(ModelKind::RegimeSwitching), 5000);
+
+        let fitted = SyntheticModel::fit(ModelKind::RegimeSwitching, &candles).unwrap();
+        let PriceModel::RegimeSwitching { regimes } = &fitted.price else {
+            panic!("expected regimes, got {:?}", fitted.price);
+        };
+        assert_eq!(regimes.len(), 2);
+        assert!(regimes[1].volatility > regimes[0].volatility * 1.5);
+        assert!(regimes.iter().all(|r| r.mean_duration > 0.0));
+    }
+
+    #[test]
+    fn test_fit_rejects_short_series() {
+        let candles = simulate(&SyntheticModel::preset(ModelKind::Gbm), 10);
+        assert!(SyntheticModel::fit(ModelKind::Gbm, &candles).is_err());
+    }
+
+    #[test]
+    fn test_model_kind_names_round_trip() {
+        for kind in ModelKind::ALL {
+            assert_eq!(kind.to_string().parse::<ModelKind>(), Ok(kind));
+            assert_eq!(PriceModel::preset(kind).kind(), kind);
+        }
+        assert_eq!("jump".parse::<ModelKind>(), Ok(ModelKind::JumpDiffusion));
+        assert!("heston".parse::<ModelKind>().is_err());
+    }
+}
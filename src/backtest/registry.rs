@@ -0,0 +1,281 @@
+use crate::backtest::metrics::BacktestMetrics;
+use crate::models::Candle;
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use uuid::Uuid;
+
+/// Tokens and time span a backtest replayed
+#[derive(Debug, Clone, PartialEq)]
+pub struct DataRange {
+    pub tokens: Vec<String>,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+}
+
+impl DataRange {
+    /// Range covered by a set of series (tokens sorted by symbol)
+    pub fn of(series: &HashMap<String, Vec<Candle>>) -> Option<Self> {
+        let mut tokens: Vec<String> = series.keys().cloned().collect();
+        tokens.sort();
+        let start = series
+            .values()
+            .filter_map(|c| c.first())
+            .map(|c| c.timestamp)
+            .min()?;
+        let end = series
+            .values()
+            .filter_map(|c| c.last())
+            .map(|c| c.timestamp)
+            .max()?;
+        Some(Self { tokens, start, end })
+    }
+
+    /// Range of a single token's candles
+    pub fn single(token: &str, candles: &[Candle]) -> Option<Self> {
+        Some(Self {
+            tokens: vec![token.to_string()],
+            start: candles.first()?.timestamp,
+            end: candles.last()?.timestamp,
+        })
+    }
+}
+
+/// A stored backtest: what ran, on which data, at which revision, and the results
+#[derive(Debug, Clone)]
+pub struct BacktestRun {
+    pub id: Uuid,
+    pub name: String,
+    pub strategy: String,
+    pub tokens: Vec<String>,
+    /// Runner + strategy parameters (see `PortfolioBacktestRunner::config`)
+    pub config: Value,
+    pub data_start: DateTime<Utc>,
+    pub data_end: DateTime<Utc>,
+    pub git_revision: Option<String>,
+    pub metrics: BacktestMetrics,
+    pub created_at: DateTime<Utc>,
+}
+
+impl BacktestRun {
+    pub fn new(
+        name: &str,
+        strategy: &str,
+        config: Value,
+        data: DataRange,
+        metrics: BacktestMetrics,
+    ) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            name: name.to_string(),
+            strategy: strategy.to_string(),
+            tokens: data.tokens,
+            config,
+            data_start: data.start,
+            data_end: data.end,
+            git_revision: git_revision(),
+            metrics,
+            created_at: Utc::now(),
+        }
+    }
+}
+
+/// One row of `cryptobot backtests list`
+#[derive(Debug, Clone)]
+pub struct BacktestRunSummary {
+    pub id: Uuid,
+    pub name: String,
+    pub strategy: String,
+    pub tokens: Vec<String>,
+    pub data_start: DateTime<Utc>,
+    pub data_end: DateTime<Utc>,
+    pub git_revision: Option<String>,
+    pub total_return_pct: f64,
+    pub total_trades: i64,
+    pub win_rate: f64,
+    pub max_drawdown_pct: f64,
+    pub sharpe_ratio: f64,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Current git revision: `GIT_REVISION` / `RAILWAY_GIT_COMMIT_SHA`, else `git rev-parse`
+pub fn git_revision() -> Option<String> {
+    for var in ["GIT_REVISION", "RAILWAY_GIT_COMMIT_SHA"] {
+        if let Ok(revision) = std::env::var(var) {
+            if !revision.is_empty() {
+                return Some(revision);
+            }
+        }
+    }
+
+    let output = std::process::Command::new("git")
+        .args(["rev-parse", "--short", "HEAD"])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let revision = String::from_utf8(output.stdout).ok()?.trim().to_string();
+    (!revision.is_empty()).then_some(revision)
+}
+
+/// Flatten a JSON config into dotted paths (`circuit_breakers.max_drawdown_pct`)
+pub fn flatten_config(config: &Value) -> BTreeMap<String, Value> {
+    fn walk(prefix: &str, value: &Value, out: &mut BTreeMap<String, Value>) {
+        match value {
+            Value::Object(map) if !map.is_empty() => {
+                for (key, child) in map {
+                    let path = if prefix.is_empty() {
+                        key.clone()
+                    } else {
+                        format!("{}.{}", prefix, key)
+                    };
+                    walk(&path, child, out);
+                }
+            }
+            _ => {
+                out.insert(prefix.to_string(), value.clone());
+            }
+        }
+    }
+
+    let mut out = BTreeMap::new();
+    walk("", config, &mut out);
+    out
+}
+
+/// Config paths whose values differ between runs (missing = `null`)
+pub fn config_differences(configs: &[&Value]) -> BTreeMap<String, Vec<Value>> {
+    let flat: Vec<BTreeMap<String, Value>> = configs.iter().map(|c| flatten_config(c)).collect();
+    let mut paths: Vec<&String> = flat.iter().flat_map(|f| f.keys()).collect();
+    paths.sort();
+    paths.dedup();
+
+    paths
+        .into_iter()
+        .filter_map(|path| {
+            let values: Vec<Value> = flat
+                .iter()
+                .map(|f| f.get(path).cloned().unwrap_or(Value::Null))
+                .collect();
+            let differs = values.windows(2).any(|w| w[0] != w[1]);
+            differs.then(|| (path.clone(), values))
+        })
+        .collect()
+}
+
+/// Print runs side by side: headline metrics, then the config values that changed
+pub fn print_comparison(runs: &[BacktestRun]) {
+    let short_id = |run: &BacktestRun| run.id.to_string()[..8].to_string();
+
+    print!("{:<24}", "");
+    for run in runs {
+        print!(" {:>16}", short_id(run));
+    }
+    println!();
+    println!("{}", "─".repeat(24 + 17 * runs.len()));
+
+    type Column = fn(&BacktestRun) -> String;
+    let rows: [(&str, Column); 14] = [
+        ("Strategy", |r| r.strategy.clone()),
+        ("Revision", |r| {
+            r.git_revision.clone().unwrap_or_else(|| "-".to_string())
+        }),
+        ("Created", |r| {
+            r.created_at.format("%Y-%m-%d %H:%M").to_string()
+        }),
+        ("Data from", |r| r.data_start.format("%Y-%m-%d").to_string()),
+        ("Data to", |r| r.data_end.format("%Y-%m-%d").to_string()),
+        ("Tokens", |r| r.tokens.len().to_string()),
+        ("Return %", |r| {
+            format!("{:+.2}", r.metrics.total_return_pct)
+        }),
+        ("Trades", |r| r.metrics.total_trades.to_string()),
+        ("Win %", |r| format!("{:.1}", r.metrics.win_rate)),
+        ("Profit factor", |r| {
+            format!("{:.2}", r.metrics.profit_factor)
+        }),
+        ("Max drawdown %", |r| {
+            format!("{:.2}", r.metrics.max_drawdown_pct)
+        }),
+        ("Sharpe", |r| format!("{:.2}", r.metrics.sharpe_ratio)),
+        ("Sortino", |r| format!("{:.2}", r.metrics.sortino_ratio)),
+        ("Costs $", |r| format!("{:.2}", r.metrics.total_costs)),
+    ];
+    for (label, value) in rows {
+        print!("{:<24}", label);
+        for run in runs {
+            let text = value(run);
+            let text: String = text.chars().take(16).collect();
+            print!(" {:>16}", text);
+        }
+        println!();
+    }
+
+    let configs: Vec<&Value> = runs.iter().map(|r| &r.config).collect();
+    let differences = config_differences(&configs);
+    println!("\n⚙️  CONFIG DIFFERENCES");
+    if differences.is_empty() {
+        println!("  (identical configs)");
+    }
+    for (path, values) in differences {
+        println!("  {}", path);
+        for (run, value) in runs.iter().zip(values) {
+            println!("    {}  {}", short_id(run), value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::CandleProvenance;
+    use chrono::Duration;
+    use serde_json::json;
+
+    fn candles(token: &str, start: DateTime<Utc>, count: i64) -> Vec<Candle> {
+        (0..count)
+            .map(|i| Candle {
+                token: token.to_string(),
+                timestamp: start + Duration::minutes(5 * i),
+                open: 1.0,
+                high: 1.0,
+                low: 1.0,
+                close: 1.0,
+                volume: 0.0,
+                provenance: CandleProvenance::Live,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_data_range_spans_all_series() {
+        let start = Utc::now();
+        let mut series = HashMap::new();
+        series.insert("JUP".to_string(), candles("JUP", start, 10));
+        series.insert(
+            "BONK".to_string(),
+            candles("BONK", start - Duration::hours(1), 5),
+        );
+
+        let range = DataRange::of(&series).unwrap();
+        assert_eq!(range.tokens, vec!["BONK", "JUP"]);
+        assert_eq!(range.start, start - Duration::hours(1));
+        assert_eq!(range.end, start + Duration::minutes(45));
+        assert!(DataRange::single("SOL", &[]).is_none());
+    }
+
+    #[test]
+    fn test_config_differences() {
+        let a = json!({"sizing": {"fixed_fractional": {"fraction": 0.05}}, "fees": 0.0025});
+        let b = json!({"sizing": {"fixed_fractional": {"fraction": 0.02}}, "fees": 0.0025, "new": true});
+
+        let differences = config_differences(&[&a, &b]);
+        assert_eq!(differences.len(), 2);
+        assert_eq!(
+            differences["sizing.fixed_fractional.fraction"],
+            vec![json!(0.05), json!(0.02)]
+        );
+        assert_eq!(differences["new"], vec![Value::Null, json!(true)]);
+    }
+}
@@ -0,0 +1,581 @@
+use crate::backtest::equity::EquityPoint;
+use crate::backtest::metrics::BacktestMetrics;
+use crate::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+const CHART_WIDTH: f64 = 800.0;
+const CHART_HEIGHT: f64 = 220.0;
+const CHART_PADDING: f64 = 40.0;
+
+/// Output format for backtest results
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportFormat {
+    /// Emoji tables on stdout (no files written)
+    #[default]
+    Text,
+    /// Serialized metrics, trades and equity curve
+    Json,
+    /// Trade list
+    Csv,
+    /// Self-contained page with equity, drawdown and trade-marker charts
+    Html,
+}
+
+impl ReportFormat {
+    /// File extension, or `None` for stdout-only output
+    pub fn extension(&self) -> Option<&'static str> {
+        match self {
+            Self::Text => None,
+            Self::Json => Some("json"),
+            Self::Csv => Some("csv"),
+            Self::Html => Some("html"),
+        }
+    }
+}
+
+impl fmt::Display for ReportFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Text => "text",
+            Self::Json => "json",
+            Self::Csv => "csv",
+            Self::Html => "html",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for ReportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "text" | "txt" => Ok(Self::Text),
+            "json" => Ok(Self::Json),
+            "csv" => Ok(Self::Csv),
+            "html" => Ok(Self::Html),
+            other => Err(format!(
+                "Unknown output format '{}' (expected text, json, csv or html)",
+                other
+            )),
+        }
+    }
+}
+
+/// One named backtest result, ready to be rendered
+#[derive(Debug, Clone, Serialize)]
+pub struct BacktestReport<'a> {
+    pub name: &'a str,
+    pub strategy: &'a str,
+    pub metrics: &'a BacktestMetrics,
+}
+
+impl<'a> BacktestReport<'a> {
+    pub fn new(name: &'a str, strategy: &'a str, metrics: &'a BacktestMetrics) -> Self {
+        Self {
+            name,
+            strategy,
+            metrics,
+        }
+    }
+
+    /// Render in `format` (`None` for text, which is printed instead)
+    pub fn render(&self, format: ReportFormat) -> Result<Option<String>> {
+        Ok(match format {
+            ReportFormat::Text => None,
+            ReportFormat::Json => Some(self.to_json()?),
+            ReportFormat::Csv => Some(self.to_csv()),
+            ReportFormat::Html => Some(self.to_html()),
+        })
+    }
+
+    /// Write the report to `<dir>/<name>.<ext>` and return the path
+    pub fn write_to(&self, dir: &Path, format: ReportFormat) -> Result<Option<PathBuf>> {
+        let (Some(extension), Some(contents)) = (format.extension(), self.render(format)?) else {
+            return Ok(None);
+        };
+        std::fs::create_dir_all(dir)?;
+        let path = dir.join(format!("{}.{}", slug(self.name), extension));
+        std::fs::write(&path, contents)?;
+        Ok(Some(path))
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// Trade list, one row per closed trade
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from(
+            "token,entry_time,exit_time,entry_price,exit_price,quantity,pnl,pnl_pct,gross_pnl,costs,holding_period_minutes,mae_pct,mfe_pct\n",
+        );
+        for t in &self.metrics.trades {
+            let _ = writeln!(
+                csv,
+                "{},{},{},{},{},{},{:.6},{:.4},{:.6},{:.6},{},{:.4},{:.4}",
+                csv_field(&t.token),
+                t.entry_time.to_rfc3339(),
+                t.exit_time.to_rfc3339(),
+                t.entry_price,
+                t.exit_price,
+                t.quantity,
+                t.pnl,
+                t.pnl_pct,
+                t.gross_pnl,
+                t.costs,
+                t.holding_period_minutes,
+                t.mae_pct,
+                t.mfe_pct
+            );
+        }
+        csv
+    }
+
+    /// Self-contained HTML page (inline SVG, no external assets)
+    pub fn to_html(&self) -> String {
+        let m = self.metrics;
+        let title = format!("{} — {}", self.name, self.strategy);
+
+        let summary = [
+            (
+                "Initial portfolio",
+                format!("${:.2}", m.initial_portfolio_value),
+            ),
+            (
+                "Final portfolio",
+                format!("${:.2}", m.final_portfolio_value),
+            ),
+            (
+                "Net P&L",
+                format!("${:.2} ({:+.2}%)", m.total_pnl, m.total_return_pct),
+            ),
+            ("Gross P&L", format!("${:.2}", m.gross_pnl)),
+            ("Costs", format!("${:.2}", m.total_costs)),
+            (
+                "Trades",
+                format!("{} ({:.1}% win)", m.total_trades, m.win_rate),
+            ),
+            ("Profit factor", format!("{:.2}", m.profit_factor)),
+            (
+                "Max drawdown",
+                format!("${:.2} ({:.2}%)", m.max_drawdown, m.max_drawdown_pct),
+            ),
+            (
+                "Drawdown duration",
+                format!("{:.1} h", m.max_drawdown_duration_minutes as f64 / 60.0),
+            ),
+            ("Sharpe (annualized)", format!("{:.2}", m.sharpe_ratio)),
+            ("Sortino", format!("{:.2}", m.sortino_ratio)),
+            ("Calmar", format!("{:.2}", m.calmar_ratio)),
+            ("Time in market", format!("{:.1}%", m.time_in_market_pct)),
+            ("Turnover", format!("{:.2}x", m.turnover)),
+            (
+                "Circuit breaker trips",
+                m.circuit_breaker_events.len().to_string(),
+            ),
+        ];
+
+        let mut html = String::new();
+        let _ = write!(
+            html,
+            "<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\"><title>{title}</title>\n\
+             <style>body{{font-family:sans-serif;margin:2em;color:#222}}\
+             table{{border-collapse:collapse;margin-bottom:1.5em}}\
+             td,th{{border:1px solid #ccc;padding:4px 8px;text-align:right}}\
+             th{{background:#f3f3f3}}td:first-child{{text-align:left}}</style>\n\
+             </head><body>\n<h1>{title}</h1>\n<table>\n",
+            title = escape(&title)
+        );
+        for (label, value) in summary {
+            let _ = writeln!(
+                html,
+                "<tr><td>{}</td><td>{}</td></tr>",
+                escape(label),
+                value
+            );
+        }
+        html.push_str("</table>\n");
+
+        if !m.benchmarks.is_empty() {
+            html.push_str(
+                "<h2>Benchmarks</h2>\n<table>\n<tr><th>Benchmark</th><th>Return %</th>\
+                 <th>Excess %</th><th>Alpha %/yr</th><th>Beta</th><th>Correlation</th>\
+                 <th>Tracking error %</th><th>Information ratio</th></tr>\n",
+            );
+            for b in &m.benchmarks {
+                let _ = writeln!(
+                    html,
+                    "<tr><td>{}</td><td>{:+.2}</td><td>{:+.2}</td><td>{:+.2}</td>\
+                     <td>{:.2}</td><td>{:.2}</td><td>{:.2}</td><td>{:.2}</td></tr>",
+                    escape(&b.name),
+                    b.return_pct,
+                    b.excess_return_pct,
+                    b.alpha * 100.0,
+                    b.beta,
+                    b.correlation,
+                    b.tracking_error * 100.0,
+                    b.information_ratio
+                );
+            }
+            html.push_str("</table>\n");
+        }
+
+        html.push_str("<h2>Equity</h2>\n");
+        html.push_str(&equity_chart(m));
+        html.push_str("<h2>Drawdown</h2>\n");
+        html.push_str(&drawdown_chart(&m.equity_curve.points));
+
+        html.push_str(
+            "<h2>Trades</h2>\n<table>\n<tr><th>Token</th><th>Entry</th><th>Exit</th>\
+             <th>Entry $</th><th>Exit $</th><th>P&amp;L</th><th>P&amp;L %</th>\
+             <th>MAE %</th><th>MFE %</th></tr>\n",
+        );
+        for t in &m.trades {
+            let _ = writeln!(
+                html,
+                "<tr><td>{}</td><td>{}</td><td>{}</td><td>{:.6}</td><td>{:.6}</td>\
+                 <td>{:.2}</td><td>{:+.2}</td><td>{:.2}</td><td>{:+.2}</td></tr>",
+                escape(&t.token),
+                t.entry_time.format("%Y-%m-%d %H:%M"),
+                t.exit_time.format("%Y-%m-%d %H:%M"),
+                t.entry_price,
+                t.exit_price,
+                t.pnl,
+                t.pnl_pct,
+                t.mae_pct,
+                t.mfe_pct
+            );
+        }
+        html.push_str("</table>\n</body></html>\n");
+        html
+    }
+}
+
+/// One row per run (name, strategy and headline metrics), for comparing a batch
+pub fn summary_csv(reports: &[BacktestReport]) -> String {
+    let mut csv = String::from(
+        "name,strategy,total_pnl,total_return_pct,total_trades,win_rate,max_drawdown_pct,sharpe_ratio,sortino_ratio,calmar_ratio,time_in_market_pct,total_costs\n",
+    );
+    for r in reports {
+        let m = r.metrics;
+        let _ = writeln!(
+            csv,
+            "{},{},{:.2},{:.4},{},{:.2},{:.4},{:.4},{:.4},{:.4},{:.2},{:.2}",
+            csv_field(r.name),
+            csv_field(r.strategy),
+            m.total_pnl,
+            m.total_return_pct,
+            m.total_trades,
+            m.win_rate,
+            m.max_drawdown_pct,
+            m.sharpe_ratio,
+            m.sortino_ratio,
+            m.calmar_ratio,
+            m.time_in_market_pct,
+            m.total_costs
+        );
+    }
+    csv
+}
+
+/// Write one file per report plus a `summary.csv` index (nothing for text)
+pub fn write_reports(
+    reports: &[BacktestReport],
+    format: ReportFormat,
+    dir: &Path,
+) -> Result<Vec<PathBuf>> {
+    if format == ReportFormat::Text || reports.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let mut paths = Vec::new();
+    for report in reports {
+        paths.extend(report.write_to(dir, format)?);
+    }
+    let summary = dir.join("summary.csv");
+    std::fs::write(&summary, summary_csv(reports))?;
+    paths.push(summary);
+
+    Ok(paths)
+}
+
+/// Lowercase ASCII file name from a display name ("📈 Smooth Uptrend" -> "smooth-uptrend")
+fn slug(name: &str) -> String {
+    let mut slug = String::new();
+    for c in name.chars() {
+        if c.is_ascii_alphanumeric() {
+            slug.push(c.to_ascii_lowercase());
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    let slug = slug.trim_end_matches('-').to_string();
+    if slug.is_empty() {
+        "backtest".to_string()
+    } else {
+        slug
+    }
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Maps timestamps and values onto the SVG canvas
+struct ChartScale {
+    start: i64,
+    span: f64,
+    min: f64,
+    max: f64,
+}
+
+impl ChartScale {
+    fn new(points: &[EquityPoint], values: impl Iterator<Item = f64>) -> Option<Self> {
+        let (first, last) = (points.first()?, points.last()?);
+        let (min, max) = values.fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), v| {
+            (lo.min(v), hi.max(v))
+        });
+        let (min, max) = if max > min {
+            (min, max)
+        } else {
+            (min - 1.0, max + 1.0)
+        };
+        Some(Self {
+            start: first.timestamp.timestamp(),
+            span: ((last.timestamp.timestamp() - first.timestamp.timestamp()) as f64).max(1.0),
+            min,
+            max,
+        })
+    }
+
+    fn x(&self, time: DateTime<Utc>) -> f64 {
+        let fraction = (time.timestamp() - self.start) as f64 / self.span;
+        CHART_PADDING + fraction.clamp(0.0, 1.0) * (CHART_WIDTH - 2.0 * CHART_PADDING)
+    }
+
+    fn y(&self, value: f64) -> f64 {
+        let fraction = (value - self.min) / (self.max - self.min);
+        CHART_HEIGHT - CHART_PADDING / 2.0 - fraction * (CHART_HEIGHT - CHART_PADDING)
+    }
+
+    fn frame(&self, svg: &mut String, format_value: impl Fn(f64) -> String) {
+        let _ = write!(
+            svg,
+            "<line x1=\"{p}\" y1=\"{top}\" x2=\"{p}\" y2=\"{bottom}\" stroke=\"#999\"/>\
+             <text x=\"2\" y=\"{top_label}\" font-size=\"10\">{max}</text>\
+             <text x=\"2\" y=\"{bottom}\" font-size=\"10\">{min}</text>",
+            p = CHART_PADDING,
+            top = self.y(self.max),
+            top_label = self.y(self.max) + 10.0,
+            bottom = self.y(self.min),
+            max = format_value(self.max),
+            min = format_value(self.min),
+        );
+    }
+}
+
+fn svg_open() -> String {
+    format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\">",
+        w = CHART_WIDTH,
+        h = CHART_HEIGHT
+    )
+}
+
+/// Equity line with entry (green) and exit (red) markers
+fn equity_chart(metrics: &BacktestMetrics) -> String {
+    let points = &metrics.equity_curve.points;
+    let Some(scale) = ChartScale::new(points, points.iter().map(|p| p.value)) else {
+        return "<p>No equity curve recorded.</p>\n".to_string();
+    };
+
+    let mut svg = svg_open();
+    scale.frame(&mut svg, |v| format!("${:.0}", v));
+    let line: Vec<String> = points
+        .iter()
+        .map(|p| format!("{:.1},{:.1}", scale.x(p.timestamp), scale.y(p.value)))
+        .collect();
+    let _ = write!(
+        svg,
+        "<polyline fill=\"none\" stroke=\"#2b6cb0\" stroke-width=\"1.5\" points=\"{}\"/>",
+        line.join(" ")
+    );
+
+    for trade in &metrics.trades {
+        for (time, color) in [(trade.entry_time, "#2f855a"), (trade.exit_time, "#c53030")] {
+            if let Some(value) = value_at(points, time) {
+                let _ = write!(
+                    svg,
+                    "<circle cx=\"{:.1}\" cy=\"{:.1}\" r=\"3\" fill=\"{}\"><title>{} {}</title></circle>",
+                    scale.x(time),
+                    scale.y(value),
+                    color,
+                    escape(&trade.token),
+                    time.format("%Y-%m-%d %H:%M")
+                );
+            }
+        }
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// Percentage below the running peak, as a filled area
+fn drawdown_chart(points: &[EquityPoint]) -> String {
+    let mut peak = f64::NEG_INFINITY;
+    let drawdowns: Vec<(DateTime<Utc>, f64)> = points
+        .iter()
+        .map(|p| {
+            peak = peak.max(p.value);
+            let dd = if peak > 0.0 {
+                (p.value / peak - 1.0) * 100.0
+            } else {
+                0.0
+            };
+            (p.timestamp, dd)
+        })
+        .collect();
+
+    let Some(scale) = ChartScale::new(
+        points,
+        drawdowns
+            .iter()
+            .map(|(_, dd)| *dd)
+            .chain(std::iter::once(0.0)),
+    ) else {
+        return "<p>No equity curve recorded.</p>\n".to_string();
+    };
+
+    let mut svg = svg_open();
+    scale.frame(&mut svg, |v| format!("{:.1}%", v));
+    let mut area: Vec<String> = drawdowns
+        .iter()
+        .map(|(time, dd)| format!("{:.1},{:.1}", scale.x(*time), scale.y(*dd)))
+        .collect();
+    if let (Some(first), Some(last)) = (drawdowns.first(), drawdowns.last()) {
+        area.push(format!("{:.1},{:.1}", scale.x(last.0), scale.y(0.0)));
+        area.push(format!("{:.1},{:.1}", scale.x(first.0), scale.y(0.0)));
+    }
+    let _ = writeln!(
+        svg,
+        "<polygon fill=\"#feb2b2\" stroke=\"#c53030\" points=\"{}\"/></svg>",
+        area.join(" ")
+    );
+    svg
+}
+
+/// Equity at the last sample at or before `time`
+fn value_at(points: &[EquityPoint], time: DateTime<Utc>) -> Option<f64> {
+    let index = points.partition_point(|p| p.timestamp <= time);
+    points.get(index.checked_sub(1)?).map(|p| p.value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backtest::equity::EquityCurve;
+    use crate::backtest::metrics::TradeRecord;
+    use chrono::Duration;
+    use uuid::Uuid;
+
+    fn metrics() -> BacktestMetrics {
+        let start = Utc::now();
+        let mut curve = EquityCurve::new();
+        for (i, value) in [10000.0, 10100.0, 9900.0, 10200.0].iter().enumerate() {
+            curve.push(start + Duration::minutes(5 * i as i64), *value, 0.0);
+        }
+
+        let mut metrics = BacktestMetrics::from_positions(vec![], 10000.0, 10200.0, vec![])
+            .with_equity_curve(curve);
+        metrics.trades.push(TradeRecord {
+            position_id: Uuid::new_v4(),
+            token: "SOL,USDC".to_string(),
+            entry_time: start,
+            exit_time: start + Duration::minutes(15),
+            entry_price: 100.0,
+            exit_price: 102.0,
+            quantity: 1.0,
+            pnl: 2.0,
+            pnl_pct: 2.0,
+            gross_pnl: 2.0,
+            costs: 0.0,
+            holding_period_minutes: 15,
+            mae_pct: -1.0,
+            mfe_pct: 3.0,
+        });
+        metrics
+    }
+
+    #[test]
+    fn test_parse_formats() {
+        assert_eq!("JSON".parse::<ReportFormat>().unwrap(), ReportFormat::Json);
+        assert_eq!("html".parse::<ReportFormat>().unwrap(), ReportFormat::Html);
+        assert!("pdf".parse::<ReportFormat>().is_err());
+        assert_eq!(ReportFormat::Text.extension(), None);
+    }
+
+    #[test]
+    fn test_json_includes_equity_curve() {
+        let metrics = metrics();
+        let report = BacktestReport::new("Smooth Uptrend", "Momentum", &metrics);
+        let json: serde_json::Value = serde_json::from_str(&report.to_json().unwrap()).unwrap();
+
+        assert_eq!(json["name"], "Smooth Uptrend");
+        assert_eq!(
+            json["metrics"]["equity_curve"]["points"]
+                .as_array()
+                .unwrap()
+                .len(),
+            4
+        );
+        assert_eq!(json["metrics"]["trades"][0]["mfe_pct"], 3.0);
+    }
+
+    #[test]
+    fn test_csv_quotes_fields() {
+        let metrics = metrics();
+        let csv = BacktestReport::new("x", "Momentum", &metrics).to_csv();
+        let lines: Vec<&str> = csv.lines().collect();
+
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("token,entry_time"));
+        assert!(lines[1].starts_with("\"SOL,USDC\","));
+    }
+
+    #[test]
+    fn test_html_is_self_contained() {
+        let metrics = metrics();
+        let html = BacktestReport::new("<Test>", "Momentum", &metrics).to_html();
+
+        assert!(html.contains("&lt;Test&gt;"));
+        assert_eq!(html.matches("<svg").count(), 2);
+        assert!(html.contains("<polyline"));
+        assert!(html.contains("<circle"));
+        assert!(!html.contains("<script"));
+    }
+
+    #[test]
+    fn test_slug() {
+        assert_eq!(slug("📈 Smooth Uptrend"), "smooth-uptrend");
+        assert_eq!(slug("SOL Buy & Hold"), "sol-buy-hold");
+        assert_eq!(slug("💥"), "backtest");
+    }
+}
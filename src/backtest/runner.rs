@@ -1,16 +1,18 @@
+use crate::backtest::fills::FillModel;
 use crate::backtest::metrics::BacktestMetrics;
-use crate::execution::{ExecutionAction, Executor, PositionManager};
+use crate::backtest::portfolio::PortfolioBacktestRunner;
 use crate::models::Candle;
-use crate::risk::CircuitBreakers;
+use crate::risk::{CircuitBreakers, PositionSizing};
 use crate::strategy::Strategy;
 use crate::Result;
 use std::collections::HashMap;
-use std::sync::{Arc, Mutex};
 
 /// Backtest runner that simulates trading with historical data
 pub struct BacktestRunner {
     initial_portfolio_value: f64,
     circuit_breakers: CircuitBreakers,
+    sizing: PositionSizing,
+    fill_model: FillModel,
 }
 
 impl BacktestRunner {
@@ -19,9 +21,23 @@ impl BacktestRunner {
         Self {
             initial_portfolio_value,
             circuit_breakers,
+            sizing: PositionSizing::default(),
+            fill_model: FillModel::default(),
         }
     }
 
+    /// Set the position sizing method used by the simulated executor
+    pub fn with_sizing(mut self, sizing: PositionSizing) -> Self {
+        self.sizing = sizing;
+        self
+    }
+
+    /// Set fees, slippage and execution timing (default: `FillModel::default()`)
+    pub fn with_fill_model(mut self, fill_model: FillModel) -> Self {
+        self.fill_model = fill_model;
+        self
+    }
+
     /// Run a backtest with given strategy and candles
     ///
     /// # Arguments
@@ -31,7 +47,7 @@ impl BacktestRunner {
     ///
     /// # Returns
     /// BacktestMetrics with performance data
-    pub fn run<S: Strategy>(
+    pub fn run<S: Strategy + ?Sized>(
         &self,
         strategy: &S,
         candles: Vec<Candle>,
@@ -54,141 +70,26 @@ impl BacktestRunner {
             samples_needed
         );
 
-        // Initialize position manager and executor
-        let position_manager = Arc::new(Mutex::new(PositionManager::new(
-            self.initial_portfolio_value,
-            self.circuit_breakers.clone(),
-        )));
-
-        let mut executor = Executor::new(position_manager.clone());
-
-        // Track circuit breaker hits
-        let mut circuit_breaker_hits = 0;
-
-        // Simulate main trading loop
-        // Start trading once we have enough candles for the strategy
-        for i in samples_needed..candles.len() {
-            let lookback_candles = &candles[i - samples_needed..=i];
-            let current_candle = &candles[i];
-            let current_price = current_candle.close;
-
-            // Create price map for position manager
-            let mut prices = HashMap::new();
-            prices.insert(token_symbol.to_string(), current_price);
-
-            // Check for exit conditions on existing positions FIRST
-            {
-                let mut pm = position_manager.lock().unwrap();
-                if let Ok(closed_ids) = pm.check_exits(&prices) {
-                    if !closed_ids.is_empty() {
-                        tracing::debug!(
-                            "Closed {} positions via exit conditions",
-                            closed_ids.len()
-                        );
-                    }
-                }
-            }
-
-            // Generate signal
-            match strategy.generate_signal(lookback_candles) {
-                Ok(signal) => {
-                    // Process signal with executor
-                    match executor.process_signal(&signal, token_symbol, current_price) {
-                        Ok(decision) => {
-                            match decision.action {
-                                ExecutionAction::Execute { quantity } => {
-                                    // Open position
-                                    let mut pm = position_manager.lock().unwrap();
-                                    match pm.open_position(
-                                        token_symbol.to_string(),
-                                        current_price,
-                                        quantity,
-                                    ) {
-                                        Ok(_) => {
-                                            tracing::debug!(
-                                                "Opened position @ ${:.4} qty: {:.4}",
-                                                current_price,
-                                                quantity
-                                            );
-                                        }
-                                        Err(e) => {
-                                            if e.to_string().contains("Circuit breaker") {
-                                                circuit_breaker_hits += 1;
-                                                tracing::debug!("Circuit breaker triggered: {}", e);
-                                            }
-                                        }
-                                    }
-                                }
-                                ExecutionAction::Close {
-                                    position_id,
-                                    exit_reason,
-                                } => {
-                                    // Close position
-                                    let mut pm = position_manager.lock().unwrap();
-                                    let _ =
-                                        pm.close_position(position_id, current_price, exit_reason);
-                                }
-                                ExecutionAction::Skip => {
-                                    // Do nothing
-                                }
-                            }
-                        }
-                        Err(e) => {
-                            tracing::warn!("Failed to process signal: {}", e);
-                        }
-                    }
-                }
-                Err(e) => {
-                    tracing::warn!("Failed to generate signal: {}", e);
-                }
-            }
-        }
+        // A single-token backtest is a portfolio of one
+        let mut series = HashMap::new();
+        series.insert(token_symbol.to_string(), candles);
 
-        // Close any remaining open positions at final price
-        let final_price = candles.last().unwrap().close;
-        {
-            let mut pm = position_manager.lock().unwrap();
-            // Collect position IDs first to avoid borrow checker issue
-            let position_ids: Vec<_> = pm.open_positions().iter().map(|p| p.id).collect();
-
-            for position_id in position_ids {
-                let _ = pm.close_position(
-                    position_id,
-                    final_price,
-                    crate::execution::ExitReason::Manual,
-                );
-            }
-        }
-
-        // Calculate final metrics
-        let pm = position_manager.lock().unwrap();
-        let all_positions = pm.all_positions().to_vec();
-        let final_portfolio_value = {
-            let mut prices = HashMap::new();
-            prices.insert(token_symbol.to_string(), final_price);
-            pm.portfolio_value(&prices)
-                .unwrap_or(self.initial_portfolio_value)
-        };
-
-        let metrics = BacktestMetrics::from_positions(
-            all_positions,
-            self.initial_portfolio_value,
-            final_portfolio_value,
-            circuit_breaker_hits,
-        );
+        self.portfolio_runner().run(strategy, &series)
+    }
 
-        tracing::info!(
-            "Backtest complete: {} trades, P&L: ${:.2} ({:.2}%)",
-            metrics.total_trades,
-            metrics.total_pnl,
-            metrics.total_return_pct
-        );
+    /// Runner and strategy parameters, as recorded in the backtest registry
+    pub fn config<S: Strategy + ?Sized>(&self, strategy: &S) -> serde_json::Value {
+        self.portfolio_runner().config(strategy)
+    }
 
-        Ok(metrics)
+    fn portfolio_runner(&self) -> PortfolioBacktestRunner {
+        PortfolioBacktestRunner::new(self.initial_portfolio_value, self.circuit_breakers.clone())
+            .with_sizing(self.sizing.clone())
+            .with_fill_model(self.fill_model.clone())
     }
 
     /// Run backtest and print report
-    pub fn run_and_report<S: Strategy>(
+    pub fn run_and_report<S: Strategy + ?Sized>(
         &self,
         strategy: &S,
         candles: Vec<Candle>,
@@ -199,6 +100,13 @@ impl BacktestRunner {
         println!("   Strategy: {}", strategy.name());
         println!("   Candles: {}", candles.len());
         println!("   Initial Portfolio: ${:.2}", self.initial_portfolio_value);
+        println!("   Position Sizing: {}", self.sizing.name());
+        println!(
+            "   Fills: {:?}, {:.2}% fee, {:.2}% base slippage",
+            self.fill_model.timing,
+            self.fill_model.swap_fee_pct * 100.0,
+            self.fill_model.base_slippage_pct * 100.0
+        );
 
         let metrics = self.run(strategy, candles, token_symbol)?;
         metrics.print_report();
@@ -211,6 +119,7 @@ impl BacktestRunner {
 mod tests {
     use super::*;
     use crate::backtest::synthetic::{MarketScenario, SyntheticDataGenerator};
+    use crate::risk::BreakerActions;
     use crate::strategy::momentum::MomentumStrategy;
 
     #[test]
@@ -306,6 +215,7 @@ mod tests {
             max_consecutive_losses: 2,
             max_position_size_pct: 0.05,
             max_daily_trades: 10,
+            actions: BreakerActions::recommended(),
         };
 
         let runner = BacktestRunner::new(10000.0, circuit_breakers);
@@ -317,6 +227,47 @@ mod tests {
         println!("Metrics: {:?}", metrics);
 
         // With drawdown scenario and tight breakers, should hit them
-        // (though this depends on strategy behavior)
+        // (though this depends on strategy behavior). Every recorded trip must
+        // carry the simulated candle time, not wall-clock time.
+        for event in &metrics.circuit_breaker_events {
+            assert!(event.timestamp < chrono::Utc::now() - chrono::Duration::minutes(1));
+        }
+    }
+
+    #[test]
+    fn test_backtest_with_sizing_methods() {
+        use crate::risk::VolatilityMeasure;
+
+        let mut gen = SyntheticDataGenerator::new(42);
+        let candles = gen.generate(MarketScenario::FlashCrash, 500, 5);
+        let strategy = MomentumStrategy::default();
+
+        let sizings = vec![
+            PositionSizing::FixedFractional { fraction: 0.05 },
+            PositionSizing::VolatilityTarget {
+                target_volatility_pct: 0.0005,
+                period: 14,
+                measure: VolatilityMeasure::Atr,
+            },
+            PositionSizing::FixedRisk { risk_pct: 0.002 },
+            PositionSizing::Kelly {
+                kelly_fraction: 0.5,
+                min_trades: 10,
+                fallback_fraction: 0.02,
+            },
+        ];
+
+        for sizing in sizings {
+            // Frictionless fills so entry prices match the sized reference price
+            let runner = BacktestRunner::new(10000.0, CircuitBreakers::default())
+                .with_sizing(sizing)
+                .with_fill_model(FillModel::frictionless());
+            let metrics = runner.run(&strategy, candles.clone(), "SYNTH").unwrap();
+
+            // No position may exceed the 5% max position cap
+            for trade in &metrics.trades {
+                assert!(trade.entry_price * trade.quantity <= 10000.0 * 0.05 + 1e-6);
+            }
+        }
     }
 }
@@ -0,0 +1,460 @@
+use crate::backtest::batch::BatchExecutor;
+use crate::backtest::fills::FillModel;
+use crate::backtest::monte_carlo::MonteCarloConfig;
+use crate::backtest::portfolio::PortfolioBacktestRunner;
+use crate::backtest::price_models::{ModelKind, SyntheticModel};
+use crate::backtest::report::ReportFormat;
+use crate::backtest::synthetic::{MarketScenario, SyntheticSource};
+use crate::models::Candle;
+use crate::risk::{CircuitBreakers, CorrelationLimits, PositionSizing};
+use crate::strategy::buy_and_hold::BuyAndHoldStrategy;
+use crate::strategy::momentum::MomentumStrategy;
+use crate::strategy::signals::SignalConfig;
+use crate::strategy::Strategy;
+use crate::Result;
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// Token list entry that selects every `snapshots:*` key in Redis
+pub const ALL_TOKENS: &str = "all";
+
+/// Strategies that can be backtested from the CLI
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum StrategyKind {
+    #[default]
+    Momentum,
+    BuyAndHold,
+}
+
+impl StrategyKind {
+    /// Build the strategy (`signal` only applies to momentum)
+    pub fn build(&self, signal: &SignalConfig) -> Box<dyn Strategy> {
+        match self {
+            Self::Momentum => Box::new(MomentumStrategy::new(signal.clone())),
+            Self::BuyAndHold => Box::new(BuyAndHoldStrategy::new()),
+        }
+    }
+}
+
+impl fmt::Display for StrategyKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Momentum => "momentum",
+            Self::BuyAndHold => "buy-and-hold",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for StrategyKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim().to_lowercase().replace('_', "-").as_str() {
+            "momentum" => Ok(Self::Momentum),
+            "buy-and-hold" | "buyandhold" | "hold" => Ok(Self::BuyAndHold),
+            other => Err(format!(
+                "Unknown strategy '{}' (expected momentum or buy-and-hold)",
+                other
+            )),
+        }
+    }
+}
+
+/// Everything a backtest needs, loadable from TOML and overridable by CLI flags
+///
+/// Missing keys fall back to the live bot's defaults.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BacktestSpec {
+    /// Label used in reports and the run registry
+    pub name: Option<String>,
+    /// Token symbols with candles in Redis, or `["all"]`
+    pub tokens: Vec<String>,
+    /// Synthetic scenarios to run instead of Redis data (each runs on its own)
+    pub scenarios: Vec<MarketScenario>,
+    /// Price models (GBM, GARCH, jump diffusion, regimes) to run like scenarios
+    pub models: Vec<SyntheticModel>,
+    /// Fit the models' parameters to this token's stored candles
+    pub fit_token: Option<String>,
+    /// Read stored candles from files in this directory (see `cryptobot data
+    /// export`) instead of Redis
+    pub data_dir: Option<PathBuf>,
+    /// Seed and length for synthetic scenarios and models
+    pub seed: u64,
+    pub candles: usize,
+    /// Data range (defaults: everything stored up to now)
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    pub strategy: StrategyKind,
+    /// Momentum signal parameters
+    pub signal: SignalConfig,
+    pub capital: f64,
+    pub circuit_breakers: CircuitBreakers,
+    pub sizing: PositionSizing,
+    pub fill_model: FillModel,
+    pub correlation_limits: Option<CorrelationLimits>,
+    /// Also backtest each token on its own, not just as one portfolio
+    pub per_token: bool,
+    /// Token whose stored candles every run is compared against (stored data
+    /// only; each run is always compared to buy-and-hold of its own tokens)
+    pub benchmark: Option<String>,
+    /// Resample each run's trades (and re-run synthetic scenarios across seeds)
+    pub monte_carlo: Option<MonteCarloConfig>,
+    /// Worker threads for running backtests in parallel (default: one per core)
+    pub threads: Option<usize>,
+    pub output: ReportFormat,
+    pub output_dir: PathBuf,
+}
+
+impl Default for BacktestSpec {
+    fn default() -> Self {
+        Self {
+            name: None,
+            tokens: vec![ALL_TOKENS.to_string()],
+            scenarios: vec![],
+            models: vec![],
+            fit_token: None,
+            data_dir: None,
+            seed: 42,
+            candles: 500,
+            from: None,
+            to: None,
+            strategy: StrategyKind::default(),
+            signal: SignalConfig::default(),
+            capital: 10000.0,
+            circuit_breakers: CircuitBreakers::default(),
+            sizing: PositionSizing::default(),
+            fill_model: FillModel::default(),
+            correlation_limits: None,
+            per_token: false,
+            benchmark: Some("SOL".to_string()),
+            monte_carlo: None,
+            threads: None,
+            output: ReportFormat::default(),
+            output_dir: PathBuf::from("backtest-reports"),
+        }
+    }
+}
+
+impl BacktestSpec {
+    /// Load a spec from a TOML file
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let spec = config::Config::builder()
+            .add_source(config::File::from(path).format(config::FileFormat::Toml))
+            .build()?
+            .try_deserialize()?;
+        Ok(spec)
+    }
+
+    /// Reject settings that would run but produce meaningless results
+    ///
+    /// Checked after CLI flags are merged over the TOML file.
+    pub fn validate(&self) -> Result<()> {
+        if !self.capital.is_finite() || self.capital <= 0.0 {
+            return Err(format!(
+                "Invalid capital: {} (expected a positive number)",
+                self.capital
+            )
+            .into());
+        }
+        if self.candles == 0 {
+            return Err("Invalid candles: 0 (expected at least 1)".into());
+        }
+        if self.threads == Some(0) {
+            return Err("Invalid threads: 0 (expected at least 1)".into());
+        }
+        if let (Some(from), Some(to)) = (self.from, self.to) {
+            if from >= to {
+                return Err(
+                    format!("Invalid date range: from {} is not before to {}", from, to).into(),
+                );
+            }
+        }
+        let interpolated = self.signal.max_interpolated_fraction;
+        if !(0.0..=1.0).contains(&interpolated) {
+            return Err(format!(
+                "Invalid signal max_interpolated_fraction: {} (expected 0.0 to 1.0)",
+                interpolated
+            )
+            .into());
+        }
+        self.sizing.validate()?;
+        self.fill_model.validate()?;
+        Ok(())
+    }
+
+    /// Whether every token stored in Redis was requested
+    pub fn all_tokens(&self) -> bool {
+        self.tokens
+            .iter()
+            .any(|t| t.eq_ignore_ascii_case(ALL_TOKENS))
+    }
+
+    /// Synthetic scenarios followed by price models (empty = use stored candles)
+    pub fn synthetic_sources(&self) -> Vec<SyntheticSource> {
+        self.scenarios
+            .iter()
+            .map(|scenario| SyntheticSource::Scenario(*scenario))
+            .chain(self.models.iter().cloned().map(SyntheticSource::Model))
+            .collect()
+    }
+
+    /// Replace the models with ones fitted to `candles` of `fit_token`
+    ///
+    /// Keeps the configured model kinds (every kind when none are configured).
+    pub fn fit_models(&mut self, candles: &[Candle]) -> Result<()> {
+        let token = self.fit_token.clone().unwrap_or_default();
+        let kinds: Vec<ModelKind> = if self.models.is_empty() {
+            ModelKind::ALL.to_vec()
+        } else {
+            self.models.iter().map(|m| m.price.kind()).collect()
+        };
+
+        self.models = kinds
+            .into_iter()
+            .map(|kind| {
+                let mut model = SyntheticModel::fit(kind, candles)?;
+                model.name = Some(format!("{} fit {}", kind, token));
+                Ok(model)
+            })
+            .collect::<Result<_>>()?;
+        Ok(())
+    }
+
+    pub fn build_strategy(&self) -> Box<dyn Strategy> {
+        self.strategy.build(&self.signal)
+    }
+
+    /// Portfolio runner with this spec's capital, breakers, sizing, fills and limits
+    pub fn runner(&self) -> PortfolioBacktestRunner {
+        let runner = PortfolioBacktestRunner::new(self.capital, self.circuit_breakers.clone())
+            .with_sizing(self.sizing.clone())
+            .with_fill_model(self.fill_model.clone());
+        match &self.correlation_limits {
+            Some(limits) => runner.with_correlation_limits(limits.clone()),
+            None => runner,
+        }
+    }
+
+    /// Parallel executor for this spec's runs, with a progress counter
+    pub fn executor(&self) -> BatchExecutor {
+        let executor = BatchExecutor::new().with_progress(true);
+        match self.threads {
+            Some(threads) => executor.with_threads(threads),
+            None => executor,
+        }
+    }
+
+    /// Display name: the configured name, or strategy + data source
+    pub fn label(&self) -> String {
+        if let Some(name) = &self.name {
+            return name.clone();
+        }
+        let source = if !self.scenarios.is_empty() || !self.models.is_empty() {
+            "synthetic".to_string()
+        } else if self.all_tokens() {
+            "all tokens".to_string()
+        } else {
+            self.tokens.join(",")
+        };
+        format!("{} {}", self.strategy, source)
+    }
+}
+
+/// Parse `2024-06-01` (midnight UTC) or an RFC 3339 timestamp
+pub fn parse_date(s: &str) -> std::result::Result<DateTime<Utc>, String> {
+    if let Ok(timestamp) = DateTime::parse_from_rfc3339(s) {
+        return Ok(timestamp.with_timezone(&Utc));
+    }
+    NaiveDate::parse_from_str(s, "%Y-%m-%d")
+        .map(|date| date.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc())
+        .map_err(|_| format!("Invalid date '{}' (expected YYYY-MM-DD or RFC 3339)", s))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backtest::fills::ExecutionTiming;
+
+    #[test]
+    fn test_spec_from_toml_overrides_defaults() {
+        let path = std::env::temp_dir().join(format!("spec-{}.toml", uuid::Uuid::new_v4()));
+        std::fs::write(
+            &path,
+            r#"
+name = "weekly momentum"
+tokens = ["SOL", "JUP"]
+from = "2024-06-01T00:00:00Z"
+strategy = "buy-and-hold"
+capital = 5000
+output = "json"
+
+[signal]
+rsi_oversold = 35.0
+
+[circuit_breakers]
+max_drawdown_pct = 0.1
+
+[sizing]
+method = "fixed_fractional"
+fraction = 0.02
+
+[fill_model]
+swap_fee_pct = 0.003
+timing = "same_candle_close"
+"#,
+        )
+        .unwrap();
+
+        let spec = BacktestSpec::from_file(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(spec.name.as_deref(), Some("weekly momentum"));
+        assert_eq!(spec.tokens, vec!["SOL", "JUP"]);
+        assert!(!spec.all_tokens());
+        assert_eq!(spec.from, Some(parse_date("2024-06-01").unwrap()));
+        assert_eq!(spec.strategy, StrategyKind::BuyAndHold);
+        assert_eq!(spec.capital, 5000.0);
+        assert_eq!(spec.output, ReportFormat::Json);
+        assert_eq!(
+            spec.sizing,
+            PositionSizing::FixedFractional { fraction: 0.02 }
+        );
+
+        // Partial tables keep the remaining defaults
+        assert_eq!(spec.signal.rsi_oversold, 35.0);
+        assert_eq!(spec.signal.rsi_period, 14);
+        assert_eq!(spec.circuit_breakers.max_drawdown_pct, 0.1);
+        assert_eq!(spec.circuit_breakers.max_daily_trades, 10);
+        assert_eq!(spec.fill_model.swap_fee_pct, 0.003);
+        assert_eq!(spec.fill_model.timing, ExecutionTiming::SameCandleClose);
+        assert_eq!(spec.fill_model.priority_fee_usd, 0.02);
+    }
+
+    #[test]
+    fn test_validate_rejects_meaningless_settings() {
+        assert!(BacktestSpec::default().validate().is_ok());
+
+        let invalid: Vec<fn(&mut BacktestSpec)> = vec![
+            |s| s.capital = 0.0,
+            |s| s.capital = f64::NAN,
+            |s| s.candles = 0,
+            |s| s.threads = Some(0),
+            |s| s.fill_model.swap_fee_pct = -0.001,
+            |s| s.fill_model.base_slippage_pct = f64::INFINITY,
+            |s| s.fill_model.liquidity_usd = Some(0.0),
+            |s| s.sizing = PositionSizing::FixedFractional { fraction: 1.5 },
+            |s| s.signal.max_interpolated_fraction = f64::NAN,
+            |s| s.signal.max_interpolated_fraction = -0.1,
+            |s| s.signal.max_interpolated_fraction = 1.5,
+            |s| {
+                s.from = Some(parse_date("2024-06-02").unwrap());
+                s.to = Some(parse_date("2024-06-01").unwrap());
+            },
+        ];
+        for (i, break_spec) in invalid.into_iter().enumerate() {
+            let mut spec = BacktestSpec::default();
+            break_spec(&mut spec);
+            assert!(spec.validate().is_err(), "case {}", i);
+        }
+    }
+
+    #[test]
+    fn test_defaults_select_all_tokens() {
+        let spec = BacktestSpec::default();
+        assert!(spec.all_tokens());
+        assert_eq!(spec.label(), "momentum all tokens");
+        assert_eq!(spec.build_strategy().name(), "MomentumStrategy");
+    }
+
+    #[test]
+    fn test_models_from_toml() {
+        let path = std::env::temp_dir().join(format!("spec-{}.toml", uuid::Uuid::new_v4()));
+        std::fs::write(
+            &path,
+            r#"
+scenarios = ["flash-crash"]
+
+[[models]]
+name = "rug pull"
+model = "jump_diffusion"
+drift = 0.0
+volatility = 0.01
+jump_probability = 0.01
+jump_mean = -0.3
+jump_volatility = 0.1
+
+[[models]]
+model = "garch"
+drift = 0.0
+omega = 0.000005
+alpha = 0.1
+beta = 0.85
+start_price = 0.002
+
+[models.volume]
+sensitivity = 3.0
+"#,
+        )
+        .unwrap();
+
+        let spec = BacktestSpec::from_file(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(spec.models.len(), 2);
+        assert_eq!(spec.models[0].name(), "rug pull");
+        assert_eq!(spec.models[0].price.kind(), ModelKind::JumpDiffusion);
+        assert_eq!(spec.models[0].start_price, 150.0);
+        assert_eq!(spec.models[1].name(), "garch");
+        assert_eq!(spec.models[1].start_price, 0.002);
+        assert_eq!(spec.models[1].volume.sensitivity, 3.0);
+        assert_eq!(spec.models[1].volume.base, 1_000_000.0);
+
+        let names: Vec<String> = spec.synthetic_sources().iter().map(|s| s.name()).collect();
+        assert_eq!(names, vec!["flash-crash", "rug pull", "garch"]);
+        assert_eq!(spec.label(), "momentum synthetic");
+    }
+
+    #[test]
+    fn test_fit_models_keeps_configured_kinds() {
+        let mut generator = crate::backtest::SyntheticDataGenerator::new(1);
+        let candles = generator.generate(MarketScenario::Volatile, 300, 5);
+
+        let mut spec = BacktestSpec {
+            fit_token: Some("SOL".to_string()),
+            models: vec![SyntheticModel::preset(ModelKind::Garch)],
+            ..BacktestSpec::default()
+        };
+        spec.fit_models(&candles).unwrap();
+        assert_eq!(spec.models.len(), 1);
+        assert_eq!(spec.models[0].name(), "garch fit SOL");
+
+        spec.models.clear();
+        spec.fit_models(&candles).unwrap();
+        assert_eq!(spec.models.len(), ModelKind::ALL.len());
+    }
+
+    #[test]
+    fn test_parse_date() {
+        assert_eq!(
+            parse_date("2024-06-01").unwrap().to_rfc3339(),
+            "2024-06-01T00:00:00+00:00"
+        );
+        assert!(parse_date("2024-06-01T12:00:00+02:00").is_ok());
+        assert!(parse_date("June 1st").is_err());
+    }
+
+    #[test]
+    fn test_parse_strategy_kind() {
+        assert_eq!(
+            "buy_and_hold".parse::<StrategyKind>().unwrap(),
+            StrategyKind::BuyAndHold
+        );
+        assert!("grid".parse::<StrategyKind>().is_err());
+    }
+}
@@ -1,10 +1,15 @@
-use crate::models::Candle;
+use crate::backtest::price_models::SyntheticModel;
+use crate::models::{Candle, CandleProvenance};
 use chrono::{DateTime, Duration, Utc};
 use rand::rngs::StdRng;
 use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
 
 /// Market scenario types for synthetic data generation
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
 pub enum MarketScenario {
     /// Steady uptrend with noise (+2% daily average)
     Uptrend,
@@ -22,6 +27,76 @@ pub enum MarketScenario {
     FlashCrash,
     /// Volatile uptrend with deep pullbacks - SHOULD BUY on dips
     VolatileUptrend,
+    /// Pump, then liquidity pulled: price gaps to ~2% in one candle and volume
+    /// goes to ~0 (stops can't fill at their level)
+    RugPull,
+    /// Token death: steady bleed to <10% with dead-cat bounces and fading volume
+    SlowBleed,
+    /// Sideways price while volume (and so pool liquidity) drains to nothing
+    LiquidityDrain,
+}
+
+impl MarketScenario {
+    pub const ALL: [MarketScenario; 11] = [
+        Self::Uptrend,
+        Self::Downtrend,
+        Self::Sideways,
+        Self::Volatile,
+        Self::WithGaps,
+        Self::DrawdownTest,
+        Self::FlashCrash,
+        Self::VolatileUptrend,
+        Self::RugPull,
+        Self::SlowBleed,
+        Self::LiquidityDrain,
+    ];
+}
+
+impl fmt::Display for MarketScenario {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Uptrend => "uptrend",
+            Self::Downtrend => "downtrend",
+            Self::Sideways => "sideways",
+            Self::Volatile => "volatile",
+            Self::WithGaps => "with-gaps",
+            Self::DrawdownTest => "drawdown-test",
+            Self::FlashCrash => "flash-crash",
+            Self::VolatileUptrend => "volatile-uptrend",
+            Self::RugPull => "rug-pull",
+            Self::SlowBleed => "slow-bleed",
+            Self::LiquidityDrain => "liquidity-drain",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for MarketScenario {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let name = s.trim().to_lowercase().replace('_', "-");
+        Self::ALL
+            .into_iter()
+            .find(|scenario| scenario.to_string() == name)
+            .ok_or_else(|| format!("Unknown market scenario '{}'", s))
+    }
+}
+
+/// Where a synthetic series comes from: a hand-written scenario or a price model
+#[derive(Debug, Clone, PartialEq)]
+pub enum SyntheticSource {
+    Scenario(MarketScenario),
+    Model(SyntheticModel),
+}
+
+impl SyntheticSource {
+    pub fn name(&self) -> String {
+        match self {
+            Self::Scenario(scenario) => scenario.to_string(),
+            Self::Model(model) => model.name(),
+        }
+    }
 }
 
 /// Generates synthetic price data for backtesting
@@ -57,7 +132,19 @@ impl SyntheticDataGenerator {
         interval_minutes: i64,
     ) -> Vec<Candle> {
         let start_time = Utc::now() - Duration::minutes(num_candles as i64 * interval_minutes);
+        self.generate_at(scenario, start_time, num_candles, interval_minutes)
+    }
 
+    /// Generate candles starting at a fixed time
+    ///
+    /// Use this to build several series that share timestamps (e.g., portfolio backtests).
+    pub fn generate_at(
+        &mut self,
+        scenario: MarketScenario,
+        start_time: DateTime<Utc>,
+        num_candles: usize,
+        interval_minutes: i64,
+    ) -> Vec<Candle> {
         match scenario {
             MarketScenario::Uptrend => {
                 self.generate_uptrend(start_time, num_candles, interval_minutes)
@@ -83,6 +170,59 @@ impl SyntheticDataGenerator {
             MarketScenario::VolatileUptrend => {
                 self.generate_volatile_uptrend(start_time, num_candles, interval_minutes)
             }
+            MarketScenario::RugPull => {
+                self.generate_rug_pull(start_time, num_candles, interval_minutes)
+            }
+            MarketScenario::SlowBleed => {
+                self.generate_slow_bleed(start_time, num_candles, interval_minutes)
+            }
+            MarketScenario::LiquidityDrain => {
+                self.generate_liquidity_drain(start_time, num_candles, interval_minutes)
+            }
+        }
+    }
+
+    /// Generate candles from a price model (GBM, GARCH, jumps, regimes)
+    pub fn generate_model(
+        &mut self,
+        model: &SyntheticModel,
+        num_candles: usize,
+        interval_minutes: i64,
+    ) -> Vec<Candle> {
+        let start_time = Utc::now() - Duration::minutes(num_candles as i64 * interval_minutes);
+        self.generate_model_at(model, start_time, num_candles, interval_minutes)
+    }
+
+    /// Generate model candles starting at a fixed time
+    pub fn generate_model_at(
+        &mut self,
+        model: &SyntheticModel,
+        start_time: DateTime<Utc>,
+        num_candles: usize,
+        interval_minutes: i64,
+    ) -> Vec<Candle> {
+        model.generate(&mut self.rng, start_time, num_candles, interval_minutes)
+    }
+
+    /// Generate candles from either kind of source, starting at `start_time` or
+    /// ending now
+    pub fn generate_source(
+        &mut self,
+        source: &SyntheticSource,
+        start_time: Option<DateTime<Utc>>,
+        num_candles: usize,
+        interval_minutes: i64,
+    ) -> Vec<Candle> {
+        let start_time = start_time.unwrap_or_else(|| {
+            Utc::now() - Duration::minutes(num_candles as i64 * interval_minutes)
+        });
+        match source {
+            SyntheticSource::Scenario(scenario) => {
+                self.generate_at(*scenario, start_time, num_candles, interval_minutes)
+            }
+            SyntheticSource::Model(model) => {
+                self.generate_model_at(model, start_time, num_candles, interval_minutes)
+            }
         }
     }
 
@@ -363,6 +503,111 @@ impl SyntheticDataGenerator {
         candles
     }
 
+    /// Generate rug pull: pump with pullbacks for 70% of the series, then the
+    /// liquidity is pulled and the next candle opens at ~2% of the price
+    ///
+    /// After the rug the token is dead: tiny drifting price, almost no volume.
+    fn generate_rug_pull(
+        &mut self,
+        start_time: DateTime<Utc>,
+        num_candles: usize,
+        interval_minutes: i64,
+    ) -> Vec<Candle> {
+        let mut candles = Vec::with_capacity(num_candles);
+        let mut current_price = self.base_price;
+        let rug_index = num_candles * 7 / 10;
+
+        for i in 0..num_candles {
+            let timestamp = start_time + Duration::minutes(i as i64 * interval_minutes);
+
+            let volume_multiplier = if i < rug_index {
+                // Pump: +0.2% per candle, sharp pullbacks to attract dip buyers,
+                // volume building from 1x to 3x
+                let pullback = if (30..38).contains(&(i % 60)) {
+                    -0.015
+                } else {
+                    0.0
+                };
+                current_price *= 1.002 + pullback + self.rng.gen_range(-0.004..0.004);
+                1.0 + 2.0 * i as f64 / rug_index as f64
+            } else {
+                if i == rug_index {
+                    current_price *= 0.02; // LP removed: gap straight through any stop
+                }
+                current_price *= 1.0 + self.rng.gen_range(-0.02..0.019);
+                0.0005 // A few hundred dollars of volume
+            };
+
+            let candle =
+                self.create_candle_with_volume(current_price, timestamp, volume_multiplier);
+            candles.push(candle);
+        }
+
+        candles
+    }
+
+    /// Generate slow bleed: -0.7% per candle (>90% down over 500 candles) with
+    /// dead-cat bounces every 40 candles, volume fading to under 1%
+    fn generate_slow_bleed(
+        &mut self,
+        start_time: DateTime<Utc>,
+        num_candles: usize,
+        interval_minutes: i64,
+    ) -> Vec<Candle> {
+        let mut candles = Vec::with_capacity(num_candles);
+        let mut current_price = self.base_price;
+
+        for i in 0..num_candles {
+            let timestamp = start_time + Duration::minutes(i as i64 * interval_minutes);
+            let progress = i as f64 / num_candles as f64;
+
+            // Dead-cat bounce: +1% per candle for 5 candles, on a volume spike
+            let bounce = (i % 40) < 5 && i >= 40;
+            let change = if bounce { 0.01 } else { -0.007 };
+            current_price *= 1.0 + change + self.rng.gen_range(-0.002..0.002);
+
+            let fading = (-5.0 * progress).exp();
+            let volume_multiplier = if bounce { fading * 2.0 } else { fading };
+
+            let candle =
+                self.create_candle_with_volume(current_price, timestamp, volume_multiplier);
+            candles.push(candle);
+        }
+
+        candles
+    }
+
+    /// Generate liquidity drain: choppy, slightly falling price while volume
+    /// shrinks with the cube of the time left; the last 15% barely trades
+    fn generate_liquidity_drain(
+        &mut self,
+        start_time: DateTime<Utc>,
+        num_candles: usize,
+        interval_minutes: i64,
+    ) -> Vec<Candle> {
+        let mut candles = Vec::with_capacity(num_candles);
+        let mut current_price = self.base_price;
+
+        for i in 0..num_candles {
+            let timestamp = start_time + Duration::minutes(i as i64 * interval_minutes);
+            let progress = i as f64 / num_candles as f64;
+
+            current_price *= 1.0 - 0.0005 + self.rng.gen_range(-0.01..0.01);
+
+            let volume_multiplier = if progress < 0.85 {
+                (1.0 - progress).powi(3)
+            } else {
+                0.0001
+            };
+
+            let candle =
+                self.create_candle_with_volume(current_price, timestamp, volume_multiplier);
+            candles.push(candle);
+        }
+
+        candles
+    }
+
     /// Helper to create a candle from price and timestamp
     fn create_candle(&mut self, price: f64, timestamp: DateTime<Utc>) -> Candle {
         self.create_candle_with_volume(price, timestamp, 1.0)
@@ -397,6 +642,7 @@ impl SyntheticDataGenerator {
             low,
             close: price,
             volume,
+            provenance: CandleProvenance::Live,
         }
     }
 }
@@ -507,4 +753,46 @@ mod tests {
             assert!(candle.low <= candle.open, "Low should be <= open");
         }
     }
+
+    #[test]
+    fn test_rug_pull_gaps_to_zero_and_kills_volume() {
+        let mut gen = SyntheticDataGenerator::new(42);
+        let candles = gen.generate(MarketScenario::RugPull, 500, 5);
+        let rug = 350;
+
+        assert!(candles[rug - 1].close > gen.base_price);
+        // The rug candle opens already collapsed: no chance to sell in between
+        assert!(candles[rug].open < candles[rug - 1].close * 0.03);
+        assert!(candles[rug].high < candles[rug - 1].close * 0.03);
+        assert!(candles[rug..].iter().all(|c| c.volume < 1_000.0));
+        assert!(candles[..rug].iter().all(|c| c.volume > 100_000.0));
+    }
+
+    #[test]
+    fn test_token_death_scenarios_lose_price_and_volume() {
+        let mut gen = SyntheticDataGenerator::new(42);
+        let bleed = gen.generate(MarketScenario::SlowBleed, 500, 5);
+        assert!(bleed.last().unwrap().close < gen.base_price * 0.1);
+        assert!(bleed.last().unwrap().volume < bleed[0].volume * 0.02);
+
+        let drain = gen.generate(MarketScenario::LiquidityDrain, 500, 5);
+        let last = drain.last().unwrap();
+        assert!(last.close > gen.base_price * 0.5, "{}", last.close);
+        assert!(last.volume < 1_000.0);
+        assert!(drain
+            .windows(2)
+            .all(|w| w[1].volume <= w[0].volume * 1.3 / 0.7));
+    }
+
+    #[test]
+    fn test_scenario_names_round_trip() {
+        for scenario in MarketScenario::ALL {
+            assert_eq!(scenario.to_string().parse::<MarketScenario>(), Ok(scenario));
+        }
+        assert_eq!(
+            "flash_crash".parse::<MarketScenario>(),
+            Ok(MarketScenario::FlashCrash)
+        );
+        assert!("moon".parse::<MarketScenario>().is_err());
+    }
 }
@@ -1,4 +1,6 @@
+use crate::backtest::{BacktestMetrics, BacktestRun, BacktestRunSummary};
 use crate::execution::{ExitReason, Position, PositionStatus};
+use crate::risk::{CircuitBreakerEvent, CircuitBreakerTrip};
 use crate::Result;
 use chrono::{DateTime, Utc};
 use sqlx::{postgres::PgPoolOptions, PgPool, Row};
@@ -58,6 +60,7 @@ impl PostgresPersistence {
             ExitReason::TimeStop => "TimeStop",
             ExitReason::Manual => "Manual",
             ExitReason::StrategySell => "StrategySell",
+            ExitReason::CircuitBreaker => "CircuitBreaker",
         });
 
         sqlx::query(
@@ -150,6 +153,7 @@ impl PostgresPersistence {
                 Some("TimeStop") => Some(ExitReason::TimeStop),
                 Some("Manual") => Some(ExitReason::Manual),
                 Some("StrategySell") => Some(ExitReason::StrategySell),
+                Some("CircuitBreaker") => Some(ExitReason::CircuitBreaker),
                 None => None,
                 _ => return Err("Invalid exit reason".into()),
             };
@@ -224,6 +228,7 @@ impl PostgresPersistence {
                 Some("TimeStop") => Some(ExitReason::TimeStop),
                 Some("Manual") => Some(ExitReason::Manual),
                 Some("StrategySell") => Some(ExitReason::StrategySell),
+                Some("CircuitBreaker") => Some(ExitReason::CircuitBreaker),
                 None => None,
                 _ => return Err("Invalid exit reason".into()),
             };
@@ -455,6 +460,231 @@ impl PostgresPersistence {
 
         Ok(())
     }
+
+    /// Record a backtest run in the registry
+    pub async fn save_backtest_run(&self, run: &BacktestRun) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO backtest_runs (
+                id, user_id, name, strategy, tokens, config,
+                data_start, data_end, git_revision,
+                total_return_pct, total_trades, win_rate, max_drawdown_pct, sharpe_ratio,
+                metrics, created_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6::jsonb, $7, $8, $9, $10, $11, $12, $13, $14, $15::jsonb, $16)
+            "#,
+        )
+        .bind(run.id)
+        .bind(self.user_id)
+        .bind(&run.name)
+        .bind(&run.strategy)
+        .bind(&run.tokens)
+        .bind(serde_json::to_string(&run.config)?)
+        .bind(run.data_start)
+        .bind(run.data_end)
+        .bind(&run.git_revision)
+        .bind(run.metrics.total_return_pct)
+        .bind(run.metrics.total_trades as i32)
+        .bind(run.metrics.win_rate)
+        .bind(run.metrics.max_drawdown_pct)
+        .bind(run.metrics.sharpe_ratio)
+        .bind(serde_json::to_string(&run.metrics)?)
+        .bind(run.created_at)
+        .execute(&self.pool)
+        .await?;
+
+        tracing::debug!("Saved backtest run {} ({})", run.id, run.name);
+
+        Ok(())
+    }
+
+    /// Most recent backtest runs, optionally for one strategy
+    pub async fn list_backtest_runs(
+        &self,
+        strategy: Option<&str>,
+        limit: i64,
+    ) -> Result<Vec<BacktestRunSummary>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT id, name, strategy, tokens, data_start, data_end, git_revision,
+                   total_return_pct, total_trades, win_rate, max_drawdown_pct, sharpe_ratio,
+                   created_at
+            FROM backtest_runs
+            WHERE user_id = $1 AND ($2::text IS NULL OR strategy = $2)
+            ORDER BY created_at DESC
+            LIMIT $3
+            "#,
+        )
+        .bind(self.user_id)
+        .bind(strategy)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| BacktestRunSummary {
+                id: row.get("id"),
+                name: row.get("name"),
+                strategy: row.get("strategy"),
+                tokens: row.get("tokens"),
+                data_start: row.get("data_start"),
+                data_end: row.get("data_end"),
+                git_revision: row.get("git_revision"),
+                total_return_pct: row.get("total_return_pct"),
+                total_trades: row.get::<i32, _>("total_trades") as i64,
+                win_rate: row.get("win_rate"),
+                max_drawdown_pct: row.get("max_drawdown_pct"),
+                sharpe_ratio: row.get("sharpe_ratio"),
+                created_at: row.get("created_at"),
+            })
+            .collect())
+    }
+
+    /// Load one backtest run by id or unique id prefix (as shown by `backtests list`)
+    pub async fn load_backtest_run(&self, id_prefix: &str) -> Result<Option<BacktestRun>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT id, name, strategy, tokens, config::text AS config,
+                   data_start, data_end, git_revision, metrics::text AS metrics, created_at
+            FROM backtest_runs
+            WHERE user_id = $1 AND id::text LIKE $2 || '%'
+            LIMIT 2
+            "#,
+        )
+        .bind(self.user_id)
+        .bind(id_prefix.to_lowercase())
+        .fetch_all(&self.pool)
+        .await?;
+
+        if rows.len() > 1 {
+            return Err(format!("Backtest id prefix '{}' is ambiguous", id_prefix).into());
+        }
+        let Some(row) = rows.into_iter().next() else {
+            return Ok(None);
+        };
+
+        let config: String = row.get("config");
+        let metrics: String = row.get("metrics");
+        let metrics: BacktestMetrics = serde_json::from_str(&metrics)?;
+
+        Ok(Some(BacktestRun {
+            id: row.get("id"),
+            name: row.get("name"),
+            strategy: row.get("strategy"),
+            tokens: row.get("tokens"),
+            config: serde_json::from_str(&config)?,
+            data_start: row.get("data_start"),
+            data_end: row.get("data_end"),
+            git_revision: row.get("git_revision"),
+            metrics,
+            created_at: row.get("created_at"),
+        }))
+    }
+
+    /// Delete all backtest runs for the user (for testing)
+    pub async fn clear_backtest_runs(&self) -> Result<()> {
+        sqlx::query("DELETE FROM backtest_runs WHERE user_id = $1")
+            .bind(self.user_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    // ==================== CIRCUIT BREAKER METHODS ====================
+
+    /// Record a circuit breaker trip and the entry pause it started
+    pub async fn save_circuit_breaker_event(
+        &self,
+        event: &CircuitBreakerEvent,
+        paused_until: Option<DateTime<Utc>>,
+    ) -> Result<()> {
+        let trip_str = match event.trip {
+            CircuitBreakerTrip::DailyLoss => "DailyLoss",
+            CircuitBreakerTrip::MaxDrawdown => "MaxDrawdown",
+            CircuitBreakerTrip::ConsecutiveLosses => "ConsecutiveLosses",
+            CircuitBreakerTrip::DailyTradeLimit => "DailyTradeLimit",
+        };
+
+        sqlx::query(
+            r#"
+            INSERT INTO circuit_breaker_events (
+                id, user_id, trip, occurred_at,
+                portfolio_value, peak_portfolio_value, daily_pnl,
+                consecutive_losses, daily_trades, paused_until
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+            "#,
+        )
+        .bind(Uuid::new_v4())
+        .bind(self.user_id)
+        .bind(trip_str)
+        .bind(event.timestamp)
+        .bind(event.portfolio_value)
+        .bind(event.peak_portfolio_value)
+        .bind(event.daily_pnl)
+        .bind(event.consecutive_losses as i32)
+        .bind(event.daily_trades as i32)
+        .bind(paused_until)
+        .execute(&self.pool)
+        .await?;
+
+        tracing::debug!("Saved circuit breaker event {:?}", event.trip);
+
+        Ok(())
+    }
+
+    /// Most recent circuit breaker trips, newest first
+    pub async fn load_circuit_breaker_events(
+        &self,
+        limit: i64,
+    ) -> Result<Vec<CircuitBreakerEvent>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT trip, occurred_at, portfolio_value, peak_portfolio_value, daily_pnl,
+                   consecutive_losses, daily_trades
+            FROM circuit_breaker_events
+            WHERE user_id = $1
+            ORDER BY occurred_at DESC
+            LIMIT $2
+            "#,
+        )
+        .bind(self.user_id)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter()
+            .map(|row| {
+                let trip_str: String = row.get("trip");
+                let trip = match trip_str.as_str() {
+                    "DailyLoss" => CircuitBreakerTrip::DailyLoss,
+                    "MaxDrawdown" => CircuitBreakerTrip::MaxDrawdown,
+                    "ConsecutiveLosses" => CircuitBreakerTrip::ConsecutiveLosses,
+                    "DailyTradeLimit" => CircuitBreakerTrip::DailyTradeLimit,
+                    other => return Err(format!("Unknown circuit breaker trip '{}'", other).into()),
+                };
+                Ok(CircuitBreakerEvent {
+                    trip,
+                    timestamp: row.get("occurred_at"),
+                    portfolio_value: row.get("portfolio_value"),
+                    peak_portfolio_value: row.get("peak_portfolio_value"),
+                    daily_pnl: row.get("daily_pnl"),
+                    consecutive_losses: row.get::<i32, _>("consecutive_losses") as u32,
+                    daily_trades: row.get::<i32, _>("daily_trades") as u32,
+                })
+            })
+            .collect()
+    }
+
+    /// Delete all circuit breaker events for the user (for testing)
+    pub async fn clear_circuit_breaker_events(&self) -> Result<()> {
+        sqlx::query("DELETE FROM circuit_breaker_events WHERE user_id = $1")
+            .bind(self.user_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -693,6 +923,74 @@ mod tests {
         db.clear_all_positions().await.unwrap();
     }
 
+    #[tokio::test]
+    #[ignore] // Requires Postgres running
+    async fn test_save_and_load_backtest_run() {
+        use crate::backtest::DataRange;
+
+        let db = get_test_db().await;
+        db.clear_backtest_runs().await.unwrap();
+
+        let metrics = BacktestMetrics::from_positions(vec![], 10000.0, 10000.0, vec![]);
+        let data = DataRange {
+            tokens: vec!["SOL".to_string(), "JUP".to_string()],
+            start: Utc::now() - chrono::Duration::days(7),
+            end: Utc::now(),
+        };
+        let run = BacktestRun::new(
+            "weekly",
+            "Momentum",
+            serde_json::json!({"sizing": {"fixed_fractional": {"fraction": 0.05}}}),
+            data,
+            metrics,
+        );
+        db.save_backtest_run(&run).await.unwrap();
+
+        let runs = db.list_backtest_runs(Some("Momentum"), 10).await.unwrap();
+        assert_eq!(runs.len(), 1);
+        assert_eq!(runs[0].tokens, vec!["SOL", "JUP"]);
+        assert!(db
+            .list_backtest_runs(Some("Other"), 10)
+            .await
+            .unwrap()
+            .is_empty());
+
+        let loaded = db
+            .load_backtest_run(&run.id.to_string()[..8])
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(loaded.id, run.id);
+        assert_eq!(loaded.config, run.config);
+        assert_eq!(loaded.metrics.total_trades, 0);
+
+        db.clear_backtest_runs().await.unwrap();
+    }
+
+    #[tokio::test]
+    #[ignore] // Requires Postgres running
+    async fn test_save_and_load_circuit_breaker_events() {
+        use crate::risk::TradingState;
+
+        let db = get_test_db().await;
+        db.clear_circuit_breaker_events().await.unwrap();
+
+        let mut state = TradingState::new(10000.0);
+        state.portfolio_value = 8500.0;
+        let now = Utc::now();
+        let event = CircuitBreakerEvent::new(CircuitBreakerTrip::MaxDrawdown, &state, now);
+        db.save_circuit_breaker_event(&event, Some(now + chrono::Duration::hours(24)))
+            .await
+            .unwrap();
+
+        let events = db.load_circuit_breaker_events(10).await.unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].trip, CircuitBreakerTrip::MaxDrawdown);
+        assert_eq!(events[0].portfolio_value, 8500.0);
+
+        db.clear_circuit_breaker_events().await.unwrap();
+    }
+
     // ==================== TOKEN ROTATION TESTS ====================
 
     #[tokio::test]
@@ -0,0 +1,8 @@
+// Trading loop shared by the live bot and replays
+pub mod replay;
+pub mod source;
+pub mod trading;
+
+pub use replay::{NoPriceImpact, ReplaySource};
+pub use source::{LiveMarketData, MarketDataSource, MarketTick};
+pub use trading::{TickOutcome, TradingEngine};
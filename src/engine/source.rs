@@ -0,0 +1,111 @@
+use crate::execution::PositionManager;
+use crate::models::{Candle, Token};
+use crate::persistence::RedisPersistence;
+use crate::risk::HaltState;
+use crate::Result;
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex, RwLock};
+use tokio::time::Interval;
+
+/// Everything the trading engine sees on one tick
+#[derive(Debug, Clone)]
+pub struct MarketTick {
+    pub timestamp: DateTime<Utc>,
+    /// Tokens to evaluate, in order
+    pub tokens: Vec<Token>,
+    /// Lookback candles by symbol (tokens without data are missing); may
+    /// include tokens outside `tokens` that still have open positions
+    pub candles: HashMap<String, Vec<Candle>>,
+    /// Operator halt flag, if the source knows it (`None` keeps the current mode)
+    pub halt: Option<HaltState>,
+}
+
+/// Feeds market snapshots to the trading engine (live Redis polling or a replay)
+pub trait MarketDataSource {
+    /// Wait for the next tick; `None` once the source is exhausted
+    fn next_tick(&mut self) -> impl Future<Output = Result<Option<MarketTick>>> + Send;
+}
+
+/// Live market data: candles written to Redis by the price fetch loop
+pub struct LiveMarketData {
+    redis: RedisPersistence,
+    tokens: Arc<RwLock<Vec<Token>>>,
+    lookback_hours: u64,
+    ticker: Interval,
+    position_manager: Option<Arc<Mutex<PositionManager>>>,
+}
+
+impl LiveMarketData {
+    /// Poll `tokens` (updated by discovery) on every `ticker` tick
+    pub fn new(
+        redis: RedisPersistence,
+        tokens: Arc<RwLock<Vec<Token>>>,
+        lookback_hours: u64,
+        ticker: Interval,
+    ) -> Self {
+        Self {
+            redis,
+            tokens,
+            lookback_hours,
+            ticker,
+            position_manager: None,
+        }
+    }
+
+    /// Also load candles for open positions in tokens no longer tracked, so
+    /// they keep getting exits and can be flattened
+    pub fn with_position_manager(mut self, position_manager: Arc<Mutex<PositionManager>>) -> Self {
+        self.position_manager = Some(position_manager);
+        self
+    }
+}
+
+impl MarketDataSource for LiveMarketData {
+    async fn next_tick(&mut self) -> Result<Option<MarketTick>> {
+        self.ticker.tick().await;
+        let timestamp = Utc::now();
+        tracing::info!("💹 [TRADING] Tick at {}", timestamp.format("%H:%M:%S"));
+
+        // Refresh the operator halt flag (keep the last known mode if Redis fails)
+        let halt = match self.redis.load_halt_state().await {
+            Ok(halt) => Some(halt),
+            Err(e) => {
+                tracing::warn!("Failed to load halt state: {}", e);
+                None
+            }
+        };
+
+        // Get current token list
+        let tokens = self.tokens.read().unwrap().clone();
+
+        let mut symbols: Vec<String> = tokens.iter().map(|t| t.symbol.clone()).collect();
+        if let Some(pm) = &self.position_manager {
+            for position in pm.lock().unwrap().open_positions() {
+                if !symbols.contains(&position.token) {
+                    symbols.push(position.token.clone());
+                }
+            }
+        }
+
+        let mut candles = HashMap::new();
+        for symbol in symbols {
+            match self.redis.load_candles(&symbol, self.lookback_hours).await {
+                Ok(history) => {
+                    candles.insert(symbol, history);
+                }
+                Err(e) => {
+                    tracing::warn!("  ✗ Failed to load candles for {}: {}", symbol, e);
+                }
+            }
+        }
+
+        Ok(Some(MarketTick {
+            timestamp,
+            tokens,
+            candles,
+            halt,
+        }))
+    }
+}
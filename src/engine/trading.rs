@@ -0,0 +1,633 @@
+use crate::db::PostgresPersistence;
+use crate::engine::source::{MarketDataSource, MarketTick};
+use crate::execution::{
+    ExecutionAction, Executor, ExitReason, LiquidityLimits, Position, PositionManager,
+    PositionStatus, QuoteSource,
+};
+use crate::models::{Candle, Token};
+use crate::persistence::RedisPersistence;
+use crate::risk::{
+    BookPosition, BreakerState, CircuitBreakerEvent, CorrelationDecision, CorrelationLimits,
+    HaltMode, HaltState, PortfolioRisk, PositionSizing,
+};
+use crate::strategy::signals::validate_candle_uniformity;
+use crate::strategy::Strategy;
+use crate::Result;
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, RwLock};
+
+/// What one tick did
+#[derive(Debug, Clone)]
+pub struct TickOutcome {
+    pub timestamp: DateTime<Utc>,
+    /// Latest close of every token that passed the data quality checks
+    pub prices: HashMap<String, f64>,
+    /// Positions opened, closed or modified during the tick
+    pub changed: Vec<Position>,
+}
+
+/// The trading loop, independent of where market data comes from
+///
+/// Each tick validates candles, applies the halt flag, turns signals into
+/// orders (through the correlation and liquidity checks), then checks exits
+/// and circuit breakers. The live bot drives it from Redis every 5 minutes;
+/// replays drive it from stored history as fast as they like.
+pub struct TradingEngine<Q: QuoteSource> {
+    strategy: Box<dyn Strategy>,
+    executor: Executor,
+    position_manager: Arc<Mutex<PositionManager>>,
+    quotes: Q,
+    /// Off unless configured (buys go through at full size)
+    liquidity_limits: Option<LiquidityLimits>,
+    /// Pool liquidity (USD) by symbol, as recorded at discovery time
+    token_liquidity: Arc<RwLock<HashMap<String, f64>>>,
+    /// Off unless configured (buys ignore overlap with the open book)
+    correlation_limits: Option<CorrelationLimits>,
+    poll_interval_minutes: u64,
+    simulated_clock: bool,
+    postgres: Option<PostgresPersistence>,
+    redis: Option<RedisPersistence>,
+    /// Breaker state last written to Redis
+    saved_breaker_state: BreakerState,
+}
+
+impl<Q: QuoteSource + Send + Sync> TradingEngine<Q> {
+    pub fn new(
+        strategy: Box<dyn Strategy>,
+        position_manager: Arc<Mutex<PositionManager>>,
+        quotes: Q,
+    ) -> Self {
+        Self {
+            strategy,
+            executor: Executor::new(position_manager.clone()),
+            position_manager,
+            quotes,
+            liquidity_limits: None,
+            token_liquidity: Arc::new(RwLock::new(HashMap::new())),
+            correlation_limits: None,
+            poll_interval_minutes: 5,
+            simulated_clock: false,
+            postgres: None,
+            redis: None,
+            saved_breaker_state: BreakerState::default(),
+        }
+    }
+
+    /// Set the position sizing method (default: fixed 5% of portfolio)
+    pub fn with_sizing(mut self, sizing: PositionSizing) -> Self {
+        self.executor = Executor::new(self.position_manager.clone()).with_sizing(sizing);
+        self
+    }
+
+    /// Candle spacing the strategy and data quality checks expect (default: 5)
+    pub fn with_poll_interval(mut self, poll_interval_minutes: u64) -> Self {
+        self.poll_interval_minutes = poll_interval_minutes;
+        self
+    }
+
+    /// Shrink or skip buys that don't fit the token's liquidity (default: off)
+    pub fn with_liquidity_limits(mut self, limits: LiquidityLimits) -> Self {
+        self.liquidity_limits = Some(limits);
+        self
+    }
+
+    /// Share the pool liquidity map maintained by token discovery
+    pub fn with_token_liquidity(mut self, liquidity: Arc<RwLock<HashMap<String, f64>>>) -> Self {
+        self.token_liquidity = liquidity;
+        self
+    }
+
+    /// Shrink or block buys correlated with the open book (default: off)
+    pub fn with_correlation_limits(mut self, limits: CorrelationLimits) -> Self {
+        self.correlation_limits = Some(limits);
+        self
+    }
+
+    /// Save every changed position to Postgres at the end of each tick
+    pub fn with_postgres(mut self, postgres: PostgresPersistence) -> Self {
+        self.postgres = Some(postgres);
+        self
+    }
+
+    /// Keep the breaker's active trip and entry pause in Redis, so a restart resumes them
+    pub fn with_redis(mut self, redis: RedisPersistence) -> Self {
+        self.redis = Some(redis);
+        self
+    }
+
+    /// Run the position manager on tick timestamps instead of the wall clock (replays)
+    pub fn with_simulated_clock(mut self) -> Self {
+        self.simulated_clock = true;
+        self
+    }
+
+    pub fn position_manager(&self) -> Arc<Mutex<PositionManager>> {
+        self.position_manager.clone()
+    }
+
+    pub fn strategy(&self) -> &dyn Strategy {
+        self.strategy.as_ref()
+    }
+
+    pub fn liquidity_limits(&self) -> Option<&LiquidityLimits> {
+        self.liquidity_limits.as_ref()
+    }
+
+    pub fn correlation_limits(&self) -> Option<&CorrelationLimits> {
+        self.correlation_limits.as_ref()
+    }
+
+    /// Process ticks until the source is exhausted (never, for live data)
+    pub async fn run<M: MarketDataSource>(&mut self, source: &mut M) -> Result<usize> {
+        let mut ticks = 0;
+        while let Some(tick) = source.next_tick().await? {
+            self.process_tick(tick).await;
+            ticks += 1;
+        }
+        Ok(ticks)
+    }
+
+    /// Run one iteration of the trading loop
+    pub async fn process_tick(&mut self, tick: MarketTick) -> TickOutcome {
+        if self.simulated_clock {
+            self.position_manager
+                .lock()
+                .unwrap()
+                .set_time(tick.timestamp);
+        }
+        if let Some(halt) = &tick.halt {
+            self.update_halt_mode(halt);
+        }
+
+        // A kill switch sells whatever has a price, even tokens whose history
+        // fails the quality checks below
+        let mut changed = Vec::new();
+        if self.executor.halt_mode() == HaltMode::FlattenAll {
+            let mut raw_prices = last_closes(&tick.candles);
+            raw_prices.retain(|_, price| price.is_finite() && *price > 0.0);
+            changed.extend(self.flatten_positions(&raw_prices));
+        }
+
+        // Validate candle uniformity early (fail fast), so entry checks only
+        // compare each token against tokens with usable history
+        let market = self.usable_candles(tick.candles);
+        let prices = last_closes(&market);
+
+        let samples_needed = self.strategy.samples_needed(self.poll_interval_minutes);
+        for token in &tick.tokens {
+            let Some(candles) = market.get(&token.symbol) else {
+                continue;
+            };
+
+            tracing::info!(
+                "  {} @ ${:.4} ({} candles)",
+                token.symbol,
+                prices[&token.symbol],
+                candles.len()
+            );
+
+            // Generate signals if we have enough data
+            if candles.len() >= samples_needed {
+                changed.extend(self.process_token_signal(token, &market).await);
+            } else {
+                tracing::info!(
+                    "    → Collecting data... ({}/{} needed)",
+                    candles.len(),
+                    samples_needed
+                );
+            }
+        }
+
+        // Check exit conditions for all open positions
+        changed.extend(self.check_exits(&prices));
+
+        // Check circuit breakers (may tighten stops or flatten the book)
+        let (derisked, trip) = self.check_circuit_breakers(&prices);
+        changed.extend(derisked);
+
+        self.log_portfolio_summary(&prices);
+        self.log_portfolio_risk(&market);
+        self.save_positions(&changed).await;
+        self.save_breaker_state(trip.as_ref()).await;
+
+        TickOutcome {
+            timestamp: tick.timestamp,
+            prices,
+            changed,
+        }
+    }
+
+    fn update_halt_mode(&mut self, halt: &HaltState) {
+        if halt.mode != self.executor.halt_mode() {
+            tracing::warn!(
+                "🛑 Halt mode changed: {} -> {} ({})",
+                self.executor.halt_mode(),
+                halt.mode,
+                halt.reason.as_deref().unwrap_or("no reason given")
+            );
+        }
+        self.executor.set_halt_mode(halt.mode);
+    }
+
+    /// Drop tokens whose history is empty or has gaps
+    fn usable_candles(
+        &self,
+        candles: HashMap<String, Vec<Candle>>,
+    ) -> HashMap<String, Vec<Candle>> {
+        let expected_interval_secs = self.poll_interval_minutes * 60;
+        candles
+            .into_iter()
+            .filter(|(symbol, candles)| {
+                if let Err(e) = validate_candle_uniformity(candles, expected_interval_secs) {
+                    tracing::warn!("  {} - Skipping due to data quality issue: {}", symbol, e);
+                    return false;
+                }
+                !candles.is_empty()
+            })
+            .collect()
+    }
+
+    async fn process_token_signal(
+        &mut self,
+        token: &Token,
+        market: &HashMap<String, Vec<Candle>>,
+    ) -> Option<Position> {
+        let candles = market.get(&token.symbol)?;
+        let current_price = candles.last()?.close;
+
+        let signal = match self.strategy.generate_signal(candles) {
+            Ok(signal) => signal,
+            Err(e) => {
+                tracing::warn!("  → Failed to generate signal: {}", e);
+                return None;
+            }
+        };
+        tracing::info!("  Signal: {:?}", signal);
+
+        let decision =
+            match self
+                .executor
+                .process_signal_with_candles(&signal, &token.symbol, candles)
+            {
+                Ok(decision) => decision,
+                Err(e) => {
+                    tracing::error!("  Failed to process signal: {}", e);
+                    return None;
+                }
+            };
+        tracing::info!("  Decision: {:?} - {}", decision.action, decision.reason);
+
+        match decision.action {
+            ExecutionAction::Execute { quantity } => {
+                let quantity =
+                    self.apply_correlation_limits(token, current_price, quantity, market)?;
+                let quantity = self
+                    .apply_liquidity_limits(token, current_price, quantity)
+                    .await?;
+                self.execute_buy(token, current_price, quantity)
+            }
+            ExecutionAction::Close {
+                position_id,
+                exit_reason,
+            } => self.execute_close(position_id, current_price, exit_reason),
+            ExecutionAction::Skip => None, // Already logged
+        }
+    }
+
+    /// Shrink or block a buy that overlaps the open book (returns None to skip the buy)
+    fn apply_correlation_limits(
+        &self,
+        token: &Token,
+        current_price: f64,
+        quantity: f64,
+        market: &HashMap<String, Vec<Candle>>,
+    ) -> Option<f64> {
+        let Some(limits) = &self.correlation_limits else {
+            return Some(quantity);
+        };
+        let candles = market.get(&token.symbol)?;
+        let pm = self.position_manager.lock().unwrap();
+        let open_positions = pm.open_positions();
+        let book = book_positions(&open_positions, market);
+        let prices: HashMap<String, f64> = market
+            .iter()
+            .filter_map(|(symbol, c)| c.last().map(|last| (symbol.clone(), last.close)))
+            .collect();
+        let portfolio_value = pm
+            .portfolio_value(&prices)
+            .unwrap_or(pm.trading_state().portfolio_value);
+
+        match limits.check(candles, quantity * current_price, &book, portfolio_value) {
+            CorrelationDecision::Allow => Some(quantity),
+            CorrelationDecision::Downsize { factor, reason } => {
+                tracing::info!(
+                    "  {} (qty {:.4} -> {:.4})",
+                    reason,
+                    quantity,
+                    quantity * factor
+                );
+                Some(quantity * factor).filter(|q| *q > 0.0)
+            }
+            CorrelationDecision::Block { reason } => {
+                tracing::info!("  ✗ Skipping buy for {}: {}", token.symbol, reason);
+                None
+            }
+        }
+    }
+
+    /// Shrink a buy so it fits the token's liquidity (returns None to skip the buy)
+    async fn apply_liquidity_limits(
+        &self,
+        token: &Token,
+        current_price: f64,
+        quantity: f64,
+    ) -> Option<f64> {
+        let Some(limits) = &self.liquidity_limits else {
+            return Some(quantity);
+        };
+        let liquidity_usd = self
+            .token_liquidity
+            .read()
+            .unwrap()
+            .get(&token.symbol)
+            .copied();
+
+        match limits
+            .adjust_buy(
+                &self.quotes,
+                &token.mint_address,
+                quantity,
+                current_price,
+                liquidity_usd,
+            )
+            .await
+        {
+            Ok(adjustment) => {
+                tracing::info!(
+                    "  {} (qty {:.4} -> {:.4})",
+                    adjustment.reason,
+                    quantity,
+                    adjustment.quantity
+                );
+                Some(adjustment.quantity).filter(|q| *q > 0.0)
+            }
+            Err(e) => {
+                tracing::warn!(
+                    "  ✗ Liquidity check failed for {}, skipping buy: {}",
+                    token.symbol,
+                    e
+                );
+                None
+            }
+        }
+    }
+
+    fn execute_buy(&self, token: &Token, current_price: f64, quantity: f64) -> Option<Position> {
+        tracing::info!(
+            "  → Would BUY {:.4} {} @ ${:.4} (total: ${:.2})",
+            quantity,
+            token.symbol,
+            current_price,
+            quantity * current_price
+        );
+
+        let mut pm = self.position_manager.lock().unwrap();
+        match pm.open_position(token.symbol.clone(), current_price, quantity) {
+            Ok(position_id) => {
+                tracing::info!("  ✓ Opened position {} for {}", position_id, token.symbol);
+                find_position(&pm, position_id)
+            }
+            Err(e) => {
+                tracing::error!("  ✗ Failed to open position: {}", e);
+                None
+            }
+        }
+    }
+
+    fn execute_close(
+        &self,
+        position_id: uuid::Uuid,
+        current_price: f64,
+        exit_reason: ExitReason,
+    ) -> Option<Position> {
+        let mut pm = self.position_manager.lock().unwrap();
+        match pm.close_position(position_id, current_price, exit_reason) {
+            Ok(()) => {
+                tracing::info!(
+                    "  ✓ Closed position {} @ ${:.4}",
+                    position_id,
+                    current_price
+                );
+                find_position(&pm, position_id)
+            }
+            Err(e) => {
+                tracing::error!("  ✗ Failed to close position: {}", e);
+                None
+            }
+        }
+    }
+
+    /// Close every open position at the current price (halt mode flatten-all)
+    fn flatten_positions(&self, prices: &HashMap<String, f64>) -> Vec<Position> {
+        let mut pm = self.position_manager.lock().unwrap();
+        let closed = match pm.close_all(prices, ExitReason::Manual) {
+            Ok(closed_ids) => closed_ids
+                .iter()
+                .filter_map(|id| find_position(&pm, *id))
+                .inspect(|p| tracing::warn!("🛑 Flattened {} (position {})", p.token, p.id))
+                .collect(),
+            Err(e) => {
+                tracing::error!("Failed to flatten positions: {}", e);
+                Vec::new()
+            }
+        };
+
+        for position in pm.open_positions() {
+            tracing::error!(
+                "🛑 Cannot flatten {} (position {}): no price this tick - the book is NOT flat",
+                position.token,
+                position.id
+            );
+        }
+        closed
+    }
+
+    fn check_exits(&self, prices: &HashMap<String, f64>) -> Vec<Position> {
+        let mut pm = self.position_manager.lock().unwrap();
+        match pm.check_exits(prices) {
+            Ok(closed_ids) => closed_ids
+                .into_iter()
+                .inspect(|id| tracing::info!("✓ Position {} closed by exit condition", id))
+                .filter_map(|id| find_position(&pm, id))
+                .collect(),
+            Err(e) => {
+                tracing::error!("Failed to check exits: {}", e);
+                Vec::new()
+            }
+        }
+    }
+
+    /// Record circuit breaker trips; returns positions changed by de-risking
+    /// actions and the new trip, if any
+    fn check_circuit_breakers(
+        &self,
+        prices: &HashMap<String, f64>,
+    ) -> (Vec<Position>, Option<CircuitBreakerEvent>) {
+        let mut pm = self.position_manager.lock().unwrap();
+        match pm.check_circuit_breakers(prices) {
+            Ok(Some((event, closed_ids))) => {
+                tracing::warn!(
+                    "🛑 Circuit breaker {:?}: value ${:.2}, drawdown {:.1}%, daily P&L ${:.2}",
+                    event.trip,
+                    event.portfolio_value,
+                    event.drawdown_pct() * 100.0,
+                    event.daily_pnl
+                );
+                if let Some(until) = pm.paused_until() {
+                    tracing::warn!("  New entries paused until {}", until.format("%H:%M UTC"));
+                }
+                for id in &closed_ids {
+                    tracing::warn!("  Flattened position {}", id);
+                }
+
+                // Persist closed positions and any tightened stops
+                let changed = pm
+                    .all_positions()
+                    .iter()
+                    .filter(|p| p.status == PositionStatus::Open || closed_ids.contains(&p.id))
+                    .cloned()
+                    .collect();
+                (changed, Some(event))
+            }
+            Ok(None) => (Vec::new(), None),
+            Err(e) => {
+                tracing::error!("Failed to check circuit breakers: {}", e);
+                (Vec::new(), None)
+            }
+        }
+    }
+
+    /// Record a new trip in Postgres and keep the breaker state in Redis current
+    async fn save_breaker_state(&mut self, trip: Option<&CircuitBreakerEvent>) {
+        let state = self.position_manager.lock().unwrap().breaker_state();
+
+        if let (Some(event), Some(postgres)) = (trip, self.postgres.as_ref()) {
+            if let Err(e) = postgres
+                .save_circuit_breaker_event(event, state.paused_until)
+                .await
+            {
+                tracing::warn!("Failed to save circuit breaker event to Postgres: {}", e);
+            }
+        }
+
+        if state == self.saved_breaker_state {
+            return;
+        }
+        if let Some(redis) = self.redis.as_mut() {
+            match redis.save_breaker_state(&state).await {
+                Ok(()) => self.saved_breaker_state = state,
+                Err(e) => tracing::warn!("Failed to save breaker state to Redis: {}", e),
+            }
+        }
+    }
+
+    async fn save_positions(&mut self, positions: &[Position]) {
+        if let Some(postgres) = self.postgres.as_mut() {
+            for position in positions {
+                if let Err(e) = postgres.save_position(position).await {
+                    tracing::warn!("Failed to save position to Postgres: {}", e);
+                }
+            }
+        }
+    }
+
+    fn log_portfolio_summary(&self, prices: &HashMap<String, f64>) {
+        let pm = self.position_manager.lock().unwrap();
+        let initial_portfolio_value = pm.initial_portfolio_value();
+        let portfolio_value = pm
+            .portfolio_value(prices)
+            .unwrap_or(initial_portfolio_value);
+        let open_positions = pm.open_positions();
+
+        tracing::info!("\n📊 Portfolio Summary:");
+        tracing::info!("  Value: ${:.2}", portfolio_value);
+        tracing::info!(
+            "  P&L: ${:.2} ({:.2}%)",
+            portfolio_value - initial_portfolio_value,
+            ((portfolio_value - initial_portfolio_value) / initial_portfolio_value) * 100.0
+        );
+        tracing::info!("  Open Positions: {}", open_positions.len());
+
+        for position in open_positions {
+            if let Some(&current_price) = prices.get(&position.token) {
+                let unrealized_pnl = (current_price - position.entry_price) * position.quantity;
+                let unrealized_pnl_pct =
+                    ((current_price - position.entry_price) / position.entry_price) * 100.0;
+                tracing::info!(
+                    "    {} | Entry: ${:.4} | Current: ${:.4} | P&L: ${:.2} ({:.2}%)",
+                    position.token,
+                    position.entry_price,
+                    current_price,
+                    unrealized_pnl,
+                    unrealized_pnl_pct
+                );
+            }
+        }
+    }
+
+    /// Log one-candle historical VaR / expected shortfall of the open book
+    fn log_portfolio_risk(&self, market: &HashMap<String, Vec<Candle>>) {
+        let pm = self.position_manager.lock().unwrap();
+        let open_positions = pm.open_positions();
+        let book = book_positions(&open_positions, market);
+
+        let period = self
+            .correlation_limits
+            .as_ref()
+            .map_or(CorrelationLimits::default().period, |l| l.period);
+        if let Some(risk) = PortfolioRisk::historical(&book, period, 0.95) {
+            tracing::info!(
+                "  Risk (5m, {:.0}%): VaR ${:.2} | ES ${:.2} ({} samples)",
+                risk.confidence * 100.0,
+                risk.value_at_risk,
+                risk.expected_shortfall,
+                risk.samples
+            );
+        }
+    }
+}
+
+/// Latest close of every token with candles
+fn last_closes(candles: &HashMap<String, Vec<Candle>>) -> HashMap<String, f64> {
+    candles
+        .iter()
+        .filter_map(|(symbol, c)| c.last().map(|last| (symbol.clone(), last.close)))
+        .collect()
+}
+
+fn find_position(pm: &PositionManager, position_id: uuid::Uuid) -> Option<Position> {
+    pm.all_positions()
+        .iter()
+        .find(|p| p.id == position_id)
+        .cloned()
+}
+
+/// Open positions with their current value and candle history
+fn book_positions<'a>(
+    positions: &[&'a Position],
+    market: &'a HashMap<String, Vec<Candle>>,
+) -> Vec<BookPosition<'a>> {
+    positions
+        .iter()
+        .filter_map(|p| {
+            let candles = market.get(&p.token)?;
+            let price = candles.last()?.close;
+            Some(BookPosition {
+                token: &p.token,
+                value: p.quantity * price,
+                candles,
+            })
+        })
+        .collect()
+}
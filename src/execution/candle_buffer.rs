@@ -94,6 +94,7 @@ impl CandleBuffer {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::models::CandleProvenance;
     use chrono::Utc;
 
     fn create_test_candle(token: &str, price: f64) -> Candle {
@@ -105,6 +106,7 @@ mod tests {
             low: price,
             close: price,
             volume: 1000.0,
+            provenance: CandleProvenance::Live,
         }
     }
 
@@ -1,7 +1,8 @@
 use std::sync::{Arc, Mutex};
 
-use crate::execution::{ExitReason, PositionManager};
-use crate::models::Signal;
+use crate::execution::{ExitReason, PositionManager, STOP_LOSS_PCT};
+use crate::models::{Candle, Signal};
+use crate::risk::{HaltMode, PositionSizing, SizingContext, TradeStats};
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum ExecutionAction {
@@ -23,22 +24,100 @@ pub struct ExecutionDecision {
 
 pub struct Executor {
     position_manager: Arc<Mutex<PositionManager>>,
+    sizing: PositionSizing,
+    halt_mode: HaltMode,
 }
 
 impl Executor {
     pub fn new(position_manager: Arc<Mutex<PositionManager>>) -> Self {
-        Self { position_manager }
+        Self {
+            position_manager,
+            sizing: PositionSizing::default(),
+            halt_mode: HaltMode::Running,
+        }
+    }
+
+    /// Set the position sizing method (default: fixed 5% of portfolio)
+    pub fn with_sizing(mut self, sizing: PositionSizing) -> Self {
+        self.sizing = sizing;
+        self
+    }
+
+    /// Get the configured position sizing method
+    pub fn sizing(&self) -> &PositionSizing {
+        &self.sizing
+    }
+
+    /// Set the operator halt mode (refreshed by the trading loop every tick)
+    pub fn set_halt_mode(&mut self, mode: HaltMode) {
+        self.halt_mode = mode;
+    }
+
+    /// Get the current halt mode
+    pub fn halt_mode(&self) -> HaltMode {
+        self.halt_mode
     }
 
     /// Process a signal and decide what to do
+    ///
+    /// Without candle history, volatility-based sizing falls back to the max position size.
+    /// Prefer `process_signal_with_candles` when history is available.
     pub fn process_signal(
         &mut self,
         signal: &Signal,
         token: &str,
         current_price: f64,
+    ) -> anyhow::Result<ExecutionDecision> {
+        self.decide(signal, token, current_price, &[])
+    }
+
+    /// Process a signal using the candle history (latest candle's close is the current price)
+    pub fn process_signal_with_candles(
+        &mut self,
+        signal: &Signal,
+        token: &str,
+        candles: &[Candle],
+    ) -> anyhow::Result<ExecutionDecision> {
+        let current_price = candles
+            .last()
+            .map(|c| c.close)
+            .ok_or_else(|| anyhow::anyhow!("No candles provided for {}", token))?;
+
+        self.decide(signal, token, current_price, candles)
+    }
+
+    fn decide(
+        &mut self,
+        signal: &Signal,
+        token: &str,
+        current_price: f64,
+        candles: &[Candle],
     ) -> anyhow::Result<ExecutionDecision> {
         let pm = self.position_manager.lock().unwrap();
 
+        // Operator halt overrides the signal
+        if self.halt_mode == HaltMode::FlattenAll {
+            return Ok(match pm.get_open_position(token) {
+                Some(position) => ExecutionDecision {
+                    action: ExecutionAction::Close {
+                        position_id: position.id,
+                        exit_reason: ExitReason::Manual,
+                    },
+                    reason: "Trading halted (flatten-all)".to_string(),
+                },
+                None => ExecutionDecision {
+                    action: ExecutionAction::Skip,
+                    reason: "Trading halted (flatten-all)".to_string(),
+                },
+            });
+        }
+        if *signal == Signal::Buy && !self.halt_mode.allows_entries() {
+            return Ok(ExecutionDecision {
+                action: ExecutionAction::Skip,
+                reason: format!("Trading halted ({})", self.halt_mode),
+            });
+        }
+
         match signal {
             Signal::Buy => {
                 // Check 1: Do we already have this token?
@@ -49,23 +128,42 @@ impl Executor {
                     });
                 }
 
-                // Check 2: Circuit breakers
+                // Check 2: Circuit breakers (and any pause after a trip)
                 if let Err(trip) = pm.circuit_breakers().check(pm.trading_state()) {
                     return Ok(ExecutionDecision {
                         action: ExecutionAction::Skip,
                         reason: format!("Circuit breaker: {:?}", trip),
                     });
                 }
+                if let Some(until) = pm.paused_until() {
+                    return Ok(ExecutionDecision {
+                        action: ExecutionAction::Skip,
+                        reason: format!(
+                            "Circuit breaker pause until {}",
+                            until.format("%Y-%m-%d %H:%M UTC")
+                        ),
+                    });
+                }
 
-                // Check 3: Calculate size (5% of portfolio)
+                // Check 3: Calculate size (capped at max position size)
                 let max_position_pct = pm.circuit_breakers().max_position_size_pct;
                 let quantity =
-                    self.calculate_position_size(&pm, current_price, max_position_pct)?;
+                    self.calculate_position_size(&pm, current_price, candles, max_position_pct)?;
+
+                if quantity <= 0.0 {
+                    return Ok(ExecutionDecision {
+                        action: ExecutionAction::Skip,
+                        reason: format!("Position size is zero ({} sizing)", self.sizing.name()),
+                    });
+                }
 
                 // Execute
                 Ok(ExecutionDecision {
                     action: ExecutionAction::Execute { quantity },
-                    reason: "Buy signal with available capital".to_string(),
+                    reason: format!(
+                        "Buy signal with available capital ({} sizing)",
+                        self.sizing.name()
+                    ),
                 })
             }
 
@@ -116,23 +214,28 @@ impl Executor {
         }
     }
 
-    /// Calculate position size based on portfolio value and risk limits
+    /// Calculate position size based on portfolio value, sizing method and risk limits
     fn calculate_position_size(
         &self,
         pm: &PositionManager,
         current_price: f64,
+        candles: &[Candle],
         max_position_pct: f64,
     ) -> anyhow::Result<f64> {
-        // Get current portfolio value
-        let portfolio_value = pm.trading_state().portfolio_value;
-
-        // Calculate max position value (e.g., 5% of portfolio)
-        let max_position_value = portfolio_value * max_position_pct;
+        if current_price <= 0.0 {
+            anyhow::bail!("Invalid price for sizing: {}", current_price);
+        }
 
-        // Calculate quantity based on current price
-        let quantity = max_position_value / current_price;
+        let ctx = SizingContext {
+            portfolio_value: pm.trading_state().portfolio_value,
+            price: current_price,
+            candles,
+            max_position_pct,
+            stop_loss_pct: STOP_LOSS_PCT,
+            trade_stats: TradeStats::from_positions(pm.all_positions()),
+        };
 
-        Ok(quantity)
+        Ok(self.sizing.quantity(&ctx))
     }
 
     /// Get reference to position manager (for testing)
@@ -146,8 +249,9 @@ impl Executor {
 mod tests {
     use super::*;
     use crate::execution::PositionManager;
-    use crate::models::Signal;
-    use crate::risk::CircuitBreakers;
+    use crate::models::{CandleProvenance, Signal};
+    use crate::risk::{BreakerActions, CircuitBreakers};
+    use std::collections::HashMap;
 
     #[test]
     fn test_skip_buy_when_already_positioned() {
@@ -214,7 +318,7 @@ mod tests {
         let quantity = {
             let pm_lock = pm.lock().unwrap();
             executor
-                .calculate_position_size(&pm_lock, 100.0, 0.05)
+                .calculate_position_size(&pm_lock, 100.0, &[], 0.05)
                 .unwrap()
         };
         assert_eq!(quantity, 5.0);
@@ -223,7 +327,7 @@ mod tests {
         let quantity = {
             let pm_lock = pm.lock().unwrap();
             executor
-                .calculate_position_size(&pm_lock, 50.0, 0.05)
+                .calculate_position_size(&pm_lock, 50.0, &[], 0.05)
                 .unwrap()
         };
         assert_eq!(quantity, 10.0);
@@ -391,4 +495,141 @@ mod tests {
         let decision = executor.process_signal(&Signal::Buy, "SOL", 110.0).unwrap();
         assert!(matches!(decision.action, ExecutionAction::Execute { .. }));
     }
+
+    #[test]
+    fn test_volatility_sizing_uses_candle_history() {
+        use crate::risk::VolatilityMeasure;
+        use chrono::{Duration, Utc};
+
+        let pm = Arc::new(Mutex::new(PositionManager::new(
+            10000.0,
+            CircuitBreakers::default(),
+        )));
+        let mut executor = Executor::new(pm).with_sizing(PositionSizing::VolatilityTarget {
+            target_volatility_pct: 0.001,
+            period: 14,
+            measure: VolatilityMeasure::Atr,
+        });
+
+        // 10% candle ranges -> ATR/price = 0.10 -> 1% position = $100 = 1 token
+        let start = Utc::now() - Duration::minutes(150);
+        let candles: Vec<Candle> = (0..30)
+            .map(|i| Candle {
+                token: "MEME".to_string(),
+                timestamp: start + Duration::minutes(5 * i),
+                open: 100.0,
+                high: 105.0,
+                low: 95.0,
+                close: 100.0,
+                volume: 1000.0,
+                provenance: CandleProvenance::Live,
+            })
+            .collect();
+
+        let decision = executor
+            .process_signal_with_candles(&Signal::Buy, "MEME", &candles)
+            .unwrap();
+
+        if let ExecutionAction::Execute { quantity } = decision.action {
+            assert!((quantity - 1.0).abs() < 1e-9, "got {}", quantity);
+        } else {
+            panic!("Expected Execute, got {:?}", decision.action);
+        }
+    }
+
+    #[test]
+    fn test_zero_size_skips_buy() {
+        let pm = Arc::new(Mutex::new(PositionManager::new(
+            10000.0,
+            CircuitBreakers::default(),
+        )));
+        let mut executor =
+            Executor::new(pm).with_sizing(PositionSizing::FixedFractional { fraction: 0.0 });
+
+        let decision = executor.process_signal(&Signal::Buy, "SOL", 100.0).unwrap();
+
+        assert!(matches!(decision.action, ExecutionAction::Skip));
+        assert!(decision.reason.contains("Position size is zero"));
+    }
+
+    #[test]
+    fn test_no_new_entries_blocks_buys_but_allows_sells() {
+        let pm = Arc::new(Mutex::new(PositionManager::new(
+            10000.0,
+            CircuitBreakers::default(),
+        )));
+        pm.lock()
+            .unwrap()
+            .open_position("SOL".to_string(), 100.0, 5.0)
+            .unwrap();
+
+        let mut executor = Executor::new(pm);
+        executor.set_halt_mode(HaltMode::NoNewEntries);
+
+        let decision = executor.process_signal(&Signal::Buy, "JUP", 1.0).unwrap();
+        assert!(matches!(decision.action, ExecutionAction::Skip));
+        assert!(decision.reason.contains("no-new-entries"));
+
+        let decision = executor
+            .process_signal(&Signal::Sell, "SOL", 110.0)
+            .unwrap();
+        assert!(matches!(decision.action, ExecutionAction::Close { .. }));
+    }
+
+    #[test]
+    fn test_flatten_all_closes_on_any_signal() {
+        let pm = Arc::new(Mutex::new(PositionManager::new(
+            10000.0,
+            CircuitBreakers::default(),
+        )));
+        pm.lock()
+            .unwrap()
+            .open_position("SOL".to_string(), 100.0, 5.0)
+            .unwrap();
+
+        let mut executor = Executor::new(pm);
+        executor.set_halt_mode(HaltMode::FlattenAll);
+
+        let decision = executor.process_signal(&Signal::Hold, "SOL", 95.0).unwrap();
+        assert!(matches!(
+            decision.action,
+            ExecutionAction::Close {
+                exit_reason: ExitReason::Manual,
+                ..
+            }
+        ));
+
+        let decision = executor.process_signal(&Signal::Buy, "JUP", 1.0).unwrap();
+        assert!(matches!(decision.action, ExecutionAction::Skip));
+
+        // Resume restores normal behavior
+        executor.set_halt_mode(HaltMode::Running);
+        let decision = executor.process_signal(&Signal::Buy, "JUP", 1.0).unwrap();
+        assert!(matches!(decision.action, ExecutionAction::Execute { .. }));
+    }
+
+    #[test]
+    fn test_breaker_pause_blocks_buys_after_trip_clears() {
+        let breakers = CircuitBreakers {
+            max_consecutive_losses: 1,
+            actions: BreakerActions::recommended(),
+            ..Default::default()
+        };
+        let pm = Arc::new(Mutex::new(PositionManager::new(10000.0, breakers)));
+        {
+            let mut pm = pm.lock().unwrap();
+            let id = pm.open_position("JUP".to_string(), 1.0, 10.0).unwrap();
+            pm.close_position(id, 0.9, ExitReason::StopLoss).unwrap();
+            pm.check_circuit_breakers(&HashMap::new()).unwrap();
+            // A winning trade clears the breaker, but the pause remains
+            let id = pm.open_position("JUP".to_string(), 1.0, 10.0).unwrap();
+            pm.close_position(id, 1.1, ExitReason::TakeProfit).unwrap();
+        }
+
+        let mut executor = Executor::new(pm);
+        let decision = executor.process_signal(&Signal::Buy, "SOL", 100.0).unwrap();
+
+        assert!(matches!(decision.action, ExecutionAction::Skip));
+        assert!(decision.reason.contains("pause"));
+    }
 }
@@ -0,0 +1,221 @@
+use std::future::Future;
+
+use crate::api::JupiterClient;
+use crate::Result;
+
+/// USDC mint on Solana (quote currency for sizing checks)
+pub const USDC_MINT: &str = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+const USDC_DECIMALS: u32 = 6;
+
+/// Limits used to shrink orders in thin markets
+#[derive(Debug, Clone, PartialEq)]
+pub struct LiquidityLimits {
+    /// Max acceptable price impact, in the same units as Jupiter's `priceImpactPct`
+    /// (0.01 = 1%)
+    pub max_price_impact_pct: f64,
+    /// Max order size as a fraction of the pool liquidity seen at discovery (0.02 = 2%)
+    pub max_liquidity_fraction: f64,
+    /// Give up once the order would be smaller than this (USD)
+    pub min_order_usd: f64,
+    /// Max number of quotes per order
+    pub max_quotes: usize,
+}
+
+impl Default for LiquidityLimits {
+    fn default() -> Self {
+        Self {
+            max_price_impact_pct: 0.01,   // 1% price impact
+            max_liquidity_fraction: 0.02, // 2% of pool liquidity
+            min_order_usd: 10.0,
+            max_quotes: 5,
+        }
+    }
+}
+
+/// Source of swap quotes (Jupiter in production, mocked in tests)
+pub trait QuoteSource {
+    /// Price impact for swapping `amount` raw units of `input_mint` into `output_mint`
+    fn price_impact(
+        &self,
+        input_mint: &str,
+        output_mint: &str,
+        amount: u64,
+    ) -> impl Future<Output = Result<f64>> + Send;
+}
+
+impl QuoteSource for JupiterClient {
+    async fn price_impact(&self, input_mint: &str, output_mint: &str, amount: u64) -> Result<f64> {
+        let quote = self.get_quote(input_mint, output_mint, amount, 50).await?;
+        Ok(quote.price_impact_pct.abs())
+    }
+}
+
+/// Result of a liquidity check
+#[derive(Debug, Clone, PartialEq)]
+pub struct LiquidityAdjustment {
+    pub quantity: f64,
+    pub price_impact_pct: Option<f64>,
+    pub reason: String,
+}
+
+impl LiquidityLimits {
+    /// Shrink a buy order until its quoted price impact is under the cap
+    ///
+    /// The order is first capped at `max_liquidity_fraction` of `liquidity_usd`
+    /// (when known), then quoted as a USDC -> token swap. While the quoted impact
+    /// exceeds the cap, the order is scaled down roughly in proportion to the excess.
+    /// Returns quantity 0 when no acceptable size above `min_order_usd` exists.
+    pub async fn adjust_buy<Q: QuoteSource>(
+        &self,
+        quotes: &Q,
+        token_mint: &str,
+        quantity: f64,
+        price: f64,
+        liquidity_usd: Option<f64>,
+    ) -> Result<LiquidityAdjustment> {
+        if quantity <= 0.0 || price <= 0.0 {
+            return Ok(LiquidityAdjustment {
+                quantity: 0.0,
+                price_impact_pct: None,
+                reason: "Nothing to size".to_string(),
+            });
+        }
+
+        let mut order_usd = quantity * price;
+        let mut reasons = Vec::new();
+
+        // Upper bound from liquidity recorded at discovery time
+        if let Some(liquidity) = liquidity_usd.filter(|l| *l > 0.0) {
+            let cap = liquidity * self.max_liquidity_fraction;
+            if order_usd > cap {
+                reasons.push(format!(
+                    "capped at {:.1}% of ${:.0} liquidity",
+                    self.max_liquidity_fraction * 100.0,
+                    liquidity
+                ));
+                order_usd = cap;
+            }
+        }
+
+        let mut last_impact = None;
+
+        for _ in 0..self.max_quotes {
+            if order_usd < self.min_order_usd {
+                break;
+            }
+
+            let amount = (order_usd * 10f64.powi(USDC_DECIMALS as i32)) as u64;
+            let impact = quotes.price_impact(USDC_MINT, token_mint, amount).await?;
+            last_impact = Some(impact);
+
+            if impact <= self.max_price_impact_pct {
+                if reasons.is_empty() {
+                    reasons.push("within limits".to_string());
+                }
+                return Ok(LiquidityAdjustment {
+                    quantity: order_usd / price,
+                    price_impact_pct: Some(impact),
+                    reason: format!("Liquidity check: {}", reasons.join(", ")),
+                });
+            }
+
+            // Impact grows roughly linearly with size for small orders
+            let scale = (self.max_price_impact_pct / impact * 0.9).clamp(0.1, 0.9);
+            reasons.push(format!(
+                "impact {:.3} > {:.3}, shrinking",
+                impact, self.max_price_impact_pct
+            ));
+            order_usd *= scale;
+        }
+
+        Ok(LiquidityAdjustment {
+            quantity: 0.0,
+            price_impact_pct: last_impact,
+            reason: format!(
+                "Liquidity check: no order above ${:.0} within impact cap ({})",
+                self.min_order_usd,
+                reasons.join(", ")
+            ),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Impact is linear in order size: `impact_per_usd * order_usd`
+    struct LinearImpact {
+        impact_per_usd: f64,
+        calls: AtomicUsize,
+    }
+
+    impl LinearImpact {
+        fn new(impact_per_usd: f64) -> Self {
+            Self {
+                impact_per_usd,
+                calls: AtomicUsize::new(0),
+            }
+        }
+    }
+
+    impl QuoteSource for LinearImpact {
+        async fn price_impact(&self, input_mint: &str, _output: &str, amount: u64) -> Result<f64> {
+            assert_eq!(input_mint, USDC_MINT);
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            let order_usd = amount as f64 / 1_000_000.0;
+            Ok(order_usd * self.impact_per_usd)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_deep_liquidity_keeps_size() {
+        let quotes = LinearImpact::new(0.000001); // $500 -> 0.0005
+        let adjustment = LiquidityLimits::default()
+            .adjust_buy(&quotes, "MINT", 5.0, 100.0, None)
+            .await
+            .unwrap();
+
+        assert_eq!(adjustment.quantity, 5.0);
+        assert_eq!(quotes.calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_thin_liquidity_shrinks_until_under_cap() {
+        let quotes = LinearImpact::new(0.0001); // $500 -> 0.05 (5x the cap)
+        let adjustment = LiquidityLimits::default()
+            .adjust_buy(&quotes, "MINT", 5.0, 100.0, None)
+            .await
+            .unwrap();
+
+        assert!(adjustment.quantity > 0.0);
+        assert!(adjustment.quantity < 5.0);
+        assert!(adjustment.price_impact_pct.unwrap() <= 0.01);
+    }
+
+    #[tokio::test]
+    async fn test_discovery_liquidity_is_upper_bound() {
+        let quotes = LinearImpact::new(0.0);
+        // $10k liquidity * 2% = $200 max order
+        let adjustment = LiquidityLimits::default()
+            .adjust_buy(&quotes, "MINT", 5.0, 100.0, Some(10_000.0))
+            .await
+            .unwrap();
+
+        assert!((adjustment.quantity - 2.0).abs() < 1e-9);
+        assert!(adjustment.reason.contains("liquidity"));
+    }
+
+    #[tokio::test]
+    async fn test_gives_up_when_no_size_fits() {
+        let quotes = LinearImpact::new(1.0); // Any order has huge impact
+        let adjustment = LiquidityLimits::default()
+            .adjust_buy(&quotes, "MINT", 5.0, 100.0, None)
+            .await
+            .unwrap();
+
+        assert_eq!(adjustment.quantity, 0.0);
+        assert!(adjustment.reason.contains("no order"));
+    }
+}
@@ -1,10 +1,12 @@
 // Order execution and data collection module
 pub mod candle_buffer;
 pub mod executor;
+pub mod liquidity;
 pub mod position_manager;
 pub mod price_feed;
 
 pub use candle_buffer::CandleBuffer;
 pub use executor::{ExecutionAction, ExecutionDecision, Executor};
-pub use position_manager::{ExitReason, Position, PositionManager, PositionStatus};
+pub use liquidity::{LiquidityAdjustment, LiquidityLimits, QuoteSource};
+pub use position_manager::{ExitReason, Position, PositionManager, PositionStatus, STOP_LOSS_PCT};
 pub use price_feed::PriceFeedManager;
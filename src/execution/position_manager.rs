@@ -2,7 +2,13 @@ use chrono::{DateTime, Utc};
 use std::collections::HashMap;
 use uuid::Uuid;
 
-use crate::risk::{CircuitBreakers, TradingState};
+use crate::models::Candle;
+use crate::risk::{
+    BreakerState, CircuitBreakerEvent, CircuitBreakerTrip, CircuitBreakers, TradingState,
+};
+
+/// Initial stop loss distance below entry (-8%)
+pub const STOP_LOSS_PCT: f64 = 0.08;
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum PositionStatus {
@@ -16,7 +22,8 @@ pub enum ExitReason {
     TakeProfit,
     TimeStop,
     Manual,
-    StrategySell, // Strategy-driven sell signal (e.g., overbought conditions)
+    StrategySell,   // Strategy-driven sell signal (e.g., overbought conditions)
+    CircuitBreaker, // Flattened by a circuit breaker action
 }
 
 #[derive(Debug, Clone)]
@@ -41,7 +48,11 @@ pub struct PositionManager {
     circuit_breakers: CircuitBreakers,
     trading_state: TradingState,
     initial_portfolio_value: f64,
-    total_pnl: f64, // Track total P&L across all trades
+    total_pnl: f64,               // Track total P&L across all trades
+    clock: Option<DateTime<Utc>>, // Simulated time (backtests); None = wall clock
+    breaker_events: Vec<CircuitBreakerEvent>,
+    active_trip: Option<CircuitBreakerTrip>,
+    paused_until: Option<DateTime<Utc>>,
 }
 
 impl PositionManager {
@@ -52,6 +63,10 @@ impl PositionManager {
             trading_state: TradingState::new(initial_portfolio_value),
             initial_portfolio_value,
             total_pnl: 0.0,
+            clock: None,
+            breaker_events: Vec::new(),
+            active_trip: None,
+            paused_until: None,
         }
     }
 
@@ -82,9 +97,30 @@ impl PositionManager {
             trading_state: TradingState::new(initial_portfolio_value),
             initial_portfolio_value,
             total_pnl,
+            clock: None,
+            breaker_events: Vec::new(),
+            active_trip: None,
+            paused_until: None,
         }
     }
 
+    /// Use simulated time instead of the wall clock (for backtests)
+    ///
+    /// Entry/exit times, the time stop, daily resets and breaker pauses all use it.
+    pub fn set_time(&mut self, now: DateTime<Utc>) {
+        self.clock = Some(now);
+    }
+
+    /// Current time (simulated if set, otherwise wall clock)
+    pub fn now(&self) -> DateTime<Utc> {
+        self.clock.unwrap_or_else(Utc::now)
+    }
+
+    /// Starting capital the portfolio value is measured against
+    pub fn initial_portfolio_value(&self) -> f64 {
+        self.initial_portfolio_value
+    }
+
     /// Get all positions (both open and closed)
     pub fn all_positions(&self) -> &[Position] {
         &self.positions
@@ -108,14 +144,14 @@ impl PositionManager {
         }
 
         let id = Uuid::new_v4();
-        let stop_loss = entry_price * 0.92; // -8% from entry
+        let stop_loss = entry_price * (1.0 - STOP_LOSS_PCT); // -8% from entry
 
         let position = Position {
             id,
             token,
             entry_price,
             quantity,
-            entry_time: Utc::now(),
+            entry_time: self.now(),
             stop_loss,
             take_profit: None,
             trailing_high: entry_price,
@@ -215,7 +251,7 @@ impl PositionManager {
         }
 
         // Check time stop (14 days)
-        let days_open = (Utc::now() - position.entry_time).num_days();
+        let days_open = (self.now() - position.entry_time).num_days();
         if days_open >= 14 {
             return Ok(Some(ExitReason::TimeStop));
         }
@@ -223,6 +259,51 @@ impl PositionManager {
         Ok(None)
     }
 
+    /// Check if position should exit during a candle (returns exit reason and fill price)
+    ///
+    /// Pessimistic ordering: the low is assumed to come before the high, so stops
+    /// in force at the open are tested against the low before this candle's high
+    /// can ratchet the trailing stop up. Stops fill at their level, or at the open
+    /// when the candle gaps through them.
+    pub fn should_exit_intrabar(
+        &mut self,
+        position_id: Uuid,
+        candle: &Candle,
+    ) -> anyhow::Result<Option<(ExitReason, f64)>> {
+        let position = self.get_position(position_id)?;
+
+        // The tighter of the hard stop and the trailing stop triggers first
+        let (level, reason) = match position.take_profit {
+            Some(tp) if tp > position.stop_loss => (tp, ExitReason::TakeProfit),
+            _ => (position.stop_loss, ExitReason::StopLoss),
+        };
+
+        if candle.open <= level {
+            return Ok(Some((reason, candle.open))); // Gapped through the stop
+        }
+        if candle.low <= level {
+            return Ok(Some((reason, level)));
+        }
+
+        // Survived the low: the high can now raise the trailing stop, which the
+        // move back down to the close may still hit
+        self.update_trailing_stop(position_id, candle.high)?;
+        let position = self.get_position(position_id)?;
+
+        if let Some(tp) = position.take_profit {
+            if candle.close <= tp {
+                return Ok(Some((ExitReason::TakeProfit, tp)));
+            }
+        }
+
+        let days_open = (self.now() - position.entry_time).num_days();
+        if days_open >= 14 {
+            return Ok(Some((ExitReason::TimeStop, candle.close)));
+        }
+
+        Ok(None)
+    }
+
     /// Close position
     pub fn close_position(
         &mut self,
@@ -230,6 +311,7 @@ impl PositionManager {
         exit_price: f64,
         reason: ExitReason,
     ) -> anyhow::Result<()> {
+        let now = self.now();
         let position = self.get_position_mut(position_id)?;
 
         if position.status == PositionStatus::Closed {
@@ -241,7 +323,7 @@ impl PositionManager {
         position.status = PositionStatus::Closed;
         position.realized_pnl = Some(pnl);
         position.exit_price = Some(exit_price);
-        position.exit_time = Some(Utc::now());
+        position.exit_time = Some(now);
         position.exit_reason = Some(reason);
 
         // Update trading state
@@ -290,6 +372,62 @@ impl PositionManager {
         Ok(closed_ids)
     }
 
+    /// Check all open positions for exits against each token's latest candle
+    pub fn check_exits_ohlc(
+        &mut self,
+        candles: &HashMap<String, Candle>,
+    ) -> anyhow::Result<Vec<Uuid>> {
+        let positions_to_check: Vec<(Uuid, &Candle)> = self
+            .positions
+            .iter()
+            .filter(|p| p.status == PositionStatus::Open)
+            .filter_map(|p| candles.get(&p.token).map(|candle| (p.id, candle)))
+            .collect();
+
+        let mut to_close = Vec::new();
+        for (position_id, candle) in positions_to_check {
+            if let Some((reason, exit_price)) = self.should_exit_intrabar(position_id, candle)? {
+                to_close.push((position_id, exit_price, reason));
+            }
+        }
+
+        let mut closed_ids = Vec::new();
+        for (position_id, exit_price, reason) in to_close {
+            self.close_position(position_id, exit_price, reason)?;
+            closed_ids.push(position_id);
+        }
+
+        Ok(closed_ids)
+    }
+
+    /// Close every open position that has a price (e.g., for a manual flatten)
+    pub fn close_all(
+        &mut self,
+        prices: &HashMap<String, f64>,
+        reason: ExitReason,
+    ) -> anyhow::Result<Vec<Uuid>> {
+        let to_close: Vec<(Uuid, f64)> = self
+            .positions
+            .iter()
+            .filter(|p| p.status == PositionStatus::Open)
+            .filter_map(|p| prices.get(&p.token).map(|&price| (p.id, price)))
+            .collect();
+
+        let mut closed_ids = Vec::new();
+        for (position_id, exit_price) in to_close {
+            self.close_position(position_id, exit_price, reason.clone())?;
+            closed_ids.push(position_id);
+        }
+
+        Ok(closed_ids)
+    }
+
+    /// Deduct a trading fee (swap or network fee) from realized P&L
+    pub fn record_fee(&mut self, fee: f64) {
+        self.total_pnl -= fee;
+        self.trading_state.daily_pnl -= fee;
+    }
+
     /// Get portfolio value (cash + position values)
     pub fn portfolio_value(&self, prices: &HashMap<String, f64>) -> anyhow::Result<f64> {
         let mut total_value = self.initial_portfolio_value;
@@ -312,6 +450,119 @@ impl PositionManager {
         Ok(total_value)
     }
 
+    /// Mark open positions to market and refresh portfolio value / peak
+    pub fn update_portfolio_value(&mut self, prices: &HashMap<String, f64>) -> anyhow::Result<f64> {
+        let value = self.portfolio_value(prices)?;
+        self.trading_state.update_portfolio_value(value);
+        Ok(value)
+    }
+
+    /// Check circuit breakers and apply de-risking actions on a new trip
+    ///
+    /// Marks the book to market first. A trip is recorded once when it starts;
+    /// it can fire again after the breaker has cleared. Returns the new event and
+    /// the IDs of positions closed by the flatten action.
+    pub fn check_circuit_breakers(
+        &mut self,
+        prices: &HashMap<String, f64>,
+    ) -> anyhow::Result<Option<(CircuitBreakerEvent, Vec<Uuid>)>> {
+        self.check_circuit_breakers_with_exits(prices, prices)
+    }
+
+    /// Like `check_circuit_breakers`, but a flatten sells at `exit_prices`
+    ///
+    /// Tokens missing from `exit_prices` can't be sold (e.g., no liquidity) and
+    /// stay open.
+    pub fn check_circuit_breakers_with_exits(
+        &mut self,
+        prices: &HashMap<String, f64>,
+        exit_prices: &HashMap<String, f64>,
+    ) -> anyhow::Result<Option<(CircuitBreakerEvent, Vec<Uuid>)>> {
+        let now = self.now();
+        self.trading_state.reset_daily_if_needed(now);
+        self.update_portfolio_value(prices)?;
+
+        let trip = match self.circuit_breakers.check(&self.trading_state) {
+            Ok(()) => {
+                self.active_trip = None;
+                return Ok(None);
+            }
+            Err(trip) => trip,
+        };
+
+        if self.active_trip == Some(trip) {
+            return Ok(None);
+        }
+        self.active_trip = Some(trip);
+
+        let event = CircuitBreakerEvent::new(trip, &self.trading_state, now);
+        tracing::warn!("Circuit breaker tripped: {:?} at {}", trip, now);
+        self.breaker_events.push(event.clone());
+
+        // The trade limit clears itself at midnight; only losses warrant de-risking
+        if !trip.is_loss() {
+            return Ok(Some((event, Vec::new())));
+        }
+        let actions = self.circuit_breakers.actions.clone();
+
+        if let Some(hours) = actions.pause_hours {
+            // A pause too long to represent blocks entries indefinitely
+            let until = chrono::Duration::try_hours(hours)
+                .and_then(|pause| now.checked_add_signed(pause))
+                .unwrap_or(DateTime::<Utc>::MAX_UTC);
+            self.paused_until = Some(until);
+        }
+
+        let mut closed_ids = Vec::new();
+        if trip == CircuitBreakerTrip::MaxDrawdown && actions.flatten_on_max_drawdown {
+            closed_ids = self.close_all(exit_prices, ExitReason::CircuitBreaker)?;
+        } else if let Some(tighten_pct) = actions.tighten_stop_pct {
+            self.tighten_stops(prices, tighten_pct);
+        }
+
+        Ok(Some((event, closed_ids)))
+    }
+
+    /// Raise stop losses on open positions to `distance_pct` below the current price
+    fn tighten_stops(&mut self, prices: &HashMap<String, f64>, distance_pct: f64) {
+        for position in self
+            .positions
+            .iter_mut()
+            .filter(|p| p.status == PositionStatus::Open)
+        {
+            if let Some(&price) = prices.get(&position.token) {
+                let tightened = price * (1.0 - distance_pct);
+                if tightened > position.stop_loss {
+                    position.stop_loss = tightened;
+                }
+            }
+        }
+    }
+
+    /// Whether new entries are blocked by a breaker pause
+    pub fn paused_until(&self) -> Option<DateTime<Utc>> {
+        self.paused_until.filter(|until| *until > self.now())
+    }
+
+    /// All circuit breaker trips recorded so far
+    pub fn breaker_events(&self) -> &[CircuitBreakerEvent] {
+        &self.breaker_events
+    }
+
+    /// Active trip and entry pause, for persisting across restarts
+    pub fn breaker_state(&self) -> BreakerState {
+        BreakerState {
+            active_trip: self.active_trip,
+            paused_until: self.paused_until(),
+        }
+    }
+
+    /// Restore a persisted breaker state (at startup)
+    pub fn restore_breaker_state(&mut self, state: BreakerState) {
+        self.active_trip = state.active_trip;
+        self.paused_until = state.paused_until;
+    }
+
     /// Get current trading state
     pub fn trading_state(&self) -> &TradingState {
         &self.trading_state
@@ -340,6 +591,8 @@ impl PositionManager {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::models::CandleProvenance;
+    use crate::risk::BreakerActions;
 
     #[test]
     fn test_open_position() {
@@ -538,6 +791,283 @@ mod tests {
         assert_eq!(jup_position.status, PositionStatus::Open);
     }
 
+    fn bar(open: f64, high: f64, low: f64, close: f64) -> Candle {
+        Candle {
+            token: "SOL".to_string(),
+            timestamp: Utc::now(),
+            open,
+            high,
+            low,
+            close,
+            volume: 0.0,
+            provenance: CandleProvenance::Live,
+        }
+    }
+
+    #[test]
+    fn test_intrabar_stop_hit_by_low() {
+        let mut pm = PositionManager::new(10000.0, CircuitBreakers::default());
+        let id = pm.open_position("SOL".to_string(), 100.0, 1.0).unwrap();
+
+        // Closes at 97 (a close-only check holds) but the low pierced the 92 stop
+        let exit = pm
+            .should_exit_intrabar(id, &bar(99.0, 100.0, 90.0, 97.0))
+            .unwrap();
+        assert_eq!(exit, Some((ExitReason::StopLoss, 92.0)));
+        assert_eq!(pm.should_exit(id, 97.0).unwrap(), None);
+    }
+
+    #[test]
+    fn test_intrabar_gap_fills_at_open() {
+        let mut pm = PositionManager::new(10000.0, CircuitBreakers::default());
+        let id = pm.open_position("SOL".to_string(), 100.0, 1.0).unwrap();
+
+        let exit = pm
+            .should_exit_intrabar(id, &bar(85.0, 88.0, 84.0, 86.0))
+            .unwrap();
+        assert_eq!(exit, Some((ExitReason::StopLoss, 85.0)));
+    }
+
+    #[test]
+    fn test_intrabar_pessimistic_ordering() {
+        let mut pm = PositionManager::new(10000.0, CircuitBreakers::default());
+        let id = pm.open_position("SOL".to_string(), 100.0, 1.0).unwrap();
+
+        // Activate trailing at 120 -> trail at 114
+        assert_eq!(pm.should_exit(id, 120.0).unwrap(), None);
+
+        // The high of 130 would lift the trail to 123.5, but the low of 115 is
+        // assumed first: the old trail survives and the high ratchets it up
+        let exit = pm
+            .should_exit_intrabar(id, &bar(120.0, 130.0, 115.0, 128.0))
+            .unwrap();
+        assert_eq!(exit, None);
+        assert!((pm.get_position(id).unwrap().take_profit.unwrap() - 123.5).abs() < 1e-9);
+
+        // Low below the trail: exit at the trail level, not the close
+        let exit = pm
+            .should_exit_intrabar(id, &bar(126.0, 127.0, 119.0, 125.0))
+            .unwrap();
+        assert_eq!(exit, Some((ExitReason::TakeProfit, 123.5)));
+    }
+
+    #[test]
+    fn test_check_exits_ohlc_fills_at_stop() {
+        let mut pm = PositionManager::new(10000.0, CircuitBreakers::default());
+        let id = pm.open_position("SOL".to_string(), 100.0, 1.0).unwrap();
+        pm.open_position("JUP".to_string(), 1.0, 100.0).unwrap();
+
+        let mut candles = HashMap::new();
+        candles.insert("SOL".to_string(), bar(99.0, 100.0, 90.0, 97.0));
+        let closed = pm.check_exits_ohlc(&candles).unwrap();
+
+        assert_eq!(closed, vec![id]);
+        let position = pm.get_position(id).unwrap();
+        assert_eq!(position.exit_price, Some(92.0));
+        assert_eq!(position.realized_pnl, Some(-8.0));
+        assert!(pm.has_open_position("JUP"));
+    }
+
+    #[test]
+    fn test_close_all() {
+        let mut pm = PositionManager::new(10000.0, CircuitBreakers::default());
+        pm.open_position("SOL".to_string(), 100.0, 1.0).unwrap();
+        pm.open_position("JUP".to_string(), 1.0, 100.0).unwrap();
+        pm.open_position("BONK".to_string(), 0.01, 1000.0).unwrap();
+
+        // BONK has no price, so it stays open
+        let mut prices = HashMap::new();
+        prices.insert("SOL".to_string(), 110.0);
+        prices.insert("JUP".to_string(), 0.9);
+
+        let closed = pm.close_all(&prices, ExitReason::Manual).unwrap();
+        assert_eq!(closed.len(), 2);
+        assert_eq!(pm.open_positions().len(), 1);
+        assert!(pm.has_open_position("BONK"));
+    }
+
+    #[test]
+    fn test_simulated_clock_drives_times() {
+        let mut pm = PositionManager::new(10000.0, CircuitBreakers::default());
+        let start = Utc::now() - chrono::Duration::days(30);
+
+        pm.set_time(start);
+        let id = pm.open_position("SOL".to_string(), 100.0, 1.0).unwrap();
+        assert_eq!(pm.all_positions()[0].entry_time, start);
+
+        // 14 simulated days later the time stop fires
+        pm.set_time(start + chrono::Duration::days(14));
+        assert_eq!(
+            pm.should_exit(id, 101.0).unwrap(),
+            Some(ExitReason::TimeStop)
+        );
+    }
+
+    #[test]
+    fn test_max_drawdown_trip_flattens_and_pauses() {
+        let breakers = CircuitBreakers {
+            max_drawdown_pct: 0.01,
+            actions: BreakerActions::recommended(),
+            ..Default::default()
+        };
+        let mut pm = PositionManager::new(10000.0, breakers);
+        pm.open_position("SOL".to_string(), 100.0, 5.0).unwrap();
+
+        let mut prices = HashMap::new();
+        prices.insert("SOL".to_string(), 95.0); // -$25 = 0.25% drawdown
+        assert!(pm.check_circuit_breakers(&prices).unwrap().is_none());
+
+        prices.insert("SOL".to_string(), 70.0); // -$150 = 1.5% drawdown
+        let (event, closed) = pm.check_circuit_breakers(&prices).unwrap().unwrap();
+
+        assert_eq!(event.trip, CircuitBreakerTrip::MaxDrawdown);
+        assert!((event.portfolio_value - 9850.0).abs() < 1e-9);
+        assert_eq!(closed.len(), 1);
+        assert_eq!(
+            pm.all_positions()[0].exit_reason,
+            Some(ExitReason::CircuitBreaker)
+        );
+        assert!(pm.paused_until().is_some());
+
+        // The same trip is recorded only once while it stays active
+        assert!(pm.check_circuit_breakers(&prices).unwrap().is_none());
+        assert_eq!(pm.breaker_events().len(), 1);
+    }
+
+    #[test]
+    fn test_unrepresentable_pause_blocks_entries_without_panicking() {
+        let breakers = CircuitBreakers {
+            max_drawdown_pct: 0.01,
+            actions: BreakerActions {
+                pause_hours: Some(i64::MAX),
+                ..BreakerActions::none()
+            },
+            ..Default::default()
+        };
+        let mut pm = PositionManager::new(10000.0, breakers);
+        pm.open_position("SOL".to_string(), 100.0, 5.0).unwrap();
+
+        let mut prices = HashMap::new();
+        prices.insert("SOL".to_string(), 70.0);
+        assert!(pm.check_circuit_breakers(&prices).unwrap().is_some());
+        assert_eq!(pm.paused_until(), Some(DateTime::<Utc>::MAX_UTC));
+    }
+
+    #[test]
+    fn test_restored_breaker_state_keeps_pause_and_trip() {
+        let breakers = CircuitBreakers {
+            max_drawdown_pct: 0.01,
+            actions: BreakerActions::recommended(),
+            ..Default::default()
+        };
+        let mut pm = PositionManager::new(10000.0, breakers.clone());
+        pm.open_position("SOL".to_string(), 100.0, 5.0).unwrap();
+        let prices = HashMap::from([("SOL".to_string(), 70.0)]);
+        pm.check_circuit_breakers(&prices).unwrap().unwrap();
+        let state = pm.breaker_state();
+        assert_eq!(state.active_trip, Some(CircuitBreakerTrip::MaxDrawdown));
+        assert!(state.paused_until.is_some());
+
+        // A restarted bot still in drawdown keeps the pause and doesn't re-fire
+        let mut restarted = PositionManager::new(9850.0, breakers);
+        restarted.restore_breaker_state(state.clone());
+        assert_eq!(restarted.paused_until(), state.paused_until);
+        restarted.trading_state.peak_portfolio_value = 10000.0;
+        assert!(restarted.check_circuit_breakers(&prices).unwrap().is_none());
+        assert!(restarted.breaker_events().is_empty());
+    }
+
+    #[test]
+    fn test_flatten_leaves_unsellable_tokens_open() {
+        let breakers = CircuitBreakers {
+            max_drawdown_pct: 0.01,
+            actions: BreakerActions::recommended(),
+            ..Default::default()
+        };
+        let mut pm = PositionManager::new(10000.0, breakers);
+        pm.open_position("SOL".to_string(), 100.0, 5.0).unwrap();
+        pm.open_position("RUG".to_string(), 1.0, 200.0).unwrap();
+
+        let prices = HashMap::from([("SOL".to_string(), 95.0), ("RUG".to_string(), 0.01)]);
+        // RUG has no liquidity left: only SOL can be sold, and at a slipped price
+        let exits = HashMap::from([("SOL".to_string(), 94.0)]);
+        let (_, closed) = pm
+            .check_circuit_breakers_with_exits(&prices, &exits)
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(closed.len(), 1);
+        assert_eq!(pm.all_positions()[0].exit_price, Some(94.0));
+        assert!(pm.has_open_position("RUG"));
+    }
+
+    #[test]
+    fn test_trip_tightens_stops() {
+        let breakers = CircuitBreakers {
+            max_consecutive_losses: 1,
+            actions: BreakerActions::recommended(),
+            ..Default::default()
+        };
+        let mut pm = PositionManager::new(10000.0, breakers);
+        let loser = pm.open_position("JUP".to_string(), 1.0, 10.0).unwrap();
+        pm.open_position("SOL".to_string(), 100.0, 1.0).unwrap();
+        pm.close_position(loser, 0.9, ExitReason::StopLoss).unwrap();
+
+        let mut prices = HashMap::new();
+        prices.insert("SOL".to_string(), 120.0);
+        let (event, closed) = pm.check_circuit_breakers(&prices).unwrap().unwrap();
+
+        assert_eq!(event.trip, CircuitBreakerTrip::ConsecutiveLosses);
+        assert!(closed.is_empty());
+        // Stop raised from $92 to 3% below $120
+        let sol = pm.get_open_position("SOL").unwrap();
+        assert!((sol.stop_loss - 116.4).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_trade_limit_trip_takes_no_action() {
+        let breakers = CircuitBreakers {
+            max_daily_trades: 2,
+            actions: BreakerActions::recommended(),
+            ..Default::default()
+        };
+        let mut pm = PositionManager::new(10000.0, breakers);
+        pm.open_position("SOL".to_string(), 100.0, 1.0).unwrap();
+        // Two profitable round trips hit the daily trade limit
+        for token in ["JUP", "BONK"] {
+            let id = pm.open_position(token.to_string(), 1.0, 10.0).unwrap();
+            pm.close_position(id, 1.1, ExitReason::TakeProfit).unwrap();
+        }
+        let stop_before = pm.get_open_position("SOL").unwrap().stop_loss;
+
+        let prices = HashMap::from([("SOL".to_string(), 120.0)]);
+        let (event, closed) = pm.check_circuit_breakers(&prices).unwrap().unwrap();
+
+        assert_eq!(event.trip, CircuitBreakerTrip::DailyTradeLimit);
+        assert!(closed.is_empty());
+        assert_eq!(pm.get_open_position("SOL").unwrap().stop_loss, stop_before);
+        assert_eq!(pm.paused_until(), None);
+        assert_eq!(pm.breaker_events().len(), 1);
+    }
+
+    #[test]
+    fn test_default_actions_only_record_the_trip() {
+        let breakers = CircuitBreakers {
+            max_drawdown_pct: 0.01,
+            ..Default::default()
+        };
+        let mut pm = PositionManager::new(10000.0, breakers);
+        pm.open_position("SOL".to_string(), 100.0, 5.0).unwrap();
+
+        let prices = HashMap::from([("SOL".to_string(), 70.0)]);
+        let (event, closed) = pm.check_circuit_breakers(&prices).unwrap().unwrap();
+
+        assert_eq!(event.trip, CircuitBreakerTrip::MaxDrawdown);
+        assert!(closed.is_empty());
+        assert!(pm.has_open_position("SOL"));
+        assert_eq!(pm.paused_until(), None);
+    }
+
     #[test]
     fn test_portfolio_value() {
         let mut pm = PositionManager::new(10000.0, CircuitBreakers::default());
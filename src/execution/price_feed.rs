@@ -42,7 +42,8 @@ impl PriceFeedManager {
             high: snapshot.price,
             low: snapshot.price,
             close: snapshot.price,
-            volume: price_data.volume_24h,
+            volume: price_data.volume_5m,
+            provenance: crate::models::CandleProvenance::Live,
         };
         self.buffer.add_candle(candle)?;
 
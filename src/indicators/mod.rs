@@ -3,6 +3,8 @@
 
 pub mod moving_average;
 pub mod rsi;
+pub mod volatility;
 
 pub use moving_average::{calculate_ema, calculate_sma};
 pub use rsi::calculate_rsi;
+pub use volatility::{calculate_atr, calculate_return_volatility};
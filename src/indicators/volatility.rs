@@ -0,0 +1,119 @@
+use crate::models::Candle;
+
+/// Calculate Average True Range (ATR)
+///
+/// True range is the largest of:
+/// - high - low
+/// - |high - previous close|
+/// - |low - previous close|
+///
+/// Returns the simple average of the last `period` true ranges (in price units).
+pub fn calculate_atr(candles: &[Candle], period: usize) -> Option<f64> {
+    if period == 0 || candles.len() < period + 1 {
+        return None;
+    }
+
+    let true_ranges: Vec<f64> = candles
+        .windows(2)
+        .map(|w| {
+            let prev_close = w[0].close;
+            let c = &w[1];
+            (c.high - c.low)
+                .max((c.high - prev_close).abs())
+                .max((c.low - prev_close).abs())
+        })
+        .collect();
+
+    let sum: f64 = true_ranges.iter().rev().take(period).sum();
+    Some(sum / period as f64)
+}
+
+/// Calculate the standard deviation of simple returns over the last `period` changes
+///
+/// Returned as a fraction (0.01 = 1% per sample).
+pub fn calculate_return_volatility(prices: &[f64], period: usize) -> Option<f64> {
+    if period < 2 || prices.len() < period + 1 {
+        return None;
+    }
+
+    let window = &prices[prices.len() - (period + 1)..];
+    let returns: Vec<f64> = window
+        .windows(2)
+        .filter(|w| w[0] > 0.0)
+        .map(|w| (w[1] - w[0]) / w[0])
+        .collect();
+
+    if returns.len() < 2 {
+        return None;
+    }
+
+    let mean = returns.iter().sum::<f64>() / returns.len() as f64;
+    let variance = returns
+        .iter()
+        .map(|r| {
+            let diff = r - mean;
+            diff * diff
+        })
+        .sum::<f64>()
+        / (returns.len() - 1) as f64;
+
+    Some(variance.sqrt())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::CandleProvenance;
+    use chrono::Utc;
+
+    fn candle(high: f64, low: f64, close: f64) -> Candle {
+        Candle {
+            token: "TEST".to_string(),
+            timestamp: Utc::now(),
+            open: close,
+            high,
+            low,
+            close,
+            volume: 0.0,
+            provenance: CandleProvenance::Live,
+        }
+    }
+
+    #[test]
+    fn test_atr_uses_true_range() {
+        let candles = vec![
+            candle(101.0, 99.0, 100.0),
+            candle(102.0, 100.0, 101.0), // TR = 2
+            candle(110.0, 105.0, 108.0), // Gap up: TR = 110 - 101 = 9
+        ];
+
+        let atr = calculate_atr(&candles, 2).unwrap();
+        assert_eq!(atr, 5.5);
+    }
+
+    #[test]
+    fn test_atr_insufficient_data() {
+        let candles = vec![candle(101.0, 99.0, 100.0)];
+        assert!(calculate_atr(&candles, 14).is_none());
+    }
+
+    #[test]
+    fn test_return_volatility_flat_prices() {
+        let prices = vec![100.0; 20];
+        assert_eq!(calculate_return_volatility(&prices, 10), Some(0.0));
+    }
+
+    #[test]
+    fn test_return_volatility_scales_with_swings() {
+        let calm: Vec<f64> = (0..30)
+            .map(|i| if i % 2 == 0 { 100.0 } else { 101.0 })
+            .collect();
+        let wild: Vec<f64> = (0..30)
+            .map(|i| if i % 2 == 0 { 100.0 } else { 110.0 })
+            .collect();
+
+        let calm_vol = calculate_return_volatility(&calm, 20).unwrap();
+        let wild_vol = calculate_return_volatility(&wild, 20).unwrap();
+        assert!(wild_vol > calm_vol * 5.0);
+    }
+}
@@ -4,6 +4,7 @@ pub mod backfill;
 pub mod backtest;
 pub mod db;
 pub mod discovery;
+pub mod engine;
 pub mod execution;
 pub mod indicators;
 pub mod llm;
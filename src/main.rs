@@ -1,21 +1,38 @@
 use chrono::{Timelike, Utc};
-use clap::{Parser, Subcommand};
+use clap::{Args, Parser, Subcommand};
 use cryptobot::api::birdeye::{BirdeyeClient, TrendingToken};
-use cryptobot::api::CoinGeckoClient;
-use cryptobot::backfill::backfill_token;
+use cryptobot::api::JupiterClient;
+use cryptobot::backfill::{
+    backfill_range, backfill_token, repair_gaps, scan_gaps, ChunkedBackfill, ProviderChain,
+    ProviderKind,
+};
+use cryptobot::backtest::benchmark::BUY_AND_HOLD;
+use cryptobot::backtest::monte_carlo::{resample_trades, seed_sweep};
+use cryptobot::backtest::registry::print_comparison;
+use cryptobot::backtest::report::write_reports;
+use cryptobot::backtest::spec::parse_date;
+use cryptobot::backtest::{
+    BacktestJob, BacktestMetrics, BacktestReport, BacktestRun, BacktestSpec, DataRange,
+    EquityCurve, ExecutionTiming, MarketScenario, ModelKind, MonteCarloConfig, ReportFormat,
+    Resampling, StrategyKind, SyntheticDataGenerator, SyntheticModel, SyntheticSource,
+};
 use cryptobot::db::PostgresPersistence;
 use cryptobot::discovery::safety::is_safe_token;
+use cryptobot::engine::{
+    LiveMarketData, MarketDataSource, NoPriceImpact, ReplaySource, TradingEngine,
+};
 use cryptobot::execution::{
-    ExecutionAction, Executor, Position, PositionManager, PriceFeedManager,
+    ExitReason, LiquidityLimits, Position, PositionManager, PriceFeedManager,
 };
 use cryptobot::models::Token;
-use cryptobot::persistence::RedisPersistence;
-use cryptobot::risk::CircuitBreakers;
+use cryptobot::persistence::{files, CandleSource, CandleStore, DataFormat, RedisPersistence};
+use cryptobot::risk::{
+    BreakerActions, CircuitBreakers, CorrelationLimits, HaltMode, HaltState, PositionSizing,
+};
 use cryptobot::strategy::momentum::MomentumStrategy;
-use cryptobot::strategy::signals::validate_candle_uniformity;
-use cryptobot::strategy::Strategy;
 use cryptobot::Result;
 use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
 use std::sync::{Arc, Mutex, RwLock};
 use tokio::time::{interval_at, Duration, Instant};
 
@@ -51,24 +68,465 @@ enum Commands {
         /// Token mint address
         address: String,
 
-        /// Number of days to backfill (default: 7)
+        /// Number of days to backfill (default: 7; ignored when --from is set)
         #[arg(short, long, default_value = "7")]
         days: u32,
 
+        /// Start of the range (YYYY-MM-DD or RFC 3339; default: --days before --to)
+        #[arg(long, value_parser = parse_date)]
+        from: Option<chrono::DateTime<Utc>>,
+
+        /// End of the range (YYYY-MM-DD or RFC 3339; default: now)
+        #[arg(long, value_parser = parse_date)]
+        to: Option<chrono::DateTime<Utc>>,
+
+        /// Hours fetched per request window (progress is saved after each one)
+        #[arg(long, default_value = "24")]
+        chunk_hours: u32,
+
+        /// Ignore saved progress and start again from the beginning of the range
+        #[arg(long)]
+        restart: bool,
+
         /// Force overwrite existing data
         #[arg(short, long)]
         force: bool,
+
+        /// Providers to try in order: birdeye (premium), geckoterminal, coingecko
+        /// (default: BACKFILL_PROVIDERS or geckoterminal,coingecko)
+        #[arg(short, long, value_delimiter = ',')]
+        providers: Option<Vec<ProviderKind>>,
+    },
+
+    /// Show or set the global trading halt (kill switch)
+    Halt {
+        /// resume | no-new-entries | flatten-all (omit to show the current state)
+        mode: Option<HaltMode>,
+
+        /// Why trading was halted (recorded with the flag)
+        #[arg(short, long)]
+        reason: Option<String>,
+    },
+
+    /// Backtest a strategy on stored candles or synthetic scenarios
+    Backtest(Box<BacktestArgs>),
+
+    /// Replay stored candles through the live trading loop (paper trading)
+    Replay {
+        /// Comma-separated tokens with candles in Redis, or "all"
+        #[arg(short, long, value_delimiter = ',', default_value = "all")]
+        tokens: Vec<String>,
+
+        /// Read candles from files in this directory instead of Redis
+        #[arg(long)]
+        data_dir: Option<PathBuf>,
+
+        /// Start of the replay (YYYY-MM-DD or RFC 3339)
+        #[arg(long, value_parser = parse_date)]
+        from: Option<chrono::DateTime<Utc>>,
+
+        /// End of the replay (YYYY-MM-DD or RFC 3339)
+        #[arg(long, value_parser = parse_date)]
+        to: Option<chrono::DateTime<Utc>>,
+
+        /// Speed relative to real time (288 = a day every 5 minutes; default: as fast as possible)
+        #[arg(long)]
+        speed: Option<f64>,
+
+        /// Starting capital (default: INITIAL_PORTFOLIO_VALUE)
+        #[arg(long)]
+        capital: Option<f64>,
+
+        /// Log every tick like the live bot
+        #[arg(short, long)]
+        verbose: bool,
+    },
+
+    /// Browse stored backtest runs
+    Backtests {
+        #[command(subcommand)]
+        command: BacktestsCommand,
+    },
+
+    /// Export stored candles to files or import them back into Redis
+    Data {
+        #[command(subcommand)]
+        command: DataCommand,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum DataCommand {
+    /// Write each token's stored candles to `<dir>/<TOKEN>.<ext>`
+    Export {
+        /// Comma-separated tokens with candles in Redis, or "all"
+        #[arg(short, long, value_delimiter = ',', default_value = "all")]
+        tokens: Vec<String>,
+
+        /// Output directory
+        #[arg(short, long, default_value = "data")]
+        dir: PathBuf,
+
+        /// csv, jsonl or columnar
+        #[arg(short, long, default_value = "csv")]
+        format: DataFormat,
+
+        /// First candle to export (YYYY-MM-DD or RFC 3339)
+        #[arg(long, value_parser = parse_date)]
+        from: Option<chrono::DateTime<Utc>>,
+
+        /// Last candle to export (YYYY-MM-DD or RFC 3339)
+        #[arg(long, value_parser = parse_date)]
+        to: Option<chrono::DateTime<Utc>>,
+    },
+
+    /// Load candle files from a directory into Redis
+    Import {
+        /// Directory with `<TOKEN>.csv`, `.jsonl` or `.columns.json` files
+        #[arg(short, long, default_value = "data")]
+        dir: PathBuf,
+
+        /// Only these tokens (default: every file in the directory)
+        #[arg(short, long, value_delimiter = ',')]
+        tokens: Option<Vec<String>>,
+
+        /// Only files in this format
+        #[arg(short, long)]
+        format: Option<DataFormat>,
+
+        /// First candle to import (YYYY-MM-DD or RFC 3339)
+        #[arg(long, value_parser = parse_date)]
+        from: Option<chrono::DateTime<Utc>>,
+
+        /// Last candle to import (YYYY-MM-DD or RFC 3339)
+        #[arg(long, value_parser = parse_date)]
+        to: Option<chrono::DateTime<Utc>>,
+    },
+
+    /// Rewrite price-only snapshots stored by older versions as OHLCV candles
+    Migrate {
+        /// Comma-separated tokens with candles in Redis, or "all"
+        #[arg(short, long, value_delimiter = ',', default_value = "all")]
+        tokens: Vec<String>,
+
+        /// Only count what would be migrated
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// List missing 5-minute slots in stored candles
+    Gaps {
+        /// Comma-separated tokens with candles in Redis, or "all"
+        #[arg(short, long, value_delimiter = ',', default_value = "all")]
+        tokens: Vec<String>,
+
+        /// How far back to scan
+        #[arg(long, default_value = "24")]
+        hours: u64,
+    },
+
+    /// Fill a token's gaps from historical providers
+    Repair {
+        /// Token symbol (e.g., SOL, JUP)
+        symbol: String,
+
+        /// Token mint address
+        address: String,
+
+        /// How far back to repair
+        #[arg(long, default_value = "24")]
+        hours: u64,
+
+        /// Linearly interpolate (zero volume) whatever providers cannot fill
+        #[arg(long)]
+        interpolate: bool,
+
+        /// Only interpolate; don't ask any provider
+        #[arg(long)]
+        no_providers: bool,
+
+        /// Providers to try in order (default: BACKFILL_PROVIDERS or geckoterminal,coingecko)
+        #[arg(short, long, value_delimiter = ',')]
+        providers: Option<Vec<ProviderKind>>,
     },
 }
 
+#[derive(Subcommand, Debug)]
+enum BacktestsCommand {
+    /// List recent runs (newest first)
+    List {
+        /// Only runs of this strategy
+        #[arg(short, long)]
+        strategy: Option<String>,
+
+        /// Number of runs to show
+        #[arg(short, long, default_value = "20")]
+        limit: i64,
+    },
+
+    /// Show the full report for a run
+    Show {
+        /// Run id (or a unique prefix)
+        id: String,
+    },
+
+    /// Compare runs side by side, including config differences
+    Compare {
+        /// Run ids (or unique prefixes)
+        #[arg(required = true, num_args = 2..)]
+        ids: Vec<String>,
+    },
+}
+
+/// Flags for `cryptobot backtest`; each one overrides the `--config` file
+#[derive(Args, Debug)]
+struct BacktestArgs {
+    /// TOML file with the full backtest setup (see backtests/*.toml)
+    #[arg(short, long)]
+    config: Option<PathBuf>,
+
+    /// Run name used in reports and the registry
+    #[arg(long)]
+    name: Option<String>,
+
+    /// Comma-separated tokens with candles in Redis, or "all" for every snapshots:* key
+    #[arg(short, long, value_delimiter = ',')]
+    tokens: Option<Vec<String>>,
+
+    /// Read candles from files in this directory (`cryptobot data export`) instead of Redis
+    #[arg(long)]
+    data_dir: Option<PathBuf>,
+
+    /// Comma-separated synthetic scenarios to run instead of Redis data
+    #[arg(long, value_delimiter = ',')]
+    scenarios: Option<Vec<MarketScenario>>,
+
+    /// Comma-separated price models with preset parameters: gbm, garch, jump-diffusion,
+    /// regime-switching
+    #[arg(long, value_delimiter = ',')]
+    models: Option<Vec<ModelKind>>,
+
+    /// Fit the price models to this token's stored candles (all kinds if none given)
+    #[arg(long, value_name = "TOKEN")]
+    fit: Option<String>,
+
+    /// Seed for synthetic scenarios and models
+    #[arg(long)]
+    seed: Option<u64>,
+
+    /// Start of the data range (YYYY-MM-DD or RFC 3339)
+    #[arg(long, value_parser = parse_date)]
+    from: Option<chrono::DateTime<Utc>>,
+
+    /// End of the data range (YYYY-MM-DD or RFC 3339)
+    #[arg(long, value_parser = parse_date)]
+    to: Option<chrono::DateTime<Utc>>,
+
+    /// momentum | buy-and-hold
+    #[arg(short, long)]
+    strategy: Option<StrategyKind>,
+
+    /// Starting capital (USD)
+    #[arg(long)]
+    capital: Option<f64>,
+
+    /// Position sizing, same syntax as POSITION_SIZING (e.g. fixed:0.05)
+    #[arg(long)]
+    sizing: Option<PositionSizing>,
+
+    /// Circuit breaker: max drawdown from peak (0.20 = 20%)
+    #[arg(long)]
+    max_drawdown_pct: Option<f64>,
+
+    /// Circuit breaker: max daily loss (0.05 = 5%)
+    #[arg(long)]
+    max_daily_loss_pct: Option<f64>,
+
+    /// Circuit breaker: losing trades in a row
+    #[arg(long)]
+    max_consecutive_losses: Option<u32>,
+
+    /// Circuit breaker: max position size (0.05 = 5% of portfolio)
+    #[arg(long)]
+    max_position_pct: Option<f64>,
+
+    /// Circuit breaker: trades per day
+    #[arg(long)]
+    max_daily_trades: Option<u32>,
+
+    /// Swap fee per side (0.0025 = 0.25%)
+    #[arg(long)]
+    fee_pct: Option<f64>,
+
+    /// Base slippage per swap (0.001 = 0.1%)
+    #[arg(long)]
+    slippage_pct: Option<f64>,
+
+    /// Priority + network fee per swap (USD)
+    #[arg(long)]
+    priority_fee: Option<f64>,
+
+    /// Pool liquidity (USD) for size-dependent slippage
+    #[arg(long)]
+    liquidity: Option<f64>,
+
+    /// Pool liquidity as a multiple of each candle's volume (overrides --liquidity)
+    #[arg(long)]
+    liquidity_volume_ratio: Option<f64>,
+
+    /// Candles trading less than this (USD) can't fill any swap
+    #[arg(long)]
+    min_fill_volume: Option<f64>,
+
+    /// Fill timing: next-open | close
+    #[arg(long)]
+    timing: Option<ExecutionTiming>,
+
+    /// Also backtest each token on its own
+    #[arg(long)]
+    per_token: bool,
+
+    /// Token with stored candles to benchmark against (besides buy-and-hold), "none" to skip
+    #[arg(long)]
+    benchmark: Option<String>,
+
+    /// Monte Carlo paths per run: bootstrap/shuffle the trades, re-run scenarios across seeds
+    #[arg(long)]
+    monte_carlo: Option<usize>,
+
+    /// Worker threads for running backtests in parallel (default: one per core)
+    #[arg(long)]
+    threads: Option<usize>,
+
+    /// Report format: text (stdout only), json, csv or html
+    #[arg(short, long)]
+    output: Option<ReportFormat>,
+
+    /// Directory for json/csv/html reports
+    #[arg(long)]
+    output_dir: Option<PathBuf>,
+}
+
+impl BacktestArgs {
+    /// Load the `--config` file (or defaults) and apply the flags on top
+    fn into_spec(self) -> Result<BacktestSpec> {
+        let mut spec = match &self.config {
+            Some(path) => BacktestSpec::from_file(path)?,
+            None => BacktestSpec::default(),
+        };
+
+        if self.name.is_some() {
+            spec.name = self.name;
+        }
+        if let Some(tokens) = self.tokens {
+            spec.tokens = tokens;
+            spec.scenarios.clear();
+            spec.models.clear();
+        }
+        if let Some(scenarios) = self.scenarios {
+            spec.scenarios = scenarios;
+        }
+        if let Some(models) = self.models {
+            spec.models = models.into_iter().map(SyntheticModel::preset).collect();
+        }
+        if self.fit.is_some() {
+            spec.fit_token = self.fit;
+        }
+        if self.data_dir.is_some() {
+            spec.data_dir = self.data_dir;
+        }
+        if let Some(seed) = self.seed {
+            spec.seed = seed;
+        }
+        if self.from.is_some() {
+            spec.from = self.from;
+        }
+        if self.to.is_some() {
+            spec.to = self.to;
+        }
+        if let Some(strategy) = self.strategy {
+            spec.strategy = strategy;
+        }
+        if let Some(capital) = self.capital {
+            spec.capital = capital;
+        }
+        if let Some(sizing) = self.sizing {
+            spec.sizing = sizing;
+        }
+
+        let breakers = &mut spec.circuit_breakers;
+        if let Some(value) = self.max_drawdown_pct {
+            breakers.max_drawdown_pct = value;
+        }
+        if let Some(value) = self.max_daily_loss_pct {
+            breakers.max_daily_loss_pct = value;
+        }
+        if let Some(value) = self.max_consecutive_losses {
+            breakers.max_consecutive_losses = value;
+        }
+        if let Some(value) = self.max_position_pct {
+            breakers.max_position_size_pct = value;
+        }
+        if let Some(value) = self.max_daily_trades {
+            breakers.max_daily_trades = value;
+        }
+
+        let fills = &mut spec.fill_model;
+        if let Some(value) = self.fee_pct {
+            fills.swap_fee_pct = value;
+        }
+        if let Some(value) = self.slippage_pct {
+            fills.base_slippage_pct = value;
+        }
+        if let Some(value) = self.priority_fee {
+            fills.priority_fee_usd = value;
+        }
+        if self.liquidity.is_some() {
+            fills.liquidity_usd = self.liquidity;
+        }
+        if self.liquidity_volume_ratio.is_some() {
+            fills.liquidity_volume_ratio = self.liquidity_volume_ratio;
+        }
+        if let Some(value) = self.min_fill_volume {
+            fills.min_fill_volume_usd = value;
+        }
+        if let Some(timing) = self.timing {
+            fills.timing = timing;
+        }
+
+        spec.per_token |= self.per_token;
+        if let Some(benchmark) = self.benchmark {
+            spec.benchmark = Some(benchmark).filter(|b| !b.eq_ignore_ascii_case("none"));
+        }
+        if let Some(iterations) = self.monte_carlo {
+            spec.monte_carlo
+                .get_or_insert_with(MonteCarloConfig::default)
+                .iterations = iterations;
+        }
+        if self.threads.is_some() {
+            spec.threads = self.threads;
+        }
+        if let Some(output) = self.output {
+            spec.output = output;
+        }
+        if let Some(output_dir) = self.output_dir {
+            spec.output_dir = output_dir;
+        }
+
+        spec.validate()?;
+        Ok(spec)
+    }
+}
+
 // ============================================================================
 // Shared State
 // ============================================================================
 
 struct SharedState {
     tokens: Arc<RwLock<Vec<Token>>>,
+    /// Pool liquidity (USD) by symbol, as recorded at discovery time
+    token_liquidity: Arc<RwLock<HashMap<String, f64>>>,
     position_manager: Arc<Mutex<PositionManager>>,
-    initial_portfolio_value: f64,
 }
 
 // ============================================================================
@@ -96,22 +554,739 @@ fn next_5min_boundary_plus_30s() -> Instant {
     next_5min_boundary() + Duration::from_secs(30)
 }
 
-#[tokio::main]
-async fn main() -> Result<()> {
-    dotenvy::dotenv().ok();
-    setup_logging();
+#[tokio::main]
+async fn main() -> Result<()> {
+    dotenvy::dotenv().ok();
+    let cli = Cli::parse();
+
+    // Replays run thousands of ticks; only show problems unless asked
+    match &cli.command {
+        Some(Commands::Replay { verbose: false, .. }) => setup_logging("cryptobot=warn"),
+        _ => setup_logging("cryptobot=info,cryptobot::strategy=debug"),
+    }
+
+    match cli.command {
+        Some(Commands::Backfill {
+            symbol,
+            address,
+            days,
+            from,
+            to,
+            chunk_hours,
+            restart,
+            force,
+            providers,
+        }) => {
+            let to = to.unwrap_or_else(Utc::now);
+            let from = from.unwrap_or(to - chrono::Duration::days(days as i64));
+            let plan = ChunkedBackfill::new(from, to)
+                .with_chunk(chrono::Duration::hours(chunk_hours as i64))
+                .with_force_overwrite(force)
+                .with_restart(restart);
+            run_backfill(&symbol, &address, &plan, providers).await
+        }
+        Some(Commands::Halt { mode, reason }) => run_halt(mode, reason).await,
+        Some(Commands::Backtest(args)) => run_backtest(args.into_spec()?).await,
+        Some(Commands::Replay {
+            tokens,
+            data_dir,
+            from,
+            to,
+            speed,
+            capital,
+            verbose: _,
+        }) => run_replay(&tokens, data_dir.as_deref(), from, to, speed, capital).await,
+        Some(Commands::Backtests { command }) => run_backtests(command).await,
+        Some(Commands::Data { command }) => run_data(command).await,
+        None => run_bot().await,
+    }
+}
+
+async fn run_halt(mode: Option<HaltMode>, reason: Option<String>) -> Result<()> {
+    let redis_url =
+        std::env::var("REDIS_URL").unwrap_or_else(|_| "redis://127.0.0.1:6379".to_string());
+    let mut redis = RedisPersistence::new(&redis_url).await?;
+
+    if let Some(mode) = mode {
+        redis.save_halt_state(&HaltState::new(mode, reason)).await?;
+        tracing::info!("✅ Trading halt set to {} (applies on the next tick)", mode);
+    }
+
+    let state = redis.load_halt_state().await?;
+    tracing::info!("🛑 Halt mode: {}", state.mode);
+    tracing::info!("  Reason: {}", state.reason.as_deref().unwrap_or("-"));
+    tracing::info!(
+        "  Updated: {}",
+        state.updated_at.format("%Y-%m-%d %H:%M:%S UTC")
+    );
+
+    Ok(())
+}
+
+async fn run_backtest(mut spec: BacktestSpec) -> Result<()> {
+    let strategy = spec.build_strategy();
+    let mut runner = spec.runner();
+    let label = spec.label();
+
+    println!("\n🔬 Backtest: {}", label);
+    println!("   Strategy: {}", strategy.name());
+    println!("   Capital: ${:.2}", spec.capital);
+    println!("   Position sizing: {}", spec.sizing.name());
+
+    let mut results: Vec<(String, BacktestMetrics)> = Vec::new();
+    let mut runs = Vec::new();
+
+    if let Some(token) = spec.fit_token.clone() {
+        let mut store = CandleStore::connect(spec.data_dir.as_deref()).await?;
+        let candles = store
+            .load_candles_between(&token, spec.from, spec.to)
+            .await?;
+        spec.fit_models(&candles)?;
+        for model in &spec.models {
+            println!(
+                "   Fitted {} ({} candles of {}): {}",
+                model.name(),
+                candles.len(),
+                token,
+                serde_json::to_string(&model.price)?
+            );
+        }
+    }
+
+    let executor = spec.executor();
+    let sources = spec.synthetic_sources();
+    if !sources.is_empty() {
+        // Synthetic scenarios and models: each one is its own run
+        let mut jobs = Vec::new();
+        let mut records = Vec::new();
+        for source in &sources {
+            let mut generator = SyntheticDataGenerator::new(spec.seed);
+            let candles = generator.generate_source(source, spec.from, spec.candles, 5);
+            let data = DataRange::single("SYNTH", &candles);
+            let series = HashMap::from([("SYNTH".to_string(), candles)]);
+            let scenario = match source {
+                SyntheticSource::Scenario(scenario) => serde_json::json!({
+                    "name": scenario.to_string(),
+                    "seed": spec.seed,
+                }),
+                SyntheticSource::Model(model) => serde_json::json!({
+                    "name": model.name(),
+                    "seed": spec.seed,
+                    "model": model,
+                }),
+            };
+            let name = format!("{} {}", label, source.name());
+            jobs.push(BacktestJob::new(name, &runner, strategy.as_ref(), series));
+            records.push((data, scenario));
+        }
+
+        println!(
+            "\n⚙️  Running {} backtests on {} workers",
+            jobs.len(),
+            executor.threads()
+        );
+        for (result, (data, scenario)) in executor.run(jobs).into_iter().zip(records) {
+            match result.metrics {
+                Ok(metrics) => {
+                    runner.print_report(strategy.as_ref(), 1, &result.name, &metrics);
+                    if let Some(data) = data {
+                        let mut config = runner.config(strategy.as_ref());
+                        config["scenario"] = scenario;
+                        runs.push(BacktestRun::new(
+                            &result.name,
+                            strategy.name(),
+                            config,
+                            data,
+                            metrics.clone(),
+                        ));
+                    }
+                    results.push((result.name, metrics));
+                }
+                Err(e) => eprintln!("❌ Backtest failed for {}: {}", result.name, e),
+            }
+        }
+    } else {
+        // Stored candles: all tokens on shared capital, like the live bot
+        let mut store = CandleStore::connect(spec.data_dir.as_deref()).await?;
+
+        let tokens = if spec.all_tokens() {
+            store.list_tokens().await?
+        } else {
+            spec.tokens.clone()
+        };
+
+        let mut series = HashMap::new();
+        for token in &tokens {
+            let candles = store
+                .load_candles_between(token, spec.from, spec.to)
+                .await?;
+            if candles.is_empty() {
+                println!(
+                    "⚠️  No data for {} in the requested range - skipping",
+                    token
+                );
+                continue;
+            }
+            series.insert(token.clone(), candles);
+        }
+        if series.is_empty() {
+            println!(
+                "\n⚠️  No candles found in {} for the requested tokens and range",
+                store.describe()
+            );
+            match store {
+                CandleStore::Redis(_) => {
+                    println!("   Run: cargo run backfill <SYMBOL> <address> --days 7")
+                }
+                CandleStore::Files(_) => println!("   Run: cargo run data export --dir <DIR>"),
+            }
+            return Ok(());
+        }
+
+        if let Some(benchmark) = &spec.benchmark {
+            let candles = match series.get(benchmark) {
+                Some(candles) => candles.clone(),
+                None => {
+                    store
+                        .load_candles_between(benchmark, spec.from, spec.to)
+                        .await?
+                }
+            };
+            if candles.is_empty() {
+                println!("⚠️  No {} candles to benchmark against", benchmark);
+            } else {
+                runner = runner.with_benchmark(benchmark, &candles);
+            }
+        }
+
+        let mut batches = vec![(label.clone(), series.clone())];
+        if spec.per_token && series.len() > 1 {
+            let mut per_token: Vec<_> = series.into_iter().collect();
+            per_token.sort_by(|a, b| a.0.cmp(&b.0));
+            for (token, candles) in per_token {
+                let name = format!("{} {}", token, strategy.name());
+                batches.push((name, HashMap::from([(token, candles)])));
+            }
+        }
+
+        let mut records = Vec::new();
+        let jobs: Vec<_> = batches
+            .into_iter()
+            .map(|(name, series)| {
+                records.push((series.len(), DataRange::of(&series)));
+                BacktestJob::new(name, &runner, strategy.as_ref(), series)
+            })
+            .collect();
+
+        println!(
+            "\n⚙️  Running {} backtests on {} workers",
+            jobs.len(),
+            executor.threads()
+        );
+        for (result, (tokens, data)) in executor.run(jobs).into_iter().zip(records) {
+            match result.metrics {
+                Ok(metrics) => {
+                    runner.print_report(strategy.as_ref(), tokens, &result.name, &metrics);
+                    if let Some(data) = data {
+                        runs.push(BacktestRun::new(
+                            &result.name,
+                            strategy.name(),
+                            runner.config(strategy.as_ref()),
+                            data,
+                            metrics.clone(),
+                        ));
+                    }
+                    results.push((result.name, metrics));
+                }
+                Err(e) => eprintln!("❌ Backtest failed for {}: {}", result.name, e),
+            }
+        }
+    }
+
+    print_backtest_summary(&results);
+
+    if let Some(config) = &spec.monte_carlo {
+        for (name, metrics) in &results {
+            println!("\n═══ Robustness: {} ═══", name);
+            if metrics.trades.is_empty() {
+                println!("  No trades to resample");
+                continue;
+            }
+            for method in [Resampling::Bootstrap, Resampling::Shuffle] {
+                resample_trades(&metrics.trades, spec.capital, method, config).print_report();
+            }
+        }
+        for source in &sources {
+            match seed_sweep(
+                &executor,
+                &runner,
+                strategy.as_ref(),
+                source,
+                spec.candles,
+                config,
+            ) {
+                Ok(result) => result.print_report(),
+                Err(e) => eprintln!("❌ Seed sweep failed for {}: {}", source.name(), e),
+            }
+        }
+    }
+
+    let reports: Vec<BacktestReport> = results
+        .iter()
+        .map(|(name, metrics)| BacktestReport::new(name, strategy.name(), metrics))
+        .collect();
+    for path in write_reports(&reports, spec.output, &spec.output_dir)? {
+        println!("📝 Wrote {}", path.display());
+    }
+
+    save_backtest_runs(&runs).await;
+
+    Ok(())
+}
+
+fn print_backtest_summary(results: &[(String, BacktestMetrics)]) {
+    if results.is_empty() {
+        return;
+    }
+
+    println!(
+        "\n{:<36} {:>10} {:>9} {:>7} {:>6} {:>7} {:>7} {:>9}",
+        "Run", "P&L", "Return%", "Trades", "Win%", "MaxDD%", "Sharpe", "vs B&H%"
+    );
+    println!("{}", "─".repeat(98));
+    for (name, metrics) in results {
+        let name: String = name.chars().take(36).collect();
+        let excess = metrics
+            .benchmark(BUY_AND_HOLD)
+            .map(|b| format!("{:+.2}", b.excess_return_pct))
+            .unwrap_or_else(|| "-".to_string());
+        println!(
+            "{:<36} {:>10.2} {:>+9.2} {:>7} {:>6.1} {:>7.2} {:>7.2} {:>9}",
+            name,
+            metrics.total_pnl,
+            metrics.total_return_pct,
+            metrics.total_trades,
+            metrics.win_rate,
+            metrics.max_drawdown_pct,
+            metrics.sharpe_ratio,
+            excess
+        );
+    }
+    println!();
+}
+
+/// Record runs in the backtest registry when DATABASE_URL is set
+async fn save_backtest_runs(runs: &[BacktestRun]) {
+    let Ok(database_url) = std::env::var("DATABASE_URL") else {
+        return;
+    };
+    let postgres = match PostgresPersistence::new(&database_url, None).await {
+        Ok(postgres) => postgres,
+        Err(e) => {
+            eprintln!(
+                "⚠️  Not recording runs, failed to connect to Postgres: {}",
+                e
+            );
+            return;
+        }
+    };
+
+    for run in runs {
+        match postgres.save_backtest_run(run).await {
+            Ok(()) => println!(
+                "🗄️  Recorded run {} ({})",
+                &run.id.to_string()[..8],
+                run.name
+            ),
+            Err(e) => eprintln!("⚠️  Failed to record run {}: {}", run.name, e),
+        }
+    }
+}
+
+/// Feed stored candles through the same trading engine as the live bot
+async fn run_replay(
+    tokens: &[String],
+    data_dir: Option<&std::path::Path>,
+    from: Option<chrono::DateTime<Utc>>,
+    to: Option<chrono::DateTime<Utc>>,
+    speed: Option<f64>,
+    capital: Option<f64>,
+) -> Result<()> {
+    let mut store = CandleStore::connect(data_dir).await?;
+
+    let symbols = if tokens.iter().any(|t| t.eq_ignore_ascii_case("all")) {
+        store.list_tokens().await?
+    } else {
+        tokens.to_vec()
+    };
+    let mut history = HashMap::new();
+    for symbol in symbols {
+        let candles = store.load_candles_between(&symbol, from, to).await?;
+        if !candles.is_empty() {
+            history.insert(symbol, candles);
+        }
+    }
+    if history.is_empty() {
+        return Err(format!(
+            "No candles found in {} for the requested tokens and range",
+            store.describe()
+        )
+        .into());
+    }
+
+    // Same strategy, sizing and entry limits as `run_bot`; no historical quotes
+    // exist, so only the price impact check is skipped
+    let strategy = MomentumStrategy::default().with_poll_interval(POLL_INTERVAL_MINUTES);
+    let mut source = ReplaySource::new(history, strategy.lookback_hours());
+    if let Some(speed) = speed {
+        source = source.with_speed(speed);
+    }
+
+    let initial_portfolio_value = capital.unwrap_or_else(get_initial_portfolio_value);
+    let circuit_breakers = CircuitBreakers {
+        actions: BreakerActions::from_env(),
+        ..CircuitBreakers::default()
+    };
+    let position_manager = Arc::new(Mutex::new(PositionManager::new(
+        initial_portfolio_value,
+        circuit_breakers,
+    )));
+    let mut engine = TradingEngine::new(Box::new(strategy), position_manager, NoPriceImpact)
+        .with_sizing(get_position_sizing())
+        .with_poll_interval(POLL_INTERVAL_MINUTES)
+        .with_simulated_clock();
+    if let Some(limits) = get_liquidity_limits() {
+        engine = engine.with_liquidity_limits(limits);
+    }
+    if let Some(limits) = get_correlation_limits() {
+        engine = engine.with_correlation_limits(limits);
+    }
+
+    println!(
+        "⏪ Replaying {} ticks through the trading loop...",
+        source.len()
+    );
+
+    let mut equity = EquityCurve::new();
+    let mut prices: HashMap<String, f64> = HashMap::new();
+    while let Some(tick) = source.next_tick().await? {
+        let outcome = engine.process_tick(tick).await;
+        prices.extend(outcome.prices);
+
+        let pm = engine.position_manager();
+        let pm = pm.lock().unwrap();
+        let exposure: f64 = pm
+            .open_positions()
+            .iter()
+            .filter_map(|p| prices.get(&p.token).map(|price| p.quantity * price))
+            .sum();
+        let value = pm
+            .portfolio_value(&prices)
+            .unwrap_or(initial_portfolio_value);
+        equity.push(outcome.timestamp, value, exposure);
+    }
+
+    // Mark what's still open at the last prices
+    let pm = engine.position_manager();
+    let mut pm = pm.lock().unwrap();
+    pm.close_all(&prices, ExitReason::Manual)?;
+    let final_portfolio_value = pm
+        .portfolio_value(&prices)
+        .unwrap_or(initial_portfolio_value);
+
+    let metrics = BacktestMetrics::from_positions(
+        pm.all_positions().to_vec(),
+        initial_portfolio_value,
+        final_portfolio_value,
+        pm.breaker_events().to_vec(),
+    )
+    .with_equity_curve(equity);
+    metrics.print_report();
+    metrics.print_token_breakdown();
+
+    Ok(())
+}
+
+async fn run_backtests(command: BacktestsCommand) -> Result<()> {
+    let database_url = std::env::var("DATABASE_URL")
+        .unwrap_or_else(|_| "postgres://localhost/cryptobot".to_string());
+    let postgres = PostgresPersistence::new(&database_url, None).await?;
+
+    match command {
+        BacktestsCommand::List { strategy, limit } => {
+            let runs = postgres
+                .list_backtest_runs(strategy.as_deref(), limit)
+                .await?;
+            if runs.is_empty() {
+                println!("No backtest runs recorded yet");
+                return Ok(());
+            }
+
+            println!(
+                "{:<8} {:<16} {:<28} {:<18} {:<21} {:>9} {:>6} {:>7} {:>7}",
+                "ID",
+                "Created",
+                "Name",
+                "Strategy",
+                "Data",
+                "Return%",
+                "Trades",
+                "MaxDD%",
+                "Sharpe"
+            );
+            println!("{}", "─".repeat(128));
+            for run in runs {
+                let name: String = run.name.chars().take(28).collect();
+                let strategy: String = run.strategy.chars().take(18).collect();
+                println!(
+                    "{:<8} {:<16} {:<28} {:<18} {:<21} {:>+9.2} {:>6} {:>7.2} {:>7.2}",
+                    &run.id.to_string()[..8],
+                    run.created_at.format("%Y-%m-%d %H:%M"),
+                    name,
+                    strategy,
+                    format!(
+                        "{}..{}",
+                        run.data_start.format("%m-%d"),
+                        run.data_end.format("%m-%d")
+                    ),
+                    run.total_return_pct,
+                    run.total_trades,
+                    run.max_drawdown_pct,
+                    run.sharpe_ratio
+                );
+            }
+        }
+        BacktestsCommand::Show { id } => {
+            let run = postgres
+                .load_backtest_run(&id)
+                .await?
+                .ok_or_else(|| format!("No backtest run matching '{}'", id))?;
+
+            println!("Run:       {}", run.id);
+            println!("Name:      {}", run.name);
+            println!("Strategy:  {}", run.strategy);
+            println!("Tokens:    {}", run.tokens.join(", "));
+            println!(
+                "Data:      {} to {}",
+                run.data_start.format("%Y-%m-%d %H:%M"),
+                run.data_end.format("%Y-%m-%d %H:%M")
+            );
+            println!("Revision:  {}", run.git_revision.as_deref().unwrap_or("-"));
+            println!("Created:   {}", run.created_at.format("%Y-%m-%d %H:%M UTC"));
+            println!("Config:\n{}", serde_json::to_string_pretty(&run.config)?);
+
+            run.metrics.print_report();
+            if run.tokens.len() > 1 {
+                run.metrics.print_token_breakdown();
+            }
+        }
+        BacktestsCommand::Compare { ids } => {
+            let mut runs = Vec::new();
+            for id in &ids {
+                let run = postgres
+                    .load_backtest_run(id)
+                    .await?
+                    .ok_or_else(|| format!("No backtest run matching '{}'", id))?;
+                runs.push(run);
+            }
+            print_comparison(&runs);
+        }
+    }
+
+    Ok(())
+}
+
+async fn run_data(command: DataCommand) -> Result<()> {
+    let redis_url =
+        std::env::var("REDIS_URL").unwrap_or_else(|_| "redis://127.0.0.1:6379".to_string());
+    let mut redis = RedisPersistence::new(&redis_url).await?;
+
+    match command {
+        DataCommand::Export {
+            tokens,
+            dir,
+            format,
+            from,
+            to,
+        } => {
+            let tokens = if tokens.iter().any(|t| t.eq_ignore_ascii_case("all")) {
+                redis.list_candle_tokens().await?
+            } else {
+                tokens
+            };
+
+            let mut exported = 0;
+            for token in &tokens {
+                let candles = redis.load_candles_between(token, from, to).await?;
+                if candles.is_empty() {
+                    println!(
+                        "⚠️  No candles for {} in the requested range - skipping",
+                        token
+                    );
+                    continue;
+                }
+                let path = files::candle_path(&dir, token, format);
+                files::write_candles(&path, format, &candles)?;
+                println!(
+                    "📝 {} candles of {} -> {}",
+                    candles.len(),
+                    token,
+                    path.display()
+                );
+                exported += 1;
+            }
+            println!("✅ Exported {} of {} tokens", exported, tokens.len());
+        }
+        DataCommand::Import {
+            dir,
+            tokens,
+            format,
+            from,
+            to,
+        } => {
+            let candle_files: Vec<_> = files::list_candle_files(&dir)?
+                .into_iter()
+                .filter(|f| format.is_none_or(|format| f.format == format))
+                .filter(|f| {
+                    tokens
+                        .as_ref()
+                        .is_none_or(|tokens| tokens.contains(&f.token))
+                })
+                .collect();
+            if candle_files.is_empty() {
+                println!("⚠️  No matching candle files in {}", dir.display());
+                return Ok(());
+            }
+
+            for file in &candle_files {
+                let candles = files::read_candles(&file.path, file.format, &file.token)?;
+                let candles = files::filter_range(candles, from, to);
+                redis.save_candles(&file.token, &candles).await?;
+                println!(
+                    "📥 {} candles of {} <- {}",
+                    candles.len(),
+                    file.token,
+                    file.path.display()
+                );
+            }
+            println!("✅ Imported {} files", candle_files.len());
+        }
+        DataCommand::Migrate { tokens, dry_run } => {
+            let tokens = if tokens.iter().any(|t| t.eq_ignore_ascii_case("all")) {
+                redis.list_candle_tokens().await?
+            } else {
+                tokens
+            };
 
-    let cli = Cli::parse();
+            let mut migrated = 0;
+            for token in &tokens {
+                let stats = redis.migrate_snapshots(token, dry_run).await?;
+                if stats.migrated > 0 {
+                    println!(
+                        "{} {}: {} snapshots ({} already current)",
+                        if dry_run {
+                            "Would migrate"
+                        } else {
+                            "🔄 Migrated"
+                        },
+                        token,
+                        stats.migrated,
+                        stats.already_current
+                    );
+                }
+                migrated += stats.migrated;
+            }
+            if dry_run {
+                println!(
+                    "{} snapshots across {} tokens need migrating",
+                    migrated,
+                    tokens.len()
+                );
+            } else {
+                println!(
+                    "✅ Migrated {} snapshots across {} tokens",
+                    migrated,
+                    tokens.len()
+                );
+            }
+        }
+        DataCommand::Gaps { tokens, hours } => {
+            let tokens = if tokens.iter().any(|t| t.eq_ignore_ascii_case("all")) {
+                redis.list_candle_tokens().await?
+            } else {
+                tokens
+            };
 
-    match cli.command {
-        Some(Commands::Backfill {
+            let mut with_gaps = 0;
+            for token in &tokens {
+                let gaps = scan_gaps(&mut redis, token, hours).await?;
+                if gaps.is_empty() {
+                    continue;
+                }
+                with_gaps += 1;
+                let slots: usize = gaps.iter().map(|g| g.missing_slots().len()).sum();
+                println!("{}: {} gaps, {} missing slots", token, gaps.len(), slots);
+                for gap in &gaps {
+                    println!(
+                        "  {} -> {} ({} missing)",
+                        gap.after.format("%Y-%m-%d %H:%M:%S"),
+                        gap.before.format("%Y-%m-%d %H:%M:%S"),
+                        gap.missing_slots().len()
+                    );
+                }
+            }
+            println!(
+                "{} of {} tokens have gaps in the last {}h",
+                with_gaps,
+                tokens.len(),
+                hours
+            );
+        }
+        DataCommand::Repair {
             symbol,
             address,
-            days,
-            force,
-        }) => run_backfill(&symbol, &address, days, force).await,
-        None => run_bot().await,
+            hours,
+            interpolate,
+            no_providers,
+            providers,
+        } => {
+            let chain = if no_providers {
+                None
+            } else {
+                let kinds = providers.unwrap_or_else(ProviderKind::from_env);
+                Some(ProviderChain::from_env(&kinds).await?)
+            };
+            let stats = repair_gaps(
+                &symbol,
+                &address,
+                hours,
+                interpolate,
+                chain.as_ref(),
+                &mut redis,
+            )
+            .await?;
+
+            println!(
+                "{}: {} gaps ({} missing slots)",
+                symbol, stats.gaps_found, stats.missing_slots
+            );
+            println!("  From providers: {}", stats.filled_from_providers);
+            println!("  Interpolated:   {}", stats.interpolated);
+            if stats.provider_failures > 0 {
+                println!("  Provider failures: {}", stats.provider_failures);
+            }
+            if stats.remaining_gaps > 0 {
+                println!(
+                    "⚠️  {} gaps remain (rerun with --interpolate to fill them)",
+                    stats.remaining_gaps
+                );
+            } else {
+                println!("✅ No gaps left");
+            }
+        }
     }
+
+    Ok(())
 }
 
 async fn run_bot() -> Result<()> {
@@ -152,9 +1327,13 @@ async fn run_bot() -> Result<()> {
     initialize_and_run_backfill(&final_tokens, &redis_url).await;
 
     // Initialize position manager
-    let circuit_breakers = CircuitBreakers::default();
+    let circuit_breakers = CircuitBreakers {
+        actions: BreakerActions::from_env(),
+        ..CircuitBreakers::default()
+    };
     let max_daily_loss_pct = circuit_breakers.max_daily_loss_pct;
     let max_drawdown_pct = circuit_breakers.max_drawdown_pct;
+    let breaker_actions = circuit_breakers.actions.clone();
 
     let position_manager = initialize_position_manager(
         postgres_persistence.as_mut(),
@@ -164,16 +1343,20 @@ async fn run_bot() -> Result<()> {
     .await;
 
     // Create shared state
+    let token_liquidity = Arc::new(RwLock::new(HashMap::new()));
+    update_token_liquidity(&token_liquidity, &final_tokens);
+
     let shared_state = Arc::new(SharedState {
         tokens: Arc::new(RwLock::new(initial_tokens.clone())),
+        token_liquidity,
         position_manager,
-        initial_portfolio_value,
     });
 
     tracing::info!("\n📊 Configuration:");
     tracing::info!("  Portfolio Value: ${:.2}", initial_portfolio_value);
     tracing::info!("  Max Daily Loss: {}%", max_daily_loss_pct * 100.0);
     tracing::info!("  Max Drawdown: {}%", max_drawdown_pct * 100.0);
+    tracing::info!("  Breaker Actions: {:?}", breaker_actions);
     tracing::info!("  Tokens: {}", initial_tokens.len());
     for token in &initial_tokens {
         tracing::info!("    - {} ({})", token.symbol, token.name);
@@ -203,10 +1386,20 @@ async fn run_bot() -> Result<()> {
     // Spawn Loop 3: Token Discovery (every 4 hours)
     let discovery_task = {
         let tokens = shared_state.tokens.clone();
+        let token_liquidity = shared_state.token_liquidity.clone();
         let birdeye_key = birdeye_api_key.clone();
         let postgres_url = database_url.clone();
         tokio::spawn(async move {
-            token_discovery_loop(tokens, birdeye_key, postgres_url).await;
+            token_discovery_loop(tokens, token_liquidity, birdeye_key, postgres_url).await;
+        })
+    };
+
+    // Spawn Loop 4: Gap Repair (every 30 minutes)
+    let gap_repair_task = {
+        let tokens = shared_state.tokens.clone();
+        let redis_url = redis_url.clone();
+        tokio::spawn(async move {
+            gap_repair_loop(tokens, redis_url).await;
         })
     };
 
@@ -214,6 +1407,7 @@ async fn run_bot() -> Result<()> {
     tracing::info!("  🔄 Price Fetch: every 5 min (clock-aligned to XX:00, XX:05, etc.)");
     tracing::info!("  💹 Trading: every 5 min (30 sec after price fetch)");
     tracing::info!("  🔍 Discovery: every 4 hours");
+    tracing::info!("  🩹 Gap repair: every 30 min");
     tracing::info!("\nPress Ctrl+C to stop...\n");
 
     // Wait for Ctrl+C or task failure
@@ -230,6 +1424,9 @@ async fn run_bot() -> Result<()> {
         result = discovery_task => {
             tracing::error!("Discovery loop exited: {:?}", result);
         }
+        result = gap_repair_task => {
+            tracing::error!("Gap repair loop exited: {:?}", result);
+        }
     }
 
     tracing::info!("👋 CryptoBot stopped");
@@ -240,10 +1437,8 @@ async fn run_bot() -> Result<()> {
 // Initialization Functions
 // ============================================================================
 
-fn setup_logging() {
-    tracing_subscriber::fmt()
-        .with_env_filter("cryptobot=info,cryptobot::strategy=debug")
-        .init();
+fn setup_logging(filter: &str) {
+    tracing_subscriber::fmt().with_env_filter(filter).init();
 }
 
 async fn connect_to_postgres() -> Option<PostgresPersistence> {
@@ -268,36 +1463,49 @@ async fn connect_to_postgres() -> Option<PostgresPersistence> {
     }
 }
 
-async fn run_backfill(symbol: &str, address: &str, days: u32, force: bool) -> Result<()> {
+async fn run_backfill(
+    symbol: &str,
+    address: &str,
+    plan: &ChunkedBackfill,
+    providers: Option<Vec<ProviderKind>>,
+) -> Result<()> {
     tracing::info!(
-        "📥 Backfill Mode: {} ({}) for {} days",
+        "📥 Backfill Mode: {} ({}) from {} to {} in {}h windows",
         symbol,
         address,
-        days
+        plan.from.format("%Y-%m-%d %H:%M"),
+        plan.to.format("%Y-%m-%d %H:%M"),
+        plan.chunk.num_hours()
     );
-    if force {
+    if plan.force_overwrite {
         tracing::info!("  Force mode: will overwrite existing data");
     }
 
     // Get required environment variables
     let redis_url =
         std::env::var("REDIS_URL").unwrap_or_else(|_| "redis://127.0.0.1:6379".to_string());
-    let coingecko_api_key =
-        std::env::var("COINGECKO_API_KEY").expect("COINGECKO_API_KEY not found in environment");
 
-    // Initialize CoinGecko client
-    tracing::info!("🔄 Initializing CoinGecko client...");
-    let coingecko = CoinGeckoClient::new(coingecko_api_key).await?;
+    // Initialize historical data providers
+    tracing::info!("🔄 Initializing historical data providers...");
+    let kinds = providers.unwrap_or_else(ProviderKind::from_env);
+    let providers = ProviderChain::from_env(&kinds).await?;
 
     // Initialize Redis persistence
     tracing::info!("🔄 Connecting to Redis at {}...", redis_url);
     let mut redis = RedisPersistence::new(&redis_url).await?;
 
     // Run backfill
-    let stats = backfill_token(symbol, address, days, force, &coingecko, &mut redis).await?;
+    let stats = backfill_range(symbol, address, plan, &providers, &mut redis).await?;
 
     // Print results
     tracing::info!("\n📊 Backfill Results:");
+    if let Some(resumed_from) = stats.resumed_from {
+        tracing::info!("  Resumed from: {}", resumed_from);
+    }
+    tracing::info!("  Windows fetched: {}", stats.chunks);
+    if let Some(provider) = stats.provider {
+        tracing::info!("  Provider: {}", provider);
+    }
     tracing::info!("  Fetched data points: {}", stats.fetched_points);
     tracing::info!("  Converted candles: {}", stats.converted_candles);
     tracing::info!("  Skipped existing: {}", stats.skipped_existing);
@@ -306,7 +1514,7 @@ async fn run_backfill(symbol: &str, address: &str, days: u32, force: bool) -> Re
 
     if stats.stored_new > 0 {
         tracing::info!("\n✅ Backfill complete!");
-    } else if stats.skipped_existing > 0 {
+    } else if stats.skipped_existing > 0 || (stats.resumed_from.is_some() && stats.chunks == 0) {
         tracing::info!("\n✅ All data already present, nothing to backfill");
     } else {
         tracing::warn!("\n⚠️  No data was stored");
@@ -328,6 +1536,73 @@ fn get_initial_portfolio_value() -> f64 {
         .unwrap_or(10000.0)
 }
 
+/// A fraction in (0, 1] from env: `None` if unset, `default` (with a warning) if invalid
+fn env_fraction(name: &str, default: f64) -> Option<f64> {
+    let value = std::env::var(name).ok()?;
+    match value.trim().parse::<f64>() {
+        Ok(v) if v.is_finite() && v > 0.0 && v <= 1.0 => Some(v),
+        _ => {
+            tracing::warn!(
+                "Invalid {} ({}, expected a number in (0, 1]), using {}",
+                name,
+                value,
+                default
+            );
+            Some(default)
+        }
+    }
+}
+
+/// Liquidity limits from `MAX_PRICE_IMPACT_PCT` and `MAX_LIQUIDITY_FRACTION`
+/// (off unless at least one is set)
+fn get_liquidity_limits() -> Option<LiquidityLimits> {
+    let defaults = LiquidityLimits::default();
+    let max_price_impact_pct = env_fraction("MAX_PRICE_IMPACT_PCT", defaults.max_price_impact_pct);
+    let max_liquidity_fraction =
+        env_fraction("MAX_LIQUIDITY_FRACTION", defaults.max_liquidity_fraction);
+    if max_price_impact_pct.is_none() && max_liquidity_fraction.is_none() {
+        return None;
+    }
+
+    Some(LiquidityLimits {
+        max_price_impact_pct: max_price_impact_pct.unwrap_or(defaults.max_price_impact_pct),
+        max_liquidity_fraction: max_liquidity_fraction.unwrap_or(defaults.max_liquidity_fraction),
+        ..defaults
+    })
+}
+
+/// Correlation limits from `MAX_CORRELATION` and `MAX_CORRELATED_EXPOSURE_PCT`
+/// (off unless at least one is set)
+fn get_correlation_limits() -> Option<CorrelationLimits> {
+    let defaults = CorrelationLimits::default();
+    let max_correlation = env_fraction("MAX_CORRELATION", defaults.max_correlation);
+    let max_correlated_exposure_pct = env_fraction(
+        "MAX_CORRELATED_EXPOSURE_PCT",
+        defaults.max_correlated_exposure_pct,
+    );
+    if max_correlation.is_none() && max_correlated_exposure_pct.is_none() {
+        return None;
+    }
+
+    Some(CorrelationLimits {
+        max_correlation: max_correlation.unwrap_or(defaults.max_correlation),
+        max_correlated_exposure_pct: max_correlated_exposure_pct
+            .unwrap_or(defaults.max_correlated_exposure_pct),
+        ..defaults
+    })
+}
+
+/// Position sizing from `POSITION_SIZING` (e.g., "fixed:0.05", "volatility", "risk:0.004", "kelly:0.5")
+fn get_position_sizing() -> PositionSizing {
+    match std::env::var("POSITION_SIZING") {
+        Ok(value) => value.parse().unwrap_or_else(|e| {
+            tracing::warn!("Invalid POSITION_SIZING ({}), using default", e);
+            PositionSizing::default()
+        }),
+        Err(_) => PositionSizing::default(),
+    }
+}
+
 async fn initialize_position_manager(
     postgres: Option<&mut PostgresPersistence>,
     initial_portfolio_value: f64,
@@ -667,6 +1942,22 @@ async fn run_token_rotation(postgres: &PostgresPersistence) {
     }
 }
 
+/// Record discovery-time liquidity for each token
+///
+/// Tokens restored from the database have no fresh market data (liquidity 0.0),
+/// so their last known liquidity is kept.
+fn update_token_liquidity(
+    token_liquidity: &Arc<RwLock<HashMap<String, f64>>>,
+    trending_tokens: &[TrendingToken],
+) {
+    let mut map = token_liquidity.write().unwrap();
+    for token in trending_tokens {
+        if token.liquidity_usd > 0.0 {
+            map.insert(token.symbol.clone(), token.liquidity_usd);
+        }
+    }
+}
+
 fn convert_to_tokens(trending_tokens: &[TrendingToken]) -> Vec<Token> {
     trending_tokens
         .iter()
@@ -708,13 +1999,8 @@ async fn price_fetch_loop(tokens: Arc<RwLock<Vec<Token>>>, redis_url: String) {
     let mut ticker = interval_at(start, Duration::from_secs(300));
     ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
 
-    let mut tick_count = 0u32;
-    const CLEANUP_INTERVAL: u32 = 12; // Run cleanup every 12 ticks (every hour)
-    const KEEP_HOURS: u64 = 48; // Keep 48 hours of data (2x strategy needs)
-
     loop {
         ticker.tick().await;
-        tick_count += 1;
 
         tracing::info!("🔄 [PRICE FETCH] Tick at {}", Utc::now().format("%H:%M:%S"));
 
@@ -756,28 +2042,8 @@ async fn price_fetch_loop(tokens: Arc<RwLock<Vec<Token>>>, redis_url: String) {
             }
         }
 
-        // Periodic cleanup: remove data older than 48 hours
-        // TODO: reenable cleanup when appropriate. we want to accumulate data for backtesting, not just keep the last 48 hours.
-        //if tick_count % CLEANUP_INTERVAL == 0 {
-        //    tracing::info!("🧹 Running Redis cleanup (keeping last {}h)...", KEEP_HOURS);
-
-        //    for token in &token_list {
-        //        match redis.cleanup_old(&token.symbol, KEEP_HOURS).await {
-        //            Ok(removed) => {
-        //                if removed > 0 {
-        //                    tracing::info!(
-        //                        "  ✓ Cleaned up {} old snapshots for {}",
-        //                        removed,
-        //                        token.symbol
-        //                    );
-        //                }
-        //            }
-        //            Err(e) => {
-        //                tracing::warn!("  ✗ Failed to cleanup {}: {}", token.symbol, e);
-        //            }
-        //        }
-        //    }
-        //}
+        // TODO: reenable periodic cleanup (RedisPersistence::cleanup_old) when appropriate.
+        // We want to accumulate data for backtesting, not just keep the last 48 hours.
     }
 }
 
@@ -792,7 +2058,7 @@ async fn trading_execution_loop(
     tracing::info!("💹 Trading Execution Loop starting...");
 
     // Connect to Redis
-    let mut redis = match RedisPersistence::new(&redis_url).await {
+    let redis = match RedisPersistence::new(&redis_url).await {
         Ok(r) => r,
         Err(e) => {
             tracing::error!("Trading loop: Failed to connect to Redis: {}", e);
@@ -800,16 +2066,65 @@ async fn trading_execution_loop(
         }
     };
 
-    // Connect to Postgres (optional)
-    let mut postgres = if let Some(url) = postgres_url {
-        PostgresPersistence::new(&url, None).await.ok()
-    } else {
-        None
-    };
-
     let strategy = MomentumStrategy::default().with_poll_interval(POLL_INTERVAL_MINUTES);
-    let samples_needed = strategy.samples_needed(POLL_INTERVAL_MINUTES);
-    let mut executor = Executor::new(state.position_manager.clone());
+    let lookback_hours = strategy.lookback_hours();
+    let sizing = get_position_sizing();
+    tracing::info!("Position sizing: {:?}", sizing);
+
+    let mut engine = TradingEngine::new(
+        Box::new(strategy),
+        state.position_manager.clone(),
+        JupiterClient::new(),
+    )
+    .with_sizing(sizing)
+    .with_poll_interval(POLL_INTERVAL_MINUTES)
+    .with_token_liquidity(state.token_liquidity.clone());
+    if let Some(limits) = get_liquidity_limits() {
+        engine = engine.with_liquidity_limits(limits);
+    }
+    if let Some(limits) = get_correlation_limits() {
+        engine = engine.with_correlation_limits(limits);
+    }
+    match engine.liquidity_limits() {
+        Some(limits) => tracing::info!("Liquidity limits: {:?}", limits),
+        None => tracing::info!("Liquidity limits: off"),
+    }
+    match engine.correlation_limits() {
+        Some(limits) => tracing::info!("Correlation limits: {:?}", limits),
+        None => tracing::info!("Correlation limits: off"),
+    }
+
+    // Resume a breaker pause (and the trip behind it) from before a restart
+    match RedisPersistence::new(&redis_url).await {
+        Ok(mut breaker_redis) => {
+            match breaker_redis.load_breaker_state().await {
+                Ok(breaker) => {
+                    if let Some(until) = breaker.paused_until.filter(|u| *u > Utc::now()) {
+                        tracing::warn!(
+                            "Circuit breaker pause ({:?}) still active until {}",
+                            breaker.active_trip,
+                            until.format("%Y-%m-%d %H:%M UTC")
+                        );
+                    }
+                    state
+                        .position_manager
+                        .lock()
+                        .unwrap()
+                        .restore_breaker_state(breaker);
+                }
+                Err(e) => tracing::warn!("Could not load circuit breaker state: {}", e),
+            }
+            engine = engine.with_redis(breaker_redis);
+        }
+        Err(e) => tracing::warn!("Circuit breaker state will not be persisted: {}", e),
+    }
+
+    // Connect to Postgres (optional)
+    if let Some(url) = postgres_url {
+        if let Ok(postgres) = PostgresPersistence::new(&url, None).await {
+            engine = engine.with_postgres(postgres);
+        }
+    }
 
     // Create interval starting 30 seconds after next 5-minute boundary
     // This gives price_fetch_loop time to complete
@@ -820,79 +2135,68 @@ async fn trading_execution_loop(
     let mut ticker = interval_at(start, Duration::from_secs(300));
     ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
 
-    loop {
-        ticker.tick().await;
-        tracing::info!("💹 [TRADING] Tick at {}", Utc::now().format("%H:%M:%S"));
-
-        // Get current token list
-        let tokens = state.tokens.read().unwrap().clone();
-        let mut prices = HashMap::new();
+    let mut market_data = LiveMarketData::new(redis, state.tokens.clone(), lookback_hours, ticker)
+        .with_position_manager(state.position_manager.clone());
+    if let Err(e) = engine.run(&mut market_data).await {
+        tracing::error!("Trading loop stopped: {}", e);
+    }
+}
 
-        // Load candles from Redis for all tokens
-        for token in &tokens {
-            match redis
-                .load_candles(&token.symbol, strategy.lookback_hours())
-                .await
-            {
-                Ok(candles) => {
-                    // Validate candle uniformity early (fail fast)
-                    let expected_interval_secs = POLL_INTERVAL_MINUTES * 60;
-                    if let Err(e) = validate_candle_uniformity(&candles, expected_interval_secs) {
-                        tracing::warn!(
-                            "  {} - Skipping due to data quality issue: {}",
-                            token.symbol,
-                            e
-                        );
-                        continue;
-                    }
+/// Loop 4: Gap Repair Loop (every 30 minutes)
+/// Fills missed price ticks so a single gap doesn't keep a token out of trading
+/// until it ages out of the lookback window. Missing slots are fetched from the
+/// backfill providers; `GAP_REPAIR_INTERPOLATE=true` also interpolates the rest.
+async fn gap_repair_loop(tokens: Arc<RwLock<Vec<Token>>>, redis_url: String) {
+    const SCAN_HOURS: u64 = 24;
 
-                    if let Some(latest) = candles.last() {
-                        prices.insert(token.symbol.clone(), latest.close);
+    tracing::info!("🩹 Gap Repair Loop starting...");
 
-                        tracing::info!(
-                            "  {} @ ${:.4} ({} candles)",
-                            token.symbol,
-                            latest.close,
-                            candles.len()
-                        );
+    let providers = create_backfill_providers().await;
+    let interpolate = std::env::var("GAP_REPAIR_INTERPOLATE")
+        .ok()
+        .and_then(|v| v.parse::<bool>().ok())
+        .unwrap_or(false);
+    if providers.is_none() && !interpolate {
+        tracing::info!(
+            "Gap repair disabled: no backfill providers and GAP_REPAIR_INTERPOLATE unset"
+        );
+        return std::future::pending().await;
+    }
 
-                        // Generate signals if we have enough data
-                        if candles.len() >= samples_needed {
-                            process_token_signal(
-                                &strategy,
-                                token,
-                                &candles,
-                                latest.close,
-                                &state.position_manager,
-                                &mut executor,
-                                postgres.as_mut(),
-                            )
-                            .await;
-                        } else {
-                            tracing::info!(
-                                "    → Collecting data... ({}/{} needed)",
-                                candles.len(),
-                                samples_needed
-                            );
-                        }
-                    }
-                }
-                Err(e) => {
-                    tracing::warn!("  ✗ Failed to load candles for {}: {}", token.symbol, e);
-                }
-            }
+    let mut redis = match RedisPersistence::new(&redis_url).await {
+        Ok(r) => r,
+        Err(e) => {
+            tracing::error!("Gap repair loop: Failed to connect to Redis: {}", e);
+            return;
         }
+    };
+
+    // First pass a few minutes in, after the price fetch has run
+    let mut ticker = interval_at(
+        Instant::now() + Duration::from_secs(180),
+        Duration::from_secs(1800),
+    );
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
 
-        // Check exit conditions for all open positions
-        let closed_positions = check_and_close_positions(&state.position_manager, &prices);
-        save_positions_to_db(postgres.as_mut(), &closed_positions).await;
+    loop {
+        ticker.tick().await;
+        tracing::info!("🩹 [GAP REPAIR] Tick at {}", Utc::now().format("%H:%M:%S"));
 
-        // Log portfolio summary
-        log_portfolio_summary(
-            &state.position_manager,
-            &prices,
-            state.initial_portfolio_value,
-        );
+        let token_list = tokens.read().unwrap().clone();
+        for token in &token_list {
+            if let Err(e) = repair_gaps(
+                &token.symbol,
+                &token.mint_address,
+                SCAN_HOURS,
+                interpolate,
+                providers.as_deref(),
+                &mut redis,
+            )
+            .await
+            {
+                tracing::warn!("  ✗ Gap repair failed for {}: {}", token.symbol, e);
+            }
+        }
     }
 }
 
@@ -900,6 +2204,7 @@ async fn trading_execution_loop(
 /// Discovers trending tokens and updates the token list
 async fn token_discovery_loop(
     tokens: Arc<RwLock<Vec<Token>>>,
+    token_liquidity: Arc<RwLock<HashMap<String, f64>>>,
     birdeye_api_key: String,
     postgres_url: Option<String>,
 ) {
@@ -907,23 +2212,8 @@ async fn token_discovery_loop(
 
     let birdeye_client = BirdeyeClient::new(birdeye_api_key);
 
-    // Initialize CoinGecko client if API key is available (for backfilling)
-    let coingecko_client = match std::env::var("COINGECKO_API_KEY") {
-        Ok(api_key) => match CoinGeckoClient::new(api_key).await {
-            Ok(client) => {
-                tracing::info!("✓ CoinGecko backfill enabled");
-                Some(client)
-            }
-            Err(e) => {
-                tracing::warn!("Failed to initialize CoinGecko client: {}", e);
-                None
-            }
-        },
-        Err(_) => {
-            tracing::info!("CoinGecko backfill disabled (COINGECKO_API_KEY not set)");
-            None
-        }
-    };
+    // Historical data providers for backfilling new tokens
+    let backfill_providers = create_backfill_providers().await;
 
     // Get Redis URL for backfilling
     let redis_url =
@@ -956,7 +2246,7 @@ async fn token_discovery_loop(
                 }
 
                 // Check which tokens need backfilling (new OR insufficient data)
-                let tokens_to_backfill = if coingecko_client.is_some() {
+                let tokens_to_backfill = if backfill_providers.is_some() {
                     identify_tokens_needing_backfill(&final_tokens, &redis_url).await
                 } else {
                     Vec::new()
@@ -969,8 +2259,8 @@ async fn token_discovery_loop(
                     );
 
                     // Trigger backfill (non-blocking)
-                    if let Some(ref cg_client) = coingecko_client {
-                        spawn_backfill_tasks(tokens_to_backfill, cg_client.clone(), &redis_url)
+                    if let Some(ref providers) = backfill_providers {
+                        spawn_backfill_tasks(tokens_to_backfill, providers.clone(), &redis_url)
                             .await;
                     }
                 }
@@ -984,6 +2274,7 @@ async fn token_discovery_loop(
                 // Update shared token list
                 let new_tokens = convert_to_tokens(&final_tokens);
                 *tokens.write().unwrap() = new_tokens;
+                update_token_liquidity(&token_liquidity, &final_tokens);
 
                 tracing::info!("  ✓ Updated token list");
             }
@@ -994,28 +2285,29 @@ async fn token_discovery_loop(
     }
 }
 
-/// Initialize CoinGecko client and run backfill for tokens with insufficient data
-async fn initialize_and_run_backfill(tokens: &[TrendingToken], redis_url: &str) {
-    // Try to initialize CoinGecko client
-    let coingecko_client = match std::env::var("COINGECKO_API_KEY") {
-        Ok(api_key) => match CoinGeckoClient::new(api_key).await {
-            Ok(client) => {
-                tracing::info!("✓ CoinGecko backfill enabled");
-                Some(client)
-            }
-            Err(e) => {
-                tracing::warn!("Failed to initialize CoinGecko client: {}", e);
-                None
-            }
-        },
-        Err(_) => {
-            tracing::info!("COINGECKO_API_KEY not set, backfill disabled");
+/// Historical data providers for background backfills (`BACKFILL_PROVIDERS`
+/// order), or `None` if none is usable
+async fn create_backfill_providers() -> Option<Arc<ProviderChain>> {
+    match ProviderChain::from_env(&ProviderKind::from_env()).await {
+        Ok(chain) => {
+            let names: Vec<String> = chain
+                .providers()
+                .iter()
+                .map(|p| p.kind().to_string())
+                .collect();
+            tracing::info!("✓ Backfill enabled ({})", names.join(" > "));
+            Some(Arc::new(chain))
+        }
+        Err(e) => {
+            tracing::info!("Backfill disabled: {}", e);
             None
         }
-    };
+    }
+}
 
-    // Early return if no CoinGecko client
-    let Some(cg_client) = coingecko_client else {
+/// Set up the historical providers and run backfill for tokens with insufficient data
+async fn initialize_and_run_backfill(tokens: &[TrendingToken], redis_url: &str) {
+    let Some(providers) = create_backfill_providers().await else {
         return;
     };
 
@@ -1032,7 +2324,7 @@ async fn initialize_and_run_backfill(tokens: &[TrendingToken], redis_url: &str)
     );
 
     // Spawn backfill tasks (non-blocking)
-    spawn_backfill_tasks(tokens_to_backfill, cg_client, redis_url).await;
+    spawn_backfill_tasks(tokens_to_backfill, providers, redis_url).await;
 }
 
 /// Identify tokens that need backfilling (new tokens OR existing tokens with insufficient data)
@@ -1097,11 +2389,11 @@ async fn identify_tokens_needing_backfill(
 
 /// Spawn background tasks to backfill newly discovered tokens
 ///
-/// Uses a shared CoinGecko client (with shared rate limiter) and limits
-/// concurrent backfills to avoid overwhelming the API or Redis.
+/// Uses shared provider clients (with shared rate limiters) and limits
+/// concurrent backfills to avoid overwhelming the APIs or Redis.
 async fn spawn_backfill_tasks(
     new_tokens: Vec<(String, String)>,
-    coingecko_client: CoinGeckoClient,
+    providers: Arc<ProviderChain>,
     redis_url: &str,
 ) {
     const BACKFILL_DAYS: u32 = 1; // 1 day = ~287 candles at 5-min resolution
@@ -1114,7 +2406,7 @@ async fn spawn_backfill_tasks(
 
     for (symbol, address) in new_tokens {
         let permit = semaphore.clone().acquire_owned().await.unwrap();
-        let cg_client = coingecko_client.clone(); // Shares rate limiter!
+        let providers = providers.clone(); // Shares rate limiters!
         let redis_url_clone = redis_url.to_string();
 
         // Spawn a background task for backfilling
@@ -1141,7 +2433,7 @@ async fn spawn_backfill_tasks(
                     &address,
                     BACKFILL_DAYS,
                     false, // Don't force overwrite
-                    &cg_client,
+                    providers.as_ref(),
                     &mut redis,
                 )
                 .await
@@ -1186,224 +2478,3 @@ async fn spawn_backfill_tasks(
         });
     }
 }
-
-// ============================================================================
-// Helper Functions for Trading
-// ============================================================================
-
-fn check_and_close_positions(
-    position_manager: &Arc<Mutex<PositionManager>>,
-    prices: &HashMap<String, f64>,
-) -> Vec<Position> {
-    let mut pm = position_manager.lock().unwrap();
-    match pm.check_exits(prices) {
-        Ok(closed_ids) => {
-            let mut positions = Vec::new();
-            for position_id in closed_ids {
-                tracing::info!("✓ Position {} closed by exit condition", position_id);
-                if let Some(position) = pm.all_positions().iter().find(|p| p.id == position_id) {
-                    positions.push(position.clone());
-                }
-            }
-            positions
-        }
-        Err(e) => {
-            tracing::error!("Failed to check exits: {}", e);
-            Vec::new()
-        }
-    }
-}
-
-async fn save_positions_to_db(
-    postgres_persistence: Option<&mut PostgresPersistence>,
-    positions: &[Position],
-) {
-    if let Some(postgres) = postgres_persistence {
-        for position in positions {
-            if let Err(e) = postgres.save_position(position).await {
-                tracing::warn!("Failed to save position to Postgres: {}", e);
-            }
-        }
-    }
-}
-
-async fn process_token_signal(
-    strategy: &MomentumStrategy,
-    token: &Token,
-    candles: &[cryptobot::models::Candle],
-    current_price: f64,
-    position_manager: &Arc<Mutex<PositionManager>>,
-    executor: &mut Executor,
-    postgres_persistence: Option<&mut PostgresPersistence>,
-) {
-    match strategy.generate_signal(candles) {
-        Ok(signal) => {
-            tracing::info!("  Signal: {:?}", signal);
-
-            match executor.process_signal(&signal, &token.symbol, current_price) {
-                Ok(decision) => {
-                    tracing::info!("  Decision: {:?} - {}", decision.action, decision.reason);
-
-                    execute_decision(
-                        &decision.action,
-                        token,
-                        current_price,
-                        position_manager,
-                        postgres_persistence,
-                    )
-                    .await;
-                }
-                Err(e) => {
-                    tracing::error!("  Failed to process signal: {}", e);
-                }
-            }
-        }
-        Err(e) => {
-            tracing::warn!("  → Failed to generate signal: {}", e);
-        }
-    }
-}
-
-async fn execute_decision(
-    action: &ExecutionAction,
-    token: &Token,
-    current_price: f64,
-    position_manager: &Arc<Mutex<PositionManager>>,
-    postgres_persistence: Option<&mut PostgresPersistence>,
-) {
-    match action {
-        ExecutionAction::Execute { quantity } => {
-            execute_buy(
-                token,
-                current_price,
-                *quantity,
-                position_manager,
-                postgres_persistence,
-            )
-            .await;
-        }
-        ExecutionAction::Close {
-            position_id,
-            exit_reason,
-        } => {
-            execute_close(
-                *position_id,
-                current_price,
-                exit_reason.clone(),
-                position_manager,
-                postgres_persistence,
-            )
-            .await;
-        }
-        ExecutionAction::Skip => {
-            // Do nothing - already logged
-        }
-    }
-}
-
-async fn execute_buy(
-    token: &Token,
-    current_price: f64,
-    quantity: f64,
-    position_manager: &Arc<Mutex<PositionManager>>,
-    postgres_persistence: Option<&mut PostgresPersistence>,
-) {
-    tracing::info!(
-        "  → Would BUY {:.4} {} @ ${:.4} (total: ${:.2})",
-        quantity,
-        token.symbol,
-        current_price,
-        quantity * current_price
-    );
-
-    let opened_position = {
-        let mut pm = position_manager.lock().unwrap();
-        match pm.open_position(token.symbol.clone(), current_price, quantity) {
-            Ok(position_id) => {
-                tracing::info!("  ✓ Opened position {} for {}", position_id, token.symbol);
-                pm.all_positions()
-                    .iter()
-                    .find(|p| p.id == position_id)
-                    .cloned()
-            }
-            Err(e) => {
-                tracing::error!("  ✗ Failed to open position: {}", e);
-                None
-            }
-        }
-    };
-
-    if let Some(position) = opened_position {
-        save_positions_to_db(postgres_persistence, &[position]).await;
-    }
-}
-
-async fn execute_close(
-    position_id: uuid::Uuid,
-    current_price: f64,
-    exit_reason: cryptobot::execution::ExitReason,
-    position_manager: &Arc<Mutex<PositionManager>>,
-    postgres_persistence: Option<&mut PostgresPersistence>,
-) {
-    let closed_position = {
-        let mut pm = position_manager.lock().unwrap();
-        match pm.close_position(position_id, current_price, exit_reason) {
-            Ok(()) => {
-                tracing::info!(
-                    "  ✓ Closed position {} @ ${:.4}",
-                    position_id,
-                    current_price
-                );
-                pm.all_positions()
-                    .iter()
-                    .find(|p| p.id == position_id)
-                    .cloned()
-            }
-            Err(e) => {
-                tracing::error!("  ✗ Failed to close position: {}", e);
-                None
-            }
-        }
-    };
-
-    if let Some(position) = closed_position {
-        save_positions_to_db(postgres_persistence, &[position]).await;
-    }
-}
-
-fn log_portfolio_summary(
-    position_manager: &Arc<Mutex<PositionManager>>,
-    prices: &HashMap<String, f64>,
-    initial_portfolio_value: f64,
-) {
-    let pm = position_manager.lock().unwrap();
-    let portfolio_value = pm
-        .portfolio_value(prices)
-        .unwrap_or(initial_portfolio_value);
-    let open_positions = pm.open_positions();
-
-    tracing::info!("\n📊 Portfolio Summary:");
-    tracing::info!("  Value: ${:.2}", portfolio_value);
-    tracing::info!(
-        "  P&L: ${:.2} ({:.2}%)",
-        portfolio_value - initial_portfolio_value,
-        ((portfolio_value - initial_portfolio_value) / initial_portfolio_value) * 100.0
-    );
-    tracing::info!("  Open Positions: {}", open_positions.len());
-
-    for position in open_positions {
-        if let Some(&current_price) = prices.get(&position.token) {
-            let unrealized_pnl = (current_price - position.entry_price) * position.quantity;
-            let unrealized_pnl_pct =
-                ((current_price - position.entry_price) / position.entry_price) * 100.0;
-            tracing::info!(
-                "    {} | Entry: ${:.4} | Current: ${:.4} | P&L: ${:.2} ({:.2}%)",
-                position.token,
-                position.entry_price,
-                current_price,
-                unrealized_pnl,
-                unrealized_pnl_pct
-            );
-        }
-    }
-}
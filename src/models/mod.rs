@@ -17,6 +17,8 @@ pub struct PriceData {
     pub token: String,
     pub price: f64,
     pub volume_24h: f64,
+    /// USD volume over the last 5 minutes (the live candle volume)
+    pub volume_5m: f64,
     pub timestamp: DateTime<Utc>,
     pub source: DataSource,
 }
@@ -39,7 +41,52 @@ pub struct Candle {
     pub high: f64,
     pub low: f64,
     pub close: f64,
+    /// USD traded during the candle's own interval (5 minutes), never a rolling
+    /// 24h figure; 0.0 when the source has no per-candle volume (CoinGecko,
+    /// interpolated candles), which turns volume confirmation off
     pub volume: f64,
+    /// Where the candle came from (`Live` for data stored before this was tracked)
+    #[serde(default)]
+    pub provenance: CandleProvenance,
+}
+
+/// Origin and quality of a stored candle
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CandleProvenance {
+    /// Real price tick from the live feed (DexScreener)
+    #[default]
+    Live,
+    /// Candle from a historical provider (GeckoTerminal, Birdeye, CoinGecko)
+    Backfilled,
+    /// Synthesized to fill a gap: flat at the previous close or interpolated
+    Interpolated,
+}
+
+impl std::fmt::Display for CandleProvenance {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Live => write!(f, "live"),
+            Self::Backfilled => write!(f, "backfilled"),
+            Self::Interpolated => write!(f, "interpolated"),
+        }
+    }
+}
+
+impl std::str::FromStr for CandleProvenance {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "live" => Ok(Self::Live),
+            "backfilled" | "backfill" => Ok(Self::Backfilled),
+            "interpolated" | "synthetic" => Ok(Self::Interpolated),
+            _ => Err(format!(
+                "Unknown candle provenance '{}' (expected live, backfilled or interpolated)",
+                s
+            )),
+        }
+    }
 }
 
 /// Data source identifier
@@ -0,0 +1,435 @@
+use crate::models::{Candle, CandleProvenance};
+use crate::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// Header row of candle CSV files (`provenance` is optional when reading)
+const CSV_HEADER: &str = "timestamp,open,high,low,close,volume,provenance";
+
+/// On-disk layout of exported candles (one file per token)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DataFormat {
+    /// `timestamp,open,high,low,close,volume,provenance` rows, RFC 3339 timestamps
+    #[default]
+    Csv,
+    /// One JSON candle per line
+    Jsonl,
+    /// One JSON document with an array per field (unix-second timestamps),
+    /// laid out column by column like Parquet
+    Columnar,
+}
+
+impl DataFormat {
+    pub const ALL: [DataFormat; 3] = [Self::Csv, Self::Jsonl, Self::Columnar];
+
+    /// File extension, without the leading dot
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Csv => "csv",
+            Self::Jsonl => "jsonl",
+            Self::Columnar => "columns.json",
+        }
+    }
+
+    /// Format and token of a `{token}.{extension}` file name
+    pub fn parse_file_name(path: &Path) -> Option<(String, Self)> {
+        let name = path.file_name()?.to_str()?;
+        Self::ALL.into_iter().find_map(|format| {
+            name.strip_suffix(format.extension())
+                .and_then(|stem| stem.strip_suffix('.'))
+                .filter(|token| !token.is_empty())
+                .map(|token| (token.to_string(), format))
+        })
+    }
+}
+
+impl fmt::Display for DataFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Csv => write!(f, "csv"),
+            Self::Jsonl => write!(f, "jsonl"),
+            Self::Columnar => write!(f, "columnar"),
+        }
+    }
+}
+
+impl FromStr for DataFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "csv" => Ok(Self::Csv),
+            "jsonl" | "json-lines" | "ndjson" => Ok(Self::Jsonl),
+            "columnar" | "columns" => Ok(Self::Columnar),
+            _ => Err(format!(
+                "Unknown data format '{}' (expected csv, jsonl or columnar)",
+                s
+            )),
+        }
+    }
+}
+
+/// A token's candle file found in a data directory
+#[derive(Debug, Clone, PartialEq)]
+pub struct CandleFile {
+    pub token: String,
+    pub path: PathBuf,
+    pub format: DataFormat,
+}
+
+/// Column-oriented candles as stored by [`DataFormat::Columnar`]
+#[derive(Debug, Serialize, Deserialize)]
+struct CandleColumns {
+    token: String,
+    timestamp: Vec<i64>,
+    open: Vec<f64>,
+    high: Vec<f64>,
+    low: Vec<f64>,
+    close: Vec<f64>,
+    volume: Vec<f64>,
+    /// Empty in files written before provenance was tracked (all live)
+    #[serde(default)]
+    provenance: Vec<CandleProvenance>,
+}
+
+/// Path of `token`'s file in `dir`
+pub fn candle_path(dir: &Path, token: &str, format: DataFormat) -> PathBuf {
+    dir.join(format!("{}.{}", token, format.extension()))
+}
+
+/// Every candle file in `dir`, sorted by token (then format)
+pub fn list_candle_files(dir: &Path) -> Result<Vec<CandleFile>> {
+    let mut files = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if let Some((token, format)) = DataFormat::parse_file_name(&path) {
+            files.push(CandleFile {
+                token,
+                path,
+                format,
+            });
+        }
+    }
+    files.sort_by(|a, b| {
+        a.token
+            .cmp(&b.token)
+            .then(a.format.extension().cmp(b.format.extension()))
+    });
+    Ok(files)
+}
+
+/// Candles within `from..=to` (`None` = unbounded)
+pub fn filter_range(
+    candles: Vec<Candle>,
+    from: Option<DateTime<Utc>>,
+    to: Option<DateTime<Utc>>,
+) -> Vec<Candle> {
+    candles
+        .into_iter()
+        .filter(|c| from.is_none_or(|from| c.timestamp >= from))
+        .filter(|c| to.is_none_or(|to| c.timestamp <= to))
+        .collect()
+}
+
+/// Write candles to `path`, replacing the file
+pub fn write_candles(path: &Path, format: DataFormat, candles: &[Candle]) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut out = BufWriter::new(File::create(path)?);
+
+    match format {
+        DataFormat::Csv => {
+            writeln!(out, "{}", CSV_HEADER)?;
+            for c in candles {
+                writeln!(
+                    out,
+                    "{},{},{},{},{},{},{}",
+                    c.timestamp.to_rfc3339(),
+                    c.open,
+                    c.high,
+                    c.low,
+                    c.close,
+                    c.volume,
+                    c.provenance
+                )?;
+            }
+        }
+        DataFormat::Jsonl => {
+            for c in candles {
+                serde_json::to_writer(&mut out, c)?;
+                writeln!(out)?;
+            }
+        }
+        DataFormat::Columnar => {
+            let columns = CandleColumns {
+                token: candles.first().map(|c| c.token.clone()).unwrap_or_default(),
+                timestamp: candles.iter().map(|c| c.timestamp.timestamp()).collect(),
+                open: candles.iter().map(|c| c.open).collect(),
+                high: candles.iter().map(|c| c.high).collect(),
+                low: candles.iter().map(|c| c.low).collect(),
+                close: candles.iter().map(|c| c.close).collect(),
+                volume: candles.iter().map(|c| c.volume).collect(),
+                provenance: candles.iter().map(|c| c.provenance).collect(),
+            };
+            serde_json::to_writer(&mut out, &columns)?;
+        }
+    }
+
+    out.flush()?;
+    Ok(())
+}
+
+/// Read `token`'s candles from `path`, sorted by timestamp
+pub fn read_candles(path: &Path, format: DataFormat, token: &str) -> Result<Vec<Candle>> {
+    let reader = BufReader::new(File::open(path)?);
+
+    let mut candles = match format {
+        DataFormat::Csv => {
+            parse_csv(reader, token).map_err(|e| format!("{}: {}", path.display(), e))?
+        }
+        DataFormat::Jsonl => {
+            let mut candles = Vec::new();
+            for (i, line) in reader.lines().enumerate() {
+                let line = line?;
+                if line.trim().is_empty() {
+                    continue;
+                }
+                let mut candle: Candle = serde_json::from_str(&line)
+                    .map_err(|e| format!("{} line {}: {}", path.display(), i + 1, e))?;
+                candle.token = token.to_string();
+                candles.push(candle);
+            }
+            candles
+        }
+        DataFormat::Columnar => {
+            let columns: CandleColumns = serde_json::from_reader(reader)?;
+            from_columns(columns, token).map_err(|e| format!("{}: {}", path.display(), e))?
+        }
+    };
+
+    candles.sort_by_key(|c| c.timestamp);
+    Ok(candles)
+}
+
+fn parse_csv(reader: impl BufRead, token: &str) -> std::result::Result<Vec<Candle>, String> {
+    let mut candles = Vec::new();
+    for (i, line) in reader.lines().enumerate() {
+        let line = line.map_err(|e| e.to_string())?;
+        let line = line.trim();
+        if line.is_empty() || (i == 0 && line.starts_with("timestamp")) {
+            continue;
+        }
+
+        let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+        if fields.len() != 6 && fields.len() != 7 {
+            return Err(format!(
+                "line {}: expected 6 or 7 fields ({}), got {}",
+                i + 1,
+                CSV_HEADER,
+                fields.len()
+            ));
+        }
+        let number = |field: &str| {
+            field
+                .parse::<f64>()
+                .map_err(|_| format!("line {}: invalid number '{}'", i + 1, field))
+        };
+
+        candles.push(Candle {
+            token: token.to_string(),
+            timestamp: DateTime::parse_from_rfc3339(fields[0])
+                .map_err(|e| format!("line {}: invalid timestamp '{}': {}", i + 1, fields[0], e))?
+                .with_timezone(&Utc),
+            open: number(fields[1])?,
+            high: number(fields[2])?,
+            low: number(fields[3])?,
+            close: number(fields[4])?,
+            volume: number(fields[5])?,
+            provenance: match fields.get(6) {
+                Some(field) => field
+                    .parse()
+                    .map_err(|e| format!("line {}: {}", i + 1, e))?,
+                None => CandleProvenance::Live,
+            },
+        });
+    }
+    Ok(candles)
+}
+
+fn from_columns(columns: CandleColumns, token: &str) -> std::result::Result<Vec<Candle>, String> {
+    let len = columns.timestamp.len();
+    let lengths = [
+        columns.open.len(),
+        columns.high.len(),
+        columns.low.len(),
+        columns.close.len(),
+        columns.volume.len(),
+    ];
+    if lengths.iter().any(|l| *l != len) {
+        return Err(format!(
+            "column lengths differ (timestamp {}, open/high/low/close/volume {:?})",
+            len, lengths
+        ));
+    }
+    if !columns.provenance.is_empty() && columns.provenance.len() != len {
+        return Err(format!(
+            "column lengths differ (timestamp {}, provenance {})",
+            len,
+            columns.provenance.len()
+        ));
+    }
+
+    (0..len)
+        .map(|i| {
+            let timestamp = DateTime::from_timestamp(columns.timestamp[i], 0)
+                .ok_or_else(|| format!("invalid timestamp {}", columns.timestamp[i]))?;
+            Ok(Candle {
+                token: token.to_string(),
+                timestamp,
+                open: columns.open[i],
+                high: columns.high[i],
+                low: columns.low[i],
+                close: columns.close[i],
+                volume: columns.volume[i],
+                provenance: columns.provenance.get(i).copied().unwrap_or_default(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+
+    fn candles(token: &str, count: usize) -> Vec<Candle> {
+        let start = Utc.with_ymd_and_hms(2024, 6, 1, 0, 0, 0).unwrap();
+        (0..count)
+            .map(|i| Candle {
+                token: token.to_string(),
+                timestamp: start + Duration::minutes(5 * i as i64),
+                open: 100.0 + i as f64,
+                high: 101.5 + i as f64,
+                low: 99.25 + i as f64,
+                close: 100.125 + i as f64,
+                volume: 12_345.678 * (i + 1) as f64,
+                provenance: match i % 3 {
+                    0 => CandleProvenance::Live,
+                    1 => CandleProvenance::Backfilled,
+                    _ => CandleProvenance::Interpolated,
+                },
+            })
+            .collect()
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("cryptobot-data-{}-{}", name, uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_every_format_round_trips() {
+        let dir = temp_dir("roundtrip");
+        let original = candles("SOL", 20);
+
+        for format in DataFormat::ALL {
+            let path = candle_path(&dir, "SOL", format);
+            write_candles(&path, format, &original).unwrap();
+            let loaded = read_candles(&path, format, "SOL").unwrap();
+
+            assert_eq!(loaded.len(), original.len(), "{}", format);
+            for (a, b) in original.iter().zip(&loaded) {
+                assert_eq!(a.token, b.token);
+                assert_eq!(a.timestamp, b.timestamp, "{}", format);
+                assert_eq!(a.provenance, b.provenance, "{}", format);
+                // serde_json may round the last bit when parsing floats
+                let fields = |c: &Candle| [c.open, c.high, c.low, c.close, c.volume];
+                for (x, y) in fields(a).into_iter().zip(fields(b)) {
+                    assert!(
+                        (x - y).abs() <= x.abs() * 1e-12,
+                        "{}: {} vs {}",
+                        format,
+                        x,
+                        y
+                    );
+                }
+            }
+        }
+
+        let files = list_candle_files(&dir).unwrap();
+        assert_eq!(files.len(), 3);
+        assert!(files.iter().all(|f| f.token == "SOL"));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_parse_file_name() {
+        assert_eq!(
+            DataFormat::parse_file_name(Path::new("data/JUP.columns.json")),
+            Some(("JUP".to_string(), DataFormat::Columnar))
+        );
+        assert_eq!(
+            DataFormat::parse_file_name(Path::new("Bonk.jsonl")),
+            Some(("Bonk".to_string(), DataFormat::Jsonl))
+        );
+        assert_eq!(DataFormat::parse_file_name(Path::new("notes.txt")), None);
+        assert_eq!(DataFormat::parse_file_name(Path::new(".csv")), None);
+    }
+
+    #[test]
+    fn test_filter_range_is_inclusive() {
+        let all = candles("SOL", 10);
+        let (from, to) = (all[2].timestamp, all[5].timestamp);
+        let kept = filter_range(all, Some(from), Some(to));
+        assert_eq!(kept.len(), 4);
+        assert_eq!(kept[0].timestamp, from);
+        assert_eq!(kept[3].timestamp, to);
+    }
+
+    #[test]
+    fn test_csv_errors_name_the_line() {
+        let csv = format!(
+            "{}\n2024-06-01T00:00:00Z,1,1,1,1,1\n2024-06-01T00:05:00Z,1,x,1,1,1\n",
+            CSV_HEADER
+        );
+        let err = parse_csv(csv.as_bytes(), "SOL").unwrap_err();
+        assert!(err.contains("line 3"), "{}", err);
+    }
+
+    #[test]
+    fn test_files_without_provenance_read_as_live() {
+        let csv = "timestamp,open,high,low,close,volume\n2024-06-01T00:00:00Z,1,1,1,1,1\n";
+        let parsed = parse_csv(csv.as_bytes(), "SOL").unwrap();
+        assert_eq!(parsed[0].provenance, CandleProvenance::Live);
+
+        let columns: CandleColumns = serde_json::from_str(
+            r#"{"token":"SOL","timestamp":[1717200000],"open":[1.0],"high":[1.0],"low":[1.0],"close":[1.0],"volume":[0.0]}"#,
+        )
+        .unwrap();
+        let parsed = from_columns(columns, "SOL").unwrap();
+        assert_eq!(parsed[0].provenance, CandleProvenance::Live);
+
+        let bad = "2024-06-01T00:00:00Z,1,1,1,1,1,made_up\n";
+        assert!(parse_csv(bad.as_bytes(), "SOL").is_err());
+    }
+
+    #[test]
+    fn test_data_format_from_str() {
+        assert_eq!("CSV".parse::<DataFormat>().unwrap(), DataFormat::Csv);
+        assert_eq!("ndjson".parse::<DataFormat>().unwrap(), DataFormat::Jsonl);
+        assert_eq!(
+            "columnar".parse::<DataFormat>().unwrap(),
+            DataFormat::Columnar
+        );
+        assert!("parquet".parse::<DataFormat>().is_err());
+    }
+}
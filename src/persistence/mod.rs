@@ -1,4 +1,9 @@
-use crate::models::Candle;
+pub mod files;
+pub mod source;
+
+use crate::backfill::BackfillCheckpoint;
+use crate::models::{Candle, CandleProvenance};
+use crate::risk::{BreakerState, HaltMode, HaltState};
 use crate::Result;
 use chrono::{DateTime, Utc};
 use redis::aio::ConnectionManager;
@@ -6,12 +11,97 @@ use redis::{AsyncCommands, Client};
 use serde::{Deserialize, Serialize};
 use tokio::time::{timeout, Duration};
 
-/// Simple snapshot for Redis storage
+pub use files::{CandleFile, DataFormat};
+pub use source::{CandleSource, CandleStore, FileCandleSource};
+
+/// Redis key holding the operator halt flag
+const HALT_KEY: &str = "trading:halt";
+
+/// Redis key holding the circuit breaker's active trip and entry pause
+const BREAKER_KEY: &str = "trading:breaker";
+
+/// Version written in the `v` field of every stored candle
+///
+/// Version 3 stores per-interval volume everywhere; live candles of earlier
+/// versions hold DexScreener's rolling 24h volume instead.
+const STORED_CANDLE_VERSION: u32 = 3;
+
+/// Full OHLCV candle as stored in Redis (format version 2 and later)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct StoredCandle {
+    v: u32,
+    open: f64,
+    high: f64,
+    low: f64,
+    close: f64,
+    volume: f64,
+    timestamp: DateTime<Utc>,
+    #[serde(default)]
+    provenance: CandleProvenance,
+}
+
+/// Price-only snapshot written before version 2 (no `v` field); loads as a
+/// flat candle
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct StoredSnapshot {
     price: f64,
     volume: f64,
     timestamp: DateTime<Utc>,
+    /// Missing in snapshots stored before provenance was tracked (read as live)
+    #[serde(default)]
+    provenance: CandleProvenance,
+}
+
+/// Any stored format, newest first
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+enum StoredRecord {
+    Candle(StoredCandle),
+    Snapshot(StoredSnapshot),
+}
+
+impl StoredRecord {
+    fn into_candle(self, token: &str) -> Candle {
+        match self {
+            Self::Candle(c) => Candle {
+                token: token.to_string(),
+                timestamp: c.timestamp,
+                open: c.open,
+                high: c.high,
+                low: c.low,
+                close: c.close,
+                volume: interval_volume(c.v, c.provenance, c.volume),
+                provenance: c.provenance,
+            },
+            Self::Snapshot(s) => Candle {
+                token: token.to_string(),
+                timestamp: s.timestamp,
+                open: s.price,
+                high: s.price,
+                low: s.price,
+                close: s.price,
+                volume: interval_volume(1, s.provenance, s.volume),
+                provenance: s.provenance,
+            },
+        }
+    }
+}
+
+/// Stored volume as per-interval volume: live records written before format
+/// version 3 hold a rolling 24h figure, so their volume is unknown (0.0)
+fn interval_volume(version: u32, provenance: CandleProvenance, volume: f64) -> f64 {
+    if version < 3 && provenance == CandleProvenance::Live {
+        0.0
+    } else {
+        volume
+    }
+}
+
+/// Outcome of migrating one token's stored candles
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MigrationStats {
+    pub migrated: usize,
+    pub already_current: usize,
 }
 
 /// Redis persistence for price candles/snapshots
@@ -48,18 +138,12 @@ impl RedisPersistence {
 
     /// Save candles to Redis
     ///
-    /// Stores in sorted set: `snapshots:{token}` with timestamp as score
+    /// Stores full OHLCV in sorted set: `snapshots:{token}` with timestamp as score
     pub async fn save_candles(&mut self, token: &str, candles: &[Candle]) -> Result<()> {
         let key = format!("snapshots:{}", token);
 
         for candle in candles {
-            let snapshot = StoredSnapshot {
-                price: candle.close,
-                volume: candle.volume,
-                timestamp: candle.timestamp,
-            };
-
-            let value = serde_json::to_string(&snapshot)?;
+            let value = encode_candle(candle)?;
             let score = candle.timestamp.timestamp() as f64;
 
             self.conn.zadd::<_, _, _, ()>(&key, value, score).await?;
@@ -87,22 +171,30 @@ impl RedisPersistence {
 
         // Get all snapshots after cutoff
         let results: Vec<String> = self.conn.zrangebyscore(&key, min_score, "+inf").await?;
+        let candles = parse_snapshots(token, results)?;
 
-        let mut candles = Vec::new();
+        tracing::info!(
+            "Loaded {} historical candles for {} from Redis",
+            candles.len(),
+            token
+        );
 
-        for json_str in results {
-            let snapshot: StoredSnapshot = serde_json::from_str(&json_str)?;
+        Ok(candles)
+    }
 
-            candles.push(Candle {
-                token: token.to_string(),
-                timestamp: snapshot.timestamp,
-                open: snapshot.price,
-                high: snapshot.price,
-                low: snapshot.price,
-                close: snapshot.price,
-                volume: snapshot.volume,
-            });
-        }
+    /// Load candles between two timestamps (inclusive; `None` = unbounded)
+    pub async fn load_candles_between(
+        &mut self,
+        token: &str,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+    ) -> Result<Vec<Candle>> {
+        let key = format!("snapshots:{}", token);
+        let min = from.map_or("-inf".to_string(), |t| t.timestamp().to_string());
+        let max = to.map_or("+inf".to_string(), |t| t.timestamp().to_string());
+
+        let results: Vec<String> = self.conn.zrangebyscore(&key, min, max).await?;
+        let candles = parse_snapshots(token, results)?;
 
         tracing::info!(
             "Loaded {} historical candles for {} from Redis",
@@ -113,6 +205,58 @@ impl RedisPersistence {
         Ok(candles)
     }
 
+    /// Tokens with stored snapshots (every `snapshots:*` key), sorted
+    pub async fn list_candle_tokens(&mut self) -> Result<Vec<String>> {
+        let mut keys: Vec<String> = Vec::new();
+        let mut iter: redis::AsyncIter<String> = self.conn.scan_match("snapshots:*").await?;
+        while let Some(key) = iter.next_item().await {
+            keys.push(key);
+        }
+        drop(iter);
+
+        let mut tokens: Vec<String> = keys
+            .into_iter()
+            .filter_map(|key| key.strip_prefix("snapshots:").map(str::to_string))
+            .collect();
+        tokens.sort();
+        tokens.dedup();
+        Ok(tokens)
+    }
+
+    /// Rewrite a token's price-only snapshots in the current OHLCV format
+    ///
+    /// Old snapshots only kept the close, so they become flat candles; their
+    /// timestamps and scores are unchanged. Safe to run repeatedly.
+    pub async fn migrate_snapshots(
+        &mut self,
+        token: &str,
+        dry_run: bool,
+    ) -> Result<MigrationStats> {
+        let key = format!("snapshots:{}", token);
+        let members: Vec<(String, f64)> = self.conn.zrange_withscores(&key, 0, -1).await?;
+
+        let mut stats = MigrationStats::default();
+        let mut pipe = redis::pipe();
+        pipe.atomic();
+        for (member, score) in members {
+            match migrate_member(token, &member)? {
+                Some(upgraded) => {
+                    pipe.zrem(&key, &member).ignore();
+                    pipe.zadd(&key, upgraded, score).ignore();
+                    stats.migrated += 1;
+                }
+                None => stats.already_current += 1,
+            }
+        }
+
+        if !dry_run && stats.migrated > 0 {
+            pipe.query_async::<()>(&mut self.conn).await?;
+            tracing::info!("Migrated {} snapshots for {}", stats.migrated, token);
+        }
+
+        Ok(stats)
+    }
+
     /// Clean up old snapshots to prevent unbounded growth
     ///
     /// Removes snapshots older than specified hours
@@ -131,6 +275,68 @@ impl RedisPersistence {
         Ok(removed)
     }
 
+    /// Persist the global halt flag (read by the trading loop every tick)
+    pub async fn save_halt_state(&mut self, state: &HaltState) -> Result<()> {
+        let value = serde_json::to_string(state)?;
+        self.conn.set::<_, _, ()>(HALT_KEY, value).await?;
+        Ok(())
+    }
+
+    /// Load the global halt flag (`Running` if it was never set)
+    pub async fn load_halt_state(&mut self) -> Result<HaltState> {
+        let value: Option<String> = self.conn.get(HALT_KEY).await?;
+        match value {
+            Some(json) => Ok(serde_json::from_str(&json)?),
+            None => Ok(HaltState::new(HaltMode::Running, None)),
+        }
+    }
+
+    /// Persist the circuit breaker state (active trip and entry pause)
+    pub async fn save_breaker_state(&mut self, state: &BreakerState) -> Result<()> {
+        let value = serde_json::to_string(state)?;
+        self.conn.set::<_, _, ()>(BREAKER_KEY, value).await?;
+        Ok(())
+    }
+
+    /// Load the circuit breaker state (nothing active if it was never saved)
+    pub async fn load_breaker_state(&mut self) -> Result<BreakerState> {
+        let value: Option<String> = self.conn.get(BREAKER_KEY).await?;
+        match value {
+            Some(json) => Ok(serde_json::from_str(&json)?),
+            None => Ok(BreakerState::default()),
+        }
+    }
+
+    /// Persist the progress of a chunked backfill (`backfill:checkpoint:{token}`)
+    pub async fn save_backfill_checkpoint(
+        &mut self,
+        checkpoint: &BackfillCheckpoint,
+    ) -> Result<()> {
+        let key = format!("backfill:checkpoint:{}", checkpoint.token);
+        let value = serde_json::to_string(checkpoint)?;
+        self.conn.set::<_, _, ()>(&key, value).await?;
+        Ok(())
+    }
+
+    /// Load the last saved backfill progress for a token, if any
+    pub async fn load_backfill_checkpoint(
+        &mut self,
+        token: &str,
+    ) -> Result<Option<BackfillCheckpoint>> {
+        let key = format!("backfill:checkpoint:{}", token);
+        let value: Option<String> = self.conn.get(&key).await?;
+        match value {
+            Some(json) => Ok(Some(serde_json::from_str(&json)?)),
+            None => Ok(None),
+        }
+    }
+
+    pub async fn clear_backfill_checkpoint(&mut self, token: &str) -> Result<()> {
+        let key = format!("backfill:checkpoint:{}", token);
+        self.conn.del::<_, ()>(&key).await?;
+        Ok(())
+    }
+
     /// Get count of stored snapshots for a token
     pub async fn count_snapshots(&mut self, token: &str) -> Result<usize> {
         let key = format!("snapshots:{}", token);
@@ -139,9 +345,111 @@ impl RedisPersistence {
     }
 }
 
+/// Serialize a candle in the current storage format
+fn encode_candle(candle: &Candle) -> Result<String> {
+    Ok(serde_json::to_string(&StoredCandle {
+        v: STORED_CANDLE_VERSION,
+        open: candle.open,
+        high: candle.high,
+        low: candle.low,
+        close: candle.close,
+        volume: candle.volume,
+        timestamp: candle.timestamp,
+        provenance: candle.provenance,
+    })?)
+}
+
+/// The member re-encoded in the current format, or `None` if it already is
+fn migrate_member(token: &str, member: &str) -> Result<Option<String>> {
+    match serde_json::from_str::<StoredRecord>(member)? {
+        StoredRecord::Candle(c) if c.v == STORED_CANDLE_VERSION => Ok(None),
+        StoredRecord::Candle(c) if c.v > STORED_CANDLE_VERSION => {
+            Err(format!("Unsupported stored candle version {} for {}", c.v, token).into())
+        }
+        record => Ok(Some(encode_candle(&record.into_candle(token))?)),
+    }
+}
+
+/// Decode stored candles of any format version (old snapshots load flat)
+fn parse_snapshots(token: &str, results: Vec<String>) -> Result<Vec<Candle>> {
+    let mut candles = Vec::with_capacity(results.len());
+
+    for json_str in results {
+        let record: StoredRecord = serde_json::from_str(&json_str)?;
+        candles.push(record.into_candle(token));
+    }
+
+    Ok(candles)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::risk::CircuitBreakerTrip;
+
+    #[test]
+    fn test_stored_candle_keeps_ohlc() {
+        let candle = Candle {
+            token: "SOL".to_string(),
+            timestamp: DateTime::from_timestamp(1717200000, 0).unwrap(),
+            open: 150.0,
+            high: 152.5,
+            low: 149.0,
+            close: 151.0,
+            volume: 1234.5,
+            provenance: CandleProvenance::Backfilled,
+        };
+
+        let stored = encode_candle(&candle).unwrap();
+        assert!(stored.contains("\"v\":3"), "{}", stored);
+        let loaded = parse_snapshots("SOL", vec![stored.clone()]).unwrap();
+        assert_eq!(loaded[0].open, 150.0);
+        assert_eq!(loaded[0].high, 152.5);
+        assert_eq!(loaded[0].low, 149.0);
+        assert_eq!(loaded[0].close, 151.0);
+        assert_eq!(loaded[0].provenance, CandleProvenance::Backfilled);
+        assert_eq!(migrate_member("SOL", &stored).unwrap(), None);
+    }
+
+    #[test]
+    fn test_old_snapshots_load_flat_and_migrate() {
+        let old = r#"{"price":150.0,"volume":10.0,"timestamp":"2024-06-01T00:00:00Z"}"#;
+
+        let loaded = parse_snapshots("SOL", vec![old.to_string()]).unwrap();
+        assert_eq!(loaded[0].open, 150.0);
+        assert_eq!(loaded[0].high, 150.0);
+        assert_eq!(loaded[0].close, 150.0);
+        assert_eq!(loaded[0].provenance, CandleProvenance::Live);
+
+        let upgraded = migrate_member("SOL", old).unwrap().unwrap();
+        let reloaded = parse_snapshots("SOL", vec![upgraded.clone()]).unwrap();
+        assert_eq!(reloaded[0].timestamp, loaded[0].timestamp);
+        assert_eq!(reloaded[0].low, 150.0);
+        // The old live volume was DexScreener's 24h figure, not per-interval
+        assert_eq!(reloaded[0].volume, 0.0);
+        assert_eq!(migrate_member("SOL", &upgraded).unwrap(), None);
+
+        let future = r#"{"v":9,"open":1.0,"high":1.0,"low":1.0,"close":1.0,"volume":0.0,"timestamp":"2024-06-01T00:00:00Z"}"#;
+        assert!(migrate_member("SOL", future).is_err());
+    }
+
+    #[test]
+    fn test_v2_live_volume_loads_as_unknown() {
+        let live = r#"{"v":2,"open":1.0,"high":1.0,"low":1.0,"close":1.0,"volume":5000000.0,"timestamp":"2024-06-01T00:00:00Z","provenance":"live"}"#;
+        let backfilled = r#"{"v":2,"open":1.0,"high":1.0,"low":1.0,"close":1.0,"volume":1200.0,"timestamp":"2024-06-01T00:05:00Z","provenance":"backfilled"}"#;
+
+        let loaded =
+            parse_snapshots("SOL", vec![live.to_string(), backfilled.to_string()]).unwrap();
+        assert_eq!(loaded[0].volume, 0.0);
+        assert_eq!(loaded[1].volume, 1200.0);
+
+        let upgraded = migrate_member("SOL", live).unwrap().unwrap();
+        assert!(upgraded.contains("\"v\":3"), "{}", upgraded);
+        assert_eq!(
+            parse_snapshots("SOL", vec![upgraded]).unwrap()[0].volume,
+            0.0
+        );
+    }
 
     fn create_test_candle(token: &str, hours_ago: i64, price: f64) -> Candle {
         Candle {
@@ -152,6 +460,7 @@ mod tests {
             low: price,
             close: price,
             volume: price * 1000.0,
+            provenance: CandleProvenance::Live,
         }
     }
 
@@ -255,6 +564,48 @@ mod tests {
         let _ = persistence.cleanup_old("TEST_FILTER", 0).await;
     }
 
+    #[tokio::test]
+    #[ignore] // Requires Redis running
+    async fn test_load_between_and_list_tokens() {
+        let mut persistence = RedisPersistence::new("redis://127.0.0.1:6379")
+            .await
+            .expect("Failed to connect to Redis");
+
+        let _ = persistence.cleanup_old("TEST_RANGE", 0).await;
+
+        let candles = vec![
+            create_test_candle("TEST_RANGE", 48, 100.0),
+            create_test_candle("TEST_RANGE", 12, 101.0),
+            create_test_candle("TEST_RANGE", 1, 102.0),
+        ];
+        persistence
+            .save_candles("TEST_RANGE", &candles)
+            .await
+            .unwrap();
+
+        let loaded = persistence
+            .load_candles_between(
+                "TEST_RANGE",
+                Some(Utc::now() - chrono::Duration::hours(24)),
+                Some(Utc::now() - chrono::Duration::hours(6)),
+            )
+            .await
+            .unwrap();
+        assert_eq!(loaded.len(), 1);
+        assert_eq!(loaded[0].close, 101.0);
+
+        let all = persistence
+            .load_candles_between("TEST_RANGE", None, None)
+            .await
+            .unwrap();
+        assert_eq!(all.len(), 3);
+
+        let tokens = persistence.list_candle_tokens().await.unwrap();
+        assert!(tokens.contains(&"TEST_RANGE".to_string()));
+
+        let _ = persistence.cleanup_old("TEST_RANGE", 0).await;
+    }
+
     #[tokio::test]
     #[ignore] // Requires Redis running
     async fn test_cleanup_old_data() {
@@ -338,4 +689,138 @@ mod tests {
             .unwrap();
         assert_eq!(count, 0);
     }
+
+    #[tokio::test]
+    #[ignore] // Requires Redis running
+    async fn test_halt_state_round_trip() {
+        let mut persistence = RedisPersistence::new("redis://127.0.0.1:6379")
+            .await
+            .expect("Failed to connect to Redis");
+
+        // Restore whatever an operator had set afterwards
+        let original = persistence.load_halt_state().await.unwrap();
+
+        let state = HaltState::new(HaltMode::NoNewEntries, Some("test".to_string()));
+        persistence.save_halt_state(&state).await.unwrap();
+        assert_eq!(persistence.load_halt_state().await.unwrap(), state);
+
+        persistence.save_halt_state(&original).await.unwrap();
+    }
+
+    #[tokio::test]
+    #[ignore] // Requires Redis running
+    async fn test_breaker_state_round_trip() {
+        let mut persistence = RedisPersistence::new("redis://127.0.0.1:6379")
+            .await
+            .expect("Failed to connect to Redis");
+
+        let original = persistence.load_breaker_state().await.unwrap();
+
+        let state = BreakerState {
+            active_trip: Some(CircuitBreakerTrip::MaxDrawdown),
+            paused_until: Some(Utc::now() + chrono::Duration::hours(24)),
+        };
+        persistence.save_breaker_state(&state).await.unwrap();
+        assert_eq!(persistence.load_breaker_state().await.unwrap(), state);
+
+        persistence.save_breaker_state(&original).await.unwrap();
+    }
+
+    #[tokio::test]
+    #[ignore] // Requires Redis running
+    async fn test_backfill_checkpoint_round_trip() {
+        let mut persistence = RedisPersistence::new("redis://127.0.0.1:6379")
+            .await
+            .expect("Failed to connect to Redis");
+
+        let to = Utc::now();
+        let mut checkpoint = BackfillCheckpoint::new(
+            "TEST_CHECKPOINT",
+            to - chrono::Duration::days(3),
+            to,
+            chrono::Duration::days(1),
+        );
+        checkpoint.advance(to - chrono::Duration::days(2), 288);
+        persistence
+            .save_backfill_checkpoint(&checkpoint)
+            .await
+            .unwrap();
+
+        let loaded = persistence
+            .load_backfill_checkpoint("TEST_CHECKPOINT")
+            .await
+            .unwrap();
+        assert_eq!(loaded, Some(checkpoint));
+
+        persistence
+            .clear_backfill_checkpoint("TEST_CHECKPOINT")
+            .await
+            .unwrap();
+        assert!(persistence
+            .load_backfill_checkpoint("TEST_CHECKPOINT")
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    #[tokio::test]
+    #[ignore] // Requires Redis running
+    async fn test_migrate_snapshots() {
+        let mut persistence = RedisPersistence::new("redis://127.0.0.1:6379")
+            .await
+            .expect("Failed to connect to Redis");
+        let _ = persistence.cleanup_old("TEST_MIGRATE", 0).await;
+
+        // One snapshot in the old price-only format, one current candle
+        let old = create_test_candle("TEST_MIGRATE", 2, 100.0);
+        let legacy = serde_json::to_string(&StoredSnapshot {
+            price: old.close,
+            volume: old.volume,
+            timestamp: old.timestamp,
+            provenance: CandleProvenance::Live,
+        })
+        .unwrap();
+        persistence
+            .conn
+            .zadd::<_, _, _, ()>(
+                "snapshots:TEST_MIGRATE",
+                legacy,
+                old.timestamp.timestamp() as f64,
+            )
+            .await
+            .unwrap();
+        let mut current = create_test_candle("TEST_MIGRATE", 1, 101.0);
+        current.high = 103.0;
+        persistence
+            .save_candles("TEST_MIGRATE", &[current])
+            .await
+            .unwrap();
+
+        let dry = persistence
+            .migrate_snapshots("TEST_MIGRATE", true)
+            .await
+            .unwrap();
+        assert_eq!(dry.migrated, 1);
+        let stats = persistence
+            .migrate_snapshots("TEST_MIGRATE", false)
+            .await
+            .unwrap();
+        assert_eq!(stats.migrated, 1);
+        assert_eq!(stats.already_current, 1);
+        assert_eq!(
+            persistence
+                .migrate_snapshots("TEST_MIGRATE", false)
+                .await
+                .unwrap()
+                .migrated,
+            0
+        );
+
+        let loaded = persistence.load_candles("TEST_MIGRATE", 24).await.unwrap();
+        assert_eq!(loaded.len(), 2);
+        assert_eq!(loaded[0].close, 100.0);
+        assert_eq!(loaded[1].high, 103.0);
+
+        let _ = persistence.cleanup_old("TEST_MIGRATE", 0).await;
+    }
 }
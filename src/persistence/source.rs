@@ -0,0 +1,238 @@
+use crate::models::Candle;
+use crate::persistence::files::{self, CandleFile};
+use crate::persistence::RedisPersistence;
+use crate::Result;
+use chrono::{DateTime, Utc};
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+
+/// Historical candles by token: Redis or a directory of exported files
+pub trait CandleSource {
+    /// Tokens with stored candles, sorted
+    fn list_tokens(&mut self) -> impl Future<Output = Result<Vec<String>>> + Send;
+
+    /// Candles between two timestamps (inclusive; `None` = unbounded), oldest
+    /// first; empty for unknown tokens
+    fn load_candles_between(
+        &mut self,
+        token: &str,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+    ) -> impl Future<Output = Result<Vec<Candle>>> + Send;
+
+    /// Where the candles come from, for messages
+    fn describe(&self) -> String;
+}
+
+impl CandleSource for RedisPersistence {
+    async fn list_tokens(&mut self) -> Result<Vec<String>> {
+        self.list_candle_tokens().await
+    }
+
+    async fn load_candles_between(
+        &mut self,
+        token: &str,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+    ) -> Result<Vec<Candle>> {
+        RedisPersistence::load_candles_between(self, token, from, to).await
+    }
+
+    fn describe(&self) -> String {
+        "Redis".to_string()
+    }
+}
+
+/// Candle files written by `cryptobot data export` (`<TOKEN>.csv`, `.jsonl`
+/// or `.columns.json`), keyed by the file name's token
+#[derive(Debug, Clone)]
+pub struct FileCandleSource {
+    dir: PathBuf,
+    files: BTreeMap<String, CandleFile>,
+}
+
+impl FileCandleSource {
+    /// Index the candle files in `dir` (the first format wins if a token has several)
+    pub fn open(dir: &Path) -> Result<Self> {
+        let mut files = BTreeMap::new();
+        for file in files::list_candle_files(dir)
+            .map_err(|e| format!("Cannot read data directory {}: {}", dir.display(), e))?
+        {
+            files.entry(file.token.clone()).or_insert(file);
+        }
+
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            files,
+        })
+    }
+}
+
+impl CandleSource for FileCandleSource {
+    async fn list_tokens(&mut self) -> Result<Vec<String>> {
+        Ok(self.files.keys().cloned().collect())
+    }
+
+    async fn load_candles_between(
+        &mut self,
+        token: &str,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+    ) -> Result<Vec<Candle>> {
+        let Some(file) = self.files.get(token) else {
+            return Ok(Vec::new());
+        };
+        let candles = files::read_candles(&file.path, file.format, token)?;
+        let candles = files::filter_range(candles, from, to);
+
+        tracing::info!(
+            "Loaded {} historical candles for {} from {}",
+            candles.len(),
+            token,
+            file.path.display()
+        );
+
+        Ok(candles)
+    }
+
+    fn describe(&self) -> String {
+        format!("{}", self.dir.display())
+    }
+}
+
+/// Redis, or files when a data directory is given (picked at runtime)
+pub enum CandleStore {
+    Redis(RedisPersistence),
+    Files(FileCandleSource),
+}
+
+impl CandleStore {
+    /// Files in `data_dir` if set, otherwise Redis at `REDIS_URL`
+    pub async fn connect(data_dir: Option<&Path>) -> Result<Self> {
+        match data_dir {
+            Some(dir) => Ok(Self::Files(FileCandleSource::open(dir)?)),
+            None => {
+                let redis_url = std::env::var("REDIS_URL")
+                    .unwrap_or_else(|_| "redis://127.0.0.1:6379".to_string());
+                Ok(Self::Redis(RedisPersistence::new(&redis_url).await?))
+            }
+        }
+    }
+}
+
+impl CandleSource for CandleStore {
+    async fn list_tokens(&mut self) -> Result<Vec<String>> {
+        match self {
+            Self::Redis(redis) => redis.list_tokens().await,
+            Self::Files(files) => files.list_tokens().await,
+        }
+    }
+
+    async fn load_candles_between(
+        &mut self,
+        token: &str,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+    ) -> Result<Vec<Candle>> {
+        match self {
+            Self::Redis(redis) => CandleSource::load_candles_between(redis, token, from, to).await,
+            Self::Files(files) => files.load_candles_between(token, from, to).await,
+        }
+    }
+
+    fn describe(&self) -> String {
+        match self {
+            Self::Redis(redis) => redis.describe(),
+            Self::Files(files) => files.describe(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::CandleProvenance;
+    use crate::persistence::DataFormat;
+    use chrono::{Duration, TimeZone};
+
+    fn fixture_dir() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/candles")
+    }
+
+    #[tokio::test]
+    async fn test_fixture_candles_load_from_files() {
+        let mut source = FileCandleSource::open(&fixture_dir()).unwrap();
+        assert_eq!(source.list_tokens().await.unwrap(), vec!["JUP", "SOL"]);
+
+        let all = source
+            .load_candles_between("SOL", None, None)
+            .await
+            .unwrap();
+        assert!(all.len() >= 500);
+        assert!(all.windows(2).all(|w| w[0].timestamp < w[1].timestamp));
+        assert!(all.iter().all(|c| c.token == "SOL"));
+        // The fixtures predate provenance tracking
+        assert!(all.iter().all(|c| c.provenance == CandleProvenance::Live));
+
+        let (from, to) = (all[10].timestamp, all[19].timestamp);
+        let range = source
+            .load_candles_between("SOL", Some(from), Some(to))
+            .await
+            .unwrap();
+        assert_eq!(range.len(), 10);
+
+        assert!(source
+            .load_candles_between("BONK", None, None)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_first_format_wins_per_token() {
+        let dir = std::env::temp_dir().join(format!("cryptobot-source-{}", uuid::Uuid::new_v4()));
+        let start = Utc.with_ymd_and_hms(2024, 6, 1, 0, 0, 0).unwrap();
+        let candle = |minutes: i64| Candle {
+            token: "SOL".to_string(),
+            timestamp: start + Duration::minutes(minutes),
+            open: 1.0,
+            high: 1.0,
+            low: 1.0,
+            close: 1.0,
+            volume: 0.0,
+            provenance: CandleProvenance::Live,
+        };
+        let csv = vec![candle(0), candle(5)];
+        let jsonl = vec![candle(0)];
+        files::write_candles(
+            &files::candle_path(&dir, "SOL", DataFormat::Csv),
+            DataFormat::Csv,
+            &csv,
+        )
+        .unwrap();
+        files::write_candles(
+            &files::candle_path(&dir, "SOL", DataFormat::Jsonl),
+            DataFormat::Jsonl,
+            &jsonl,
+        )
+        .unwrap();
+
+        let mut source = CandleStore::connect(Some(&dir)).await.unwrap();
+        assert_eq!(source.list_tokens().await.unwrap(), vec!["SOL"]);
+        let loaded = source
+            .load_candles_between("SOL", None, None)
+            .await
+            .unwrap();
+        assert_eq!(loaded.len(), 2);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_missing_data_dir_is_an_error() {
+        let err = FileCandleSource::open(Path::new("/nonexistent/cryptobot-data"))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("/nonexistent/cryptobot-data"), "{}", err);
+    }
+}
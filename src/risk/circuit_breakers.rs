@@ -3,12 +3,65 @@ use serde::{Deserialize, Serialize};
 
 /// Circuit breakers to prevent catastrophic losses
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct CircuitBreakers {
     pub max_daily_loss_pct: f64,
     pub max_drawdown_pct: f64,
     pub max_consecutive_losses: u32,
     pub max_position_size_pct: f64,
     pub max_daily_trades: u32,
+    pub actions: BreakerActions,
+}
+
+/// Longest entry pause accepted from `BREAKER_PAUSE_HOURS` (one year)
+pub const MAX_PAUSE_HOURS: i64 = 24 * 365;
+
+/// De-risking actions taken when a loss-type breaker trips
+///
+/// The default takes no action (a trip only blocks new entries while it lasts).
+/// `DailyTradeLimit` trips never trigger actions.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BreakerActions {
+    /// Raise stops on open positions to this distance below the current price
+    pub tighten_stop_pct: Option<f64>,
+    /// Close every open position when `MaxDrawdown` trips
+    pub flatten_on_max_drawdown: bool,
+    /// Block new entries for this many hours after a loss-type trip
+    pub pause_hours: Option<i64>,
+}
+
+impl BreakerActions {
+    /// Only block new entries (the default)
+    pub fn none() -> Self {
+        Self::default()
+    }
+
+    /// Tighten stops to -3%, pause entries for 24h and flatten on max drawdown
+    pub fn recommended() -> Self {
+        Self {
+            tighten_stop_pct: Some(0.03),
+            flatten_on_max_drawdown: true,
+            pause_hours: Some(24),
+        }
+    }
+
+    /// Actions from `BREAKER_TIGHTEN_STOP_PCT`, `BREAKER_FLATTEN_ON_MAX_DRAWDOWN`
+    /// and `BREAKER_PAUSE_HOURS` (unset = no action; at most [`MAX_PAUSE_HOURS`])
+    pub fn from_env() -> Self {
+        let var = |name: &str| std::env::var(name).ok();
+        Self {
+            tighten_stop_pct: var("BREAKER_TIGHTEN_STOP_PCT")
+                .and_then(|v| v.parse::<f64>().ok())
+                .filter(|pct| pct.is_finite() && *pct > 0.0 && *pct < 1.0),
+            flatten_on_max_drawdown: var("BREAKER_FLATTEN_ON_MAX_DRAWDOWN")
+                .and_then(|v| v.parse::<bool>().ok())
+                .unwrap_or(false),
+            pause_hours: var("BREAKER_PAUSE_HOURS")
+                .and_then(|v| v.parse::<i64>().ok())
+                .filter(|hours| *hours > 0 && *hours <= MAX_PAUSE_HOURS),
+        }
+    }
 }
 
 impl Default for CircuitBreakers {
@@ -19,6 +72,7 @@ impl Default for CircuitBreakers {
             max_consecutive_losses: 5,   // 5 losses in a row
             max_position_size_pct: 0.05, // 5% max per position
             max_daily_trades: 10,        // Max 10 trades per day
+            actions: BreakerActions::default(),
         }
     }
 }
@@ -51,9 +105,18 @@ impl TradingState {
             self.peak_portfolio_value = new_value;
         }
     }
+
+    /// Reset daily P&L and trade count when `now` falls on a new UTC day
+    pub fn reset_daily_if_needed(&mut self, now: DateTime<Utc>) {
+        if now.date_naive() != self.last_reset.date_naive() {
+            self.daily_pnl = 0.0;
+            self.daily_trades = 0;
+            self.last_reset = now;
+        }
+    }
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum CircuitBreakerTrip {
     DailyLoss,
     MaxDrawdown,
@@ -61,6 +124,63 @@ pub enum CircuitBreakerTrip {
     DailyTradeLimit,
 }
 
+impl CircuitBreakerTrip {
+    /// Trips caused by losing money (the ones that trigger de-risking actions)
+    pub fn is_loss(&self) -> bool {
+        matches!(
+            self,
+            Self::DailyLoss | Self::MaxDrawdown | Self::ConsecutiveLosses
+        )
+    }
+}
+
+/// A recorded breaker trip with the state that caused it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CircuitBreakerEvent {
+    pub trip: CircuitBreakerTrip,
+    pub timestamp: DateTime<Utc>,
+    pub portfolio_value: f64,
+    pub peak_portfolio_value: f64,
+    pub daily_pnl: f64,
+    pub consecutive_losses: u32,
+    pub daily_trades: u32,
+}
+
+impl CircuitBreakerEvent {
+    pub fn new(trip: CircuitBreakerTrip, state: &TradingState, timestamp: DateTime<Utc>) -> Self {
+        Self {
+            trip,
+            timestamp,
+            portfolio_value: state.portfolio_value,
+            peak_portfolio_value: state.peak_portfolio_value,
+            daily_pnl: state.daily_pnl,
+            consecutive_losses: state.consecutive_losses,
+            daily_trades: state.daily_trades,
+        }
+    }
+
+    /// Drawdown from peak at the time of the trip (0.10 = 10%)
+    pub fn drawdown_pct(&self) -> f64 {
+        if self.peak_portfolio_value > 0.0 {
+            (self.peak_portfolio_value - self.portfolio_value) / self.peak_portfolio_value
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Breaker state that must survive a restart
+///
+/// Without it a restarted bot would forget an entry pause, and re-fire the
+/// actions of a trip that is still active.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BreakerState {
+    /// Trip currently in effect (actions already applied)
+    pub active_trip: Option<CircuitBreakerTrip>,
+    /// New entries blocked until this time
+    pub paused_until: Option<DateTime<Utc>>,
+}
+
 impl CircuitBreakers {
     pub fn check(&self, state: &TradingState) -> Result<(), CircuitBreakerTrip> {
         // Check daily loss
@@ -131,6 +251,21 @@ mod tests {
         assert_eq!(result, Err(CircuitBreakerTrip::ConsecutiveLosses));
     }
 
+    #[test]
+    fn test_daily_reset_on_new_day() {
+        let mut state = TradingState::new(10000.0);
+        state.daily_pnl = -600.0;
+        state.daily_trades = 10;
+
+        let same_day = state.last_reset;
+        state.reset_daily_if_needed(same_day);
+        assert_eq!(state.daily_trades, 10);
+
+        state.reset_daily_if_needed(same_day + chrono::Duration::days(1));
+        assert_eq!(state.daily_pnl, 0.0);
+        assert_eq!(state.daily_trades, 0);
+    }
+
     #[test]
     fn test_circuit_breaker_ok() {
         let breakers = CircuitBreakers::default();
@@ -0,0 +1,416 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::models::Candle;
+
+/// Limits on correlated exposure across open positions
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CorrelationLimits {
+    /// Number of recent candle returns used for correlations and VaR
+    pub period: usize,
+    /// Start shrinking new positions above this correlation with the open book
+    pub downsize_correlation: f64,
+    /// Block new positions at or above this correlation with the open book
+    pub max_correlation: f64,
+    /// Max combined exposure (fraction of portfolio) to tokens correlated above
+    /// `downsize_correlation` with each other, including the new position
+    pub max_correlated_exposure_pct: f64,
+}
+
+impl Default for CorrelationLimits {
+    fn default() -> Self {
+        Self {
+            period: 288,                       // 24 hours of 5-minute candles
+            downsize_correlation: 0.6,         // Shrink above 0.6
+            max_correlation: 0.85,             // Block above 0.85
+            max_correlated_exposure_pct: 0.15, // 15% in one correlated cluster
+        }
+    }
+}
+
+/// An open position as seen by the correlation check
+#[derive(Debug, Clone)]
+pub struct BookPosition<'a> {
+    pub token: &'a str,
+    pub value: f64,
+    pub candles: &'a [Candle],
+}
+
+/// Outcome of a correlation check for a new position
+#[derive(Debug, Clone, PartialEq)]
+pub enum CorrelationDecision {
+    /// No meaningful overlap with the open book
+    Allow,
+    /// Scale the position by `factor` (0-1)
+    Downsize { factor: f64, reason: String },
+    /// Do not open the position
+    Block { reason: String },
+}
+
+/// Simple returns keyed by the timestamp of the later candle
+pub fn returns_by_timestamp(candles: &[Candle]) -> BTreeMap<DateTime<Utc>, f64> {
+    candles
+        .windows(2)
+        .filter(|w| w[0].close > 0.0)
+        .map(|w| (w[1].timestamp, (w[1].close - w[0].close) / w[0].close))
+        .collect()
+}
+
+/// Pearson correlation of two return series, matched on timestamp
+///
+/// Uses at most the last `period` shared timestamps. Returns `None` with fewer
+/// than 3 shared samples or when either series is flat.
+pub fn calculate_correlation(a: &[Candle], b: &[Candle], period: usize) -> Option<f64> {
+    let returns_a = returns_by_timestamp(a);
+    let returns_b = returns_by_timestamp(b);
+
+    let pairs: Vec<(f64, f64)> = returns_a
+        .iter()
+        .filter_map(|(ts, ra)| returns_b.get(ts).map(|rb| (*ra, *rb)))
+        .collect();
+    let pairs = &pairs[pairs.len().saturating_sub(period)..];
+
+    if pairs.len() < 3 {
+        return None;
+    }
+
+    let n = pairs.len() as f64;
+    let mean_a = pairs.iter().map(|(x, _)| x).sum::<f64>() / n;
+    let mean_b = pairs.iter().map(|(_, y)| y).sum::<f64>() / n;
+
+    let mut cov = 0.0;
+    let mut var_a = 0.0;
+    let mut var_b = 0.0;
+    for (x, y) in pairs {
+        cov += (x - mean_a) * (y - mean_b);
+        var_a += (x - mean_a).powi(2);
+        var_b += (y - mean_b).powi(2);
+    }
+
+    if var_a <= 0.0 || var_b <= 0.0 {
+        return None;
+    }
+
+    Some(cov / (var_a.sqrt() * var_b.sqrt()))
+}
+
+impl CorrelationLimits {
+    /// Check a new position against the open book
+    ///
+    /// The book correlation is the value-weighted average of pairwise correlations
+    /// with each open position. Tokens without enough shared history are ignored.
+    pub fn check(
+        &self,
+        candidate_candles: &[Candle],
+        candidate_value: f64,
+        book: &[BookPosition],
+        portfolio_value: f64,
+    ) -> CorrelationDecision {
+        let correlations: Vec<(&BookPosition, f64)> = book
+            .iter()
+            .filter_map(|p| {
+                calculate_correlation(candidate_candles, p.candles, self.period).map(|c| (p, c))
+            })
+            .collect();
+
+        let total_value: f64 = correlations.iter().map(|(p, _)| p.value).sum();
+        if correlations.is_empty() || total_value <= 0.0 {
+            return CorrelationDecision::Allow;
+        }
+
+        let book_correlation =
+            correlations.iter().map(|(p, c)| p.value * c).sum::<f64>() / total_value;
+
+        if book_correlation >= self.max_correlation {
+            return CorrelationDecision::Block {
+                reason: format!(
+                    "Correlation with open book {:.2} >= {:.2}",
+                    book_correlation, self.max_correlation
+                ),
+            };
+        }
+
+        // Shrink linearly from 1.0 at the downsize threshold to 0.0 at the max
+        let mut factor: f64 = 1.0;
+        let mut reasons = Vec::new();
+        if book_correlation > self.downsize_correlation {
+            factor = (self.max_correlation - book_correlation)
+                / (self.max_correlation - self.downsize_correlation);
+            reasons.push(format!("book correlation {:.2}", book_correlation));
+        }
+
+        // Concentration: exposure to the cluster of tokens moving with this one
+        if portfolio_value > 0.0 && candidate_value > 0.0 {
+            let cluster_value: f64 = correlations
+                .iter()
+                .filter(|(_, c)| *c > self.downsize_correlation)
+                .map(|(p, _)| p.value)
+                .sum();
+            let room = self.max_correlated_exposure_pct * portfolio_value - cluster_value;
+
+            if room <= 0.0 {
+                return CorrelationDecision::Block {
+                    reason: format!(
+                        "Correlated exposure ${:.2} already at {:.0}% limit",
+                        cluster_value,
+                        self.max_correlated_exposure_pct * 100.0
+                    ),
+                };
+            }
+            if candidate_value * factor > room {
+                factor = room / candidate_value;
+                reasons.push(format!("correlated exposure ${:.2}", cluster_value));
+            }
+        }
+
+        if reasons.is_empty() {
+            CorrelationDecision::Allow
+        } else {
+            CorrelationDecision::Downsize {
+                factor: factor.clamp(0.0, 1.0),
+                reason: format!(
+                    "Downsized to {:.0}%: {}",
+                    factor * 100.0,
+                    reasons.join(", ")
+                ),
+            }
+        }
+    }
+}
+
+/// Portfolio-level tail risk from historical simulation
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PortfolioRisk {
+    pub confidence: f64,
+    /// Loss (USD, positive) not exceeded with `confidence` over one candle
+    pub value_at_risk: f64,
+    /// Average loss (USD, positive) in the tail beyond VaR
+    pub expected_shortfall: f64,
+    pub samples: usize,
+}
+
+impl PortfolioRisk {
+    /// Historical VaR / expected shortfall of the current book
+    ///
+    /// Replays the last `period` candle returns shared by every position against
+    /// today's position values. Returns `None` for an empty book or too little
+    /// shared history.
+    pub fn historical(book: &[BookPosition], period: usize, confidence: f64) -> Option<Self> {
+        if book.is_empty() {
+            return None;
+        }
+
+        let series: Vec<BTreeMap<DateTime<Utc>, f64>> = book
+            .iter()
+            .map(|p| returns_by_timestamp(p.candles))
+            .collect();
+
+        // Timestamps where every position has a return
+        let timestamps: Vec<&DateTime<Utc>> = series[0]
+            .keys()
+            .filter(|ts| series.iter().all(|s| s.contains_key(ts)))
+            .collect();
+        let timestamps = &timestamps[timestamps.len().saturating_sub(period)..];
+
+        if timestamps.len() < 10 {
+            return None;
+        }
+
+        // Loss (positive) of today's book replayed over each historical return
+        let mut losses: Vec<f64> = timestamps
+            .iter()
+            .map(|ts| {
+                -book
+                    .iter()
+                    .zip(&series)
+                    .map(|(p, s)| p.value * s[*ts])
+                    .sum::<f64>()
+            })
+            // Bad prices (NaN/inf closes) can't be ranked
+            .filter(|loss: &f64| loss.is_finite())
+            .collect();
+        if losses.len() < 10 {
+            return None;
+        }
+        losses.sort_by(|a, b| b.total_cmp(a));
+
+        let tail = (((1.0 - confidence) * losses.len() as f64).ceil() as usize).max(1);
+        let value_at_risk = losses[tail - 1].max(0.0);
+        let expected_shortfall = (losses[..tail].iter().sum::<f64>() / tail as f64).max(0.0);
+
+        Some(Self {
+            confidence,
+            value_at_risk,
+            expected_shortfall,
+            samples: losses.len(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::CandleProvenance;
+    use chrono::Duration;
+
+    fn candles(closes: &[f64]) -> Vec<Candle> {
+        let start = DateTime::parse_from_rfc3339("2025-01-01T00:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        closes
+            .iter()
+            .enumerate()
+            .map(|(i, &close)| Candle {
+                token: "TEST".to_string(),
+                timestamp: start + Duration::minutes(5 * i as i64),
+                open: close,
+                high: close,
+                low: close,
+                close,
+                volume: 0.0,
+                provenance: CandleProvenance::Live,
+            })
+            .collect()
+    }
+
+    fn zigzag(amplitude: f64, phase: usize, count: usize) -> Vec<f64> {
+        (0..count)
+            .map(|i| {
+                if (i + phase).is_multiple_of(2) {
+                    100.0
+                } else {
+                    100.0 * (1.0 + amplitude)
+                }
+            })
+            .collect()
+    }
+
+    fn noisy(seed: u64, count: usize) -> Vec<f64> {
+        use rand::{Rng, SeedableRng};
+        let mut rng = rand::rngs::StdRng::seed_from_u64(seed);
+        let mut price = 100.0;
+        (0..count)
+            .map(|_| {
+                price *= 1.0 + rng.gen_range(-0.02..0.02);
+                price
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_correlation_of_identical_and_opposite_moves() {
+        let a = candles(&zigzag(0.02, 0, 50));
+        let b = candles(&zigzag(0.05, 0, 50));
+        let c = candles(&zigzag(0.02, 1, 50));
+
+        assert!((calculate_correlation(&a, &b, 100).unwrap() - 1.0).abs() < 1e-9);
+        assert!(calculate_correlation(&a, &c, 100).unwrap() < -0.99);
+    }
+
+    #[test]
+    fn test_correlation_requires_shared_history() {
+        let a = candles(&zigzag(0.02, 0, 2));
+        let b = candles(&zigzag(0.02, 0, 2));
+        assert!(calculate_correlation(&a, &b, 100).is_none());
+    }
+
+    #[test]
+    fn test_blocks_highly_correlated_position() {
+        let open = candles(&zigzag(0.02, 0, 50));
+        let candidate = candles(&zigzag(0.03, 0, 50));
+        let book = vec![BookPosition {
+            token: "OPEN",
+            value: 200.0,
+            candles: &open,
+        }];
+
+        let decision = CorrelationLimits::default().check(&candidate, 300.0, &book, 10000.0);
+        assert!(matches!(decision, CorrelationDecision::Block { .. }));
+    }
+
+    #[test]
+    fn test_allows_uncorrelated_position() {
+        let open = candles(&noisy(1, 100));
+        let candidate = candles(&noisy(2, 100));
+        let book = vec![BookPosition {
+            token: "OPEN",
+            value: 200.0,
+            candles: &open,
+        }];
+
+        let decision = CorrelationLimits::default().check(&candidate, 300.0, &book, 10000.0);
+        assert_eq!(decision, CorrelationDecision::Allow);
+    }
+
+    #[test]
+    fn test_downsizes_to_fit_correlated_exposure() {
+        let open = candles(&zigzag(0.02, 0, 50));
+        let candidate = candles(&zigzag(0.03, 0, 50));
+        let book = vec![BookPosition {
+            token: "OPEN",
+            value: 1300.0,
+            candles: &open,
+        }];
+        let limits = CorrelationLimits {
+            max_correlation: 1.1, // Never block on correlation alone
+            downsize_correlation: 0.5,
+            ..Default::default()
+        };
+
+        // Correlation 1.0 alone shrinks to (1.1 - 1.0) / 0.6 = 1/6 -> $333, but the
+        // cluster limit is $1500 with $1300 already open -> only $200 of room
+        match limits.check(&candidate, 2000.0, &book, 10000.0) {
+            CorrelationDecision::Downsize { factor, reason } => {
+                assert!((factor - 0.1).abs() < 1e-9, "factor {}", factor);
+                assert!(reason.contains("correlated exposure"));
+            }
+            other => panic!("expected downsize, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_historical_var_and_expected_shortfall() {
+        let a = candles(&noisy(3, 200));
+        let b = candles(&noisy(4, 200));
+        let book = vec![
+            BookPosition {
+                token: "A",
+                value: 500.0,
+                candles: &a,
+            },
+            BookPosition {
+                token: "B",
+                value: 500.0,
+                candles: &b,
+            },
+        ];
+
+        let risk = PortfolioRisk::historical(&book, 288, 0.95).unwrap();
+        assert_eq!(risk.samples, 199);
+        assert!(risk.value_at_risk > 0.0);
+        assert!(risk.expected_shortfall >= risk.value_at_risk);
+        // Each leg moves at most 2% per candle
+        assert!(risk.expected_shortfall <= 20.0);
+
+        assert!(PortfolioRisk::historical(&[], 288, 0.95).is_none());
+    }
+
+    #[test]
+    fn test_historical_skips_non_finite_returns() {
+        let mut closes = noisy(5, 50);
+        closes[20] = f64::NAN;
+        let a = candles(&closes);
+        let book = vec![BookPosition {
+            token: "A",
+            value: 1000.0,
+            candles: &a,
+        }];
+
+        let risk = PortfolioRisk::historical(&book, 288, 0.95).unwrap();
+        // 49 returns, minus the ones into and out of the NaN close
+        assert_eq!(risk.samples, 47);
+        assert!(risk.value_at_risk.is_finite());
+        assert!(risk.expected_shortfall.is_finite());
+    }
+}
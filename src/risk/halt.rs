@@ -0,0 +1,104 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// Global trading halt mode, set by an operator
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HaltMode {
+    /// Normal trading
+    #[default]
+    Running,
+    /// Manage open positions (exits still run) but open nothing new
+    NoNewEntries,
+    /// Close every open position and open nothing new
+    FlattenAll,
+}
+
+impl HaltMode {
+    /// Whether new positions may be opened
+    pub fn allows_entries(&self) -> bool {
+        *self == Self::Running
+    }
+}
+
+impl fmt::Display for HaltMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Running => "running",
+            Self::NoNewEntries => "no-new-entries",
+            Self::FlattenAll => "flatten-all",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for HaltMode {
+    type Err = String;
+
+    /// Parse `resume`/`running`, `no-new-entries`/`halt` or `flatten-all`/`flatten`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().replace('_', "-").as_str() {
+            "running" | "resume" => Ok(Self::Running),
+            "no-new-entries" | "halt" => Ok(Self::NoNewEntries),
+            "flatten-all" | "flatten" => Ok(Self::FlattenAll),
+            other => Err(format!(
+                "Unknown halt mode '{}' (expected resume, no-new-entries or flatten-all)",
+                other
+            )),
+        }
+    }
+}
+
+/// Persisted halt flag (who/why/when, for the audit trail)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HaltState {
+    pub mode: HaltMode,
+    pub reason: Option<String>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl HaltState {
+    pub fn new(mode: HaltMode, reason: Option<String>) -> Self {
+        Self {
+            mode,
+            reason,
+            updated_at: Utc::now(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_halt_modes() {
+        assert_eq!("resume".parse::<HaltMode>().unwrap(), HaltMode::Running);
+        assert_eq!(
+            "no_new_entries".parse::<HaltMode>().unwrap(),
+            HaltMode::NoNewEntries
+        );
+        assert_eq!("FLATTEN".parse::<HaltMode>().unwrap(), HaltMode::FlattenAll);
+        assert!("pause".parse::<HaltMode>().is_err());
+    }
+
+    #[test]
+    fn test_display_round_trips() {
+        for mode in [
+            HaltMode::Running,
+            HaltMode::NoNewEntries,
+            HaltMode::FlattenAll,
+        ] {
+            assert_eq!(mode.to_string().parse::<HaltMode>().unwrap(), mode);
+        }
+    }
+
+    #[test]
+    fn test_only_running_allows_entries() {
+        assert!(HaltMode::Running.allows_entries());
+        assert!(!HaltMode::NoNewEntries.allows_entries());
+        assert!(!HaltMode::FlattenAll.allows_entries());
+    }
+}
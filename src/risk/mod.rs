@@ -1,4 +1,15 @@
 // Risk management module
 pub mod circuit_breakers;
+pub mod correlation;
+pub mod halt;
+pub mod sizing;
 
-pub use circuit_breakers::{CircuitBreakerTrip, CircuitBreakers, TradingState};
+pub use circuit_breakers::{
+    BreakerActions, BreakerState, CircuitBreakerEvent, CircuitBreakerTrip, CircuitBreakers,
+    TradingState,
+};
+pub use correlation::{
+    calculate_correlation, BookPosition, CorrelationDecision, CorrelationLimits, PortfolioRisk,
+};
+pub use halt::{HaltMode, HaltState};
+pub use sizing::{PositionSizing, SizingContext, TradeStats, VolatilityMeasure};
@@ -0,0 +1,480 @@
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+use crate::execution::{Position, PositionStatus};
+use crate::indicators::{calculate_atr, calculate_return_volatility};
+use crate::models::Candle;
+
+/// Position sizing method used by the executor
+///
+/// Every method is capped by `CircuitBreakers::max_position_size_pct`, so sizing
+/// can only shrink a position relative to the hard risk limit, never grow it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "method", rename_all = "snake_case")]
+pub enum PositionSizing {
+    /// Fixed fraction of portfolio per position (e.g., 0.05 = 5%)
+    FixedFractional { fraction: f64 },
+
+    /// Scale position so that its per-candle volatility contributes
+    /// `target_volatility_pct` of the portfolio (0.001 = 0.1% per candle)
+    VolatilityTarget {
+        target_volatility_pct: f64,
+        period: usize,
+        measure: VolatilityMeasure,
+    },
+
+    /// Risk a fixed fraction of the portfolio between entry and stop loss
+    FixedRisk { risk_pct: f64 },
+
+    /// Fractional Kelly based on realized win rate and payoff ratio
+    ///
+    /// Falls back to `fallback_fraction` until `min_trades` have closed.
+    Kelly {
+        kelly_fraction: f64,
+        min_trades: usize,
+        fallback_fraction: f64,
+    },
+}
+
+/// How volatility is measured for volatility-targeted sizing
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VolatilityMeasure {
+    /// Average True Range as a fraction of price
+    Atr,
+    /// Standard deviation of candle-to-candle returns
+    StdDev,
+}
+
+impl Default for PositionSizing {
+    fn default() -> Self {
+        Self::FixedFractional { fraction: 0.05 }
+    }
+}
+
+/// Realized trade statistics used for Kelly sizing
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TradeStats {
+    pub trades: usize,
+    pub win_rate: f64,     // 0-1
+    pub payoff_ratio: f64, // avg win / avg loss
+}
+
+impl TradeStats {
+    /// Compute win rate and payoff ratio from closed positions
+    pub fn from_positions(positions: &[Position]) -> Self {
+        let pnls: Vec<f64> = positions
+            .iter()
+            .filter(|p| p.status == PositionStatus::Closed)
+            .filter_map(|p| p.realized_pnl)
+            .collect();
+
+        let wins: Vec<f64> = pnls.iter().copied().filter(|p| *p > 0.0).collect();
+        let losses: Vec<f64> = pnls.iter().copied().filter(|p| *p <= 0.0).collect();
+
+        let win_rate = if pnls.is_empty() {
+            0.0
+        } else {
+            wins.len() as f64 / pnls.len() as f64
+        };
+
+        let avg_win = if wins.is_empty() {
+            0.0
+        } else {
+            wins.iter().sum::<f64>() / wins.len() as f64
+        };
+        let avg_loss = if losses.is_empty() {
+            0.0
+        } else {
+            losses.iter().map(|l| l.abs()).sum::<f64>() / losses.len() as f64
+        };
+
+        let payoff_ratio = if avg_loss > 0.0 {
+            avg_win / avg_loss
+        } else if avg_win > 0.0 {
+            f64::INFINITY
+        } else {
+            0.0
+        };
+
+        Self {
+            trades: pnls.len(),
+            win_rate,
+            payoff_ratio,
+        }
+    }
+}
+
+/// Inputs needed to size a new position
+#[derive(Debug, Clone)]
+pub struct SizingContext<'a> {
+    pub portfolio_value: f64,
+    pub price: f64,
+    pub candles: &'a [Candle],
+    pub max_position_pct: f64,
+    pub stop_loss_pct: f64,
+    pub trade_stats: TradeStats,
+}
+
+impl PositionSizing {
+    /// Fraction of portfolio to allocate (before the max position cap)
+    ///
+    /// Returns `None` when the method lacks the data it needs (e.g., not enough
+    /// candles for volatility), in which case callers use the max position size.
+    pub fn raw_fraction(&self, ctx: &SizingContext) -> Option<f64> {
+        match self {
+            Self::FixedFractional { fraction } => Some(*fraction),
+
+            Self::VolatilityTarget {
+                target_volatility_pct,
+                period,
+                measure,
+            } => {
+                let volatility = match measure {
+                    VolatilityMeasure::Atr => {
+                        calculate_atr(ctx.candles, *period).map(|atr| atr / ctx.price)
+                    }
+                    VolatilityMeasure::StdDev => {
+                        let closes: Vec<f64> = ctx.candles.iter().map(|c| c.close).collect();
+                        calculate_return_volatility(&closes, *period)
+                    }
+                }?;
+
+                if volatility <= 0.0 {
+                    // No measurable volatility - let the max position cap decide
+                    return Some(ctx.max_position_pct);
+                }
+
+                Some(target_volatility_pct / volatility)
+            }
+
+            Self::FixedRisk { risk_pct } => {
+                if ctx.stop_loss_pct <= 0.0 {
+                    return None;
+                }
+                Some(risk_pct / ctx.stop_loss_pct)
+            }
+
+            Self::Kelly {
+                kelly_fraction,
+                min_trades,
+                fallback_fraction,
+            } => {
+                let stats = ctx.trade_stats;
+                if stats.trades < *min_trades {
+                    return Some(*fallback_fraction);
+                }
+
+                // Kelly: f* = W - (1 - W) / R
+                let kelly = if stats.payoff_ratio.is_infinite() {
+                    1.0
+                } else if stats.payoff_ratio > 0.0 {
+                    stats.win_rate - (1.0 - stats.win_rate) / stats.payoff_ratio
+                } else {
+                    0.0
+                };
+
+                Some((kelly * kelly_fraction).max(0.0))
+            }
+        }
+    }
+
+    /// Position value in USD, capped at `max_position_pct` of the portfolio
+    pub fn position_value(&self, ctx: &SizingContext) -> f64 {
+        let fraction = self
+            .raw_fraction(ctx)
+            .unwrap_or(ctx.max_position_pct)
+            .clamp(0.0, ctx.max_position_pct);
+
+        ctx.portfolio_value * fraction
+    }
+
+    /// Position quantity (in tokens) for the current price
+    pub fn quantity(&self, ctx: &SizingContext) -> f64 {
+        if ctx.price <= 0.0 {
+            return 0.0;
+        }
+        self.position_value(ctx) / ctx.price
+    }
+
+    /// Check the parameters: every value must be a positive number, and
+    /// fixed and Kelly fractions are at most 1.0
+    pub fn validate(&self) -> Result<(), String> {
+        let positive = |name: &str, value: f64| {
+            if value.is_finite() && value > 0.0 {
+                Ok(())
+            } else {
+                Err(format!(
+                    "Invalid {} sizing {}: {} (expected a positive number)",
+                    self.name(),
+                    name,
+                    value
+                ))
+            }
+        };
+        let fraction = |name: &str, value: f64| {
+            positive(name, value)?;
+            if value > 1.0 {
+                return Err(format!(
+                    "Invalid {} sizing {}: {} (a fraction is at most 1.0)",
+                    self.name(),
+                    name,
+                    value
+                ));
+            }
+            Ok(())
+        };
+
+        match self {
+            Self::FixedFractional { fraction: value } => fraction("fraction", *value),
+            Self::VolatilityTarget {
+                target_volatility_pct,
+                period,
+                ..
+            } => {
+                positive("target", *target_volatility_pct)?;
+                positive("period", *period as f64)
+            }
+            Self::FixedRisk { risk_pct } => positive("risk", *risk_pct),
+            Self::Kelly {
+                kelly_fraction,
+                fallback_fraction,
+                ..
+            } => {
+                fraction("fraction", *kelly_fraction)?;
+                fraction("fallback fraction", *fallback_fraction)
+            }
+        }
+    }
+
+    /// Short human-readable name (for logs and reports)
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::FixedFractional { .. } => "fixed",
+            Self::VolatilityTarget { .. } => "volatility",
+            Self::FixedRisk { .. } => "risk",
+            Self::Kelly { .. } => "kelly",
+        }
+    }
+}
+
+impl FromStr for PositionSizing {
+    type Err = String;
+
+    /// Parse `method[:param]`, e.g. `fixed:0.05`, `volatility:0.002`, `risk:0.01`, `kelly:0.5`
+    ///
+    /// The result must pass [`PositionSizing::validate`].
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (method, param) = match s.split_once(':') {
+            Some((m, p)) => {
+                let value = p
+                    .trim()
+                    .parse::<f64>()
+                    .map_err(|_| format!("Invalid sizing parameter: {}", p))?;
+                (m.trim(), Some(value))
+            }
+            None => (s.trim(), None),
+        };
+
+        let sizing = match method.to_lowercase().as_str() {
+            "fixed" | "fixed_fractional" => Ok(Self::FixedFractional {
+                fraction: param.unwrap_or(0.05),
+            }),
+            "volatility" | "volatility_target" => Ok(Self::VolatilityTarget {
+                target_volatility_pct: param.unwrap_or(0.0005),
+                period: 14,
+                measure: VolatilityMeasure::Atr,
+            }),
+            "risk" | "fixed_risk" => Ok(Self::FixedRisk {
+                risk_pct: param.unwrap_or(0.004),
+            }),
+            "kelly" => Ok(Self::Kelly {
+                kelly_fraction: param.unwrap_or(0.5),
+                min_trades: 10,
+                fallback_fraction: 0.02,
+            }),
+            other => Err(format!(
+                "Unknown sizing method '{}' (expected fixed, volatility, risk or kelly)",
+                other
+            )),
+        }?;
+        sizing.validate()?;
+        Ok(sizing)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::CandleProvenance;
+    use chrono::{Duration, Utc};
+
+    fn ctx(candles: &[Candle]) -> SizingContext<'_> {
+        SizingContext {
+            portfolio_value: 10000.0,
+            price: 100.0,
+            candles,
+            max_position_pct: 0.05,
+            stop_loss_pct: 0.08,
+            trade_stats: TradeStats {
+                trades: 0,
+                win_rate: 0.0,
+                payoff_ratio: 0.0,
+            },
+        }
+    }
+
+    fn candles_with_range(range_pct: f64, count: usize) -> Vec<Candle> {
+        let start = Utc::now() - Duration::minutes(5 * count as i64);
+        (0..count)
+            .map(|i| Candle {
+                token: "TEST".to_string(),
+                timestamp: start + Duration::minutes(5 * i as i64),
+                open: 100.0,
+                high: 100.0 * (1.0 + range_pct / 2.0),
+                low: 100.0 * (1.0 - range_pct / 2.0),
+                close: 100.0,
+                volume: 1000.0,
+                provenance: CandleProvenance::Live,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_fixed_fractional() {
+        let sizing = PositionSizing::FixedFractional { fraction: 0.03 };
+        assert_eq!(sizing.position_value(&ctx(&[])), 300.0);
+        assert_eq!(sizing.quantity(&ctx(&[])), 3.0);
+    }
+
+    #[test]
+    fn test_fixed_fractional_capped_by_max_position() {
+        let sizing = PositionSizing::FixedFractional { fraction: 0.5 };
+        assert_eq!(sizing.position_value(&ctx(&[])), 500.0);
+    }
+
+    #[test]
+    fn test_volatility_target_shrinks_volatile_tokens() {
+        let sizing = PositionSizing::VolatilityTarget {
+            target_volatility_pct: 0.001,
+            period: 14,
+            measure: VolatilityMeasure::Atr,
+        };
+
+        // 1% ATR -> 0.001 / 0.01 = 10% (capped to 5%)
+        let calm = candles_with_range(0.01, 30);
+        assert_eq!(sizing.position_value(&ctx(&calm)), 500.0);
+
+        // 10% ATR -> 0.001 / 0.10 = 1%
+        let wild = candles_with_range(0.10, 30);
+        let value = sizing.position_value(&ctx(&wild));
+        assert!((value - 100.0).abs() < 1e-6, "got {}", value);
+    }
+
+    #[test]
+    fn test_volatility_target_without_history_uses_max() {
+        let sizing = PositionSizing::VolatilityTarget {
+            target_volatility_pct: 0.001,
+            period: 14,
+            measure: VolatilityMeasure::StdDev,
+        };
+        assert_eq!(sizing.position_value(&ctx(&[])), 500.0);
+    }
+
+    #[test]
+    fn test_fixed_risk_uses_stop_distance() {
+        // Risk 0.2% of portfolio with an 8% stop -> 2.5% position
+        let sizing = PositionSizing::FixedRisk { risk_pct: 0.002 };
+        let value = sizing.position_value(&ctx(&[]));
+        assert!((value - 250.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_kelly_falls_back_until_enough_trades() {
+        let sizing = PositionSizing::Kelly {
+            kelly_fraction: 0.5,
+            min_trades: 10,
+            fallback_fraction: 0.02,
+        };
+        assert_eq!(sizing.position_value(&ctx(&[])), 200.0);
+    }
+
+    #[test]
+    fn test_kelly_with_edge_and_without_edge() {
+        let sizing = PositionSizing::Kelly {
+            kelly_fraction: 0.5,
+            min_trades: 10,
+            fallback_fraction: 0.02,
+        };
+
+        // W = 0.55, R = 1.0 -> f* = 0.10, half Kelly = 5%
+        let mut c = ctx(&[]);
+        c.trade_stats = TradeStats {
+            trades: 20,
+            win_rate: 0.55,
+            payoff_ratio: 1.0,
+        };
+        let value = sizing.position_value(&c);
+        assert!((value - 500.0).abs() < 1e-6);
+
+        // Negative edge -> no position
+        c.trade_stats.win_rate = 0.3;
+        assert_eq!(sizing.position_value(&c), 0.0);
+    }
+
+    #[test]
+    fn test_parse_sizing() {
+        assert_eq!(
+            "fixed:0.03".parse::<PositionSizing>().unwrap(),
+            PositionSizing::FixedFractional { fraction: 0.03 }
+        );
+        assert!(matches!(
+            "kelly".parse::<PositionSizing>().unwrap(),
+            PositionSizing::Kelly { .. }
+        ));
+        assert!("martingale".parse::<PositionSizing>().is_err());
+        assert!("risk:abc".parse::<PositionSizing>().is_err());
+    }
+
+    #[test]
+    fn test_parse_sizing_rejects_bad_parameters() {
+        for bad in [
+            "fixed:NaN",
+            "fixed:inf",
+            "volatility:-inf",
+            "risk:-0.01",
+            "kelly:0",
+            "fixed:1.5",
+            "kelly:2",
+        ] {
+            assert!(bad.parse::<PositionSizing>().is_err(), "{}", bad);
+        }
+
+        assert_eq!(
+            "fixed:1".parse::<PositionSizing>().unwrap(),
+            PositionSizing::FixedFractional { fraction: 1.0 }
+        );
+    }
+
+    #[test]
+    fn test_validate_sizing() {
+        assert!(PositionSizing::default().validate().is_ok());
+        assert!(PositionSizing::FixedFractional { fraction: 0.0 }
+            .validate()
+            .is_err());
+        assert!(PositionSizing::VolatilityTarget {
+            target_volatility_pct: 0.001,
+            period: 0,
+            measure: VolatilityMeasure::Atr,
+        }
+        .validate()
+        .is_err());
+        assert!(PositionSizing::Kelly {
+            kelly_fraction: 0.5,
+            min_trades: 10,
+            fallback_fraction: f64::NAN,
+        }
+        .validate()
+        .is_err());
+    }
+}
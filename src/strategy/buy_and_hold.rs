@@ -57,6 +57,7 @@ impl Strategy for BuyAndHoldStrategy {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::models::CandleProvenance;
     use chrono::Utc;
 
     fn create_test_candles(count: usize) -> Vec<Candle> {
@@ -69,6 +70,7 @@ mod tests {
                 low: 99.0,
                 close: 100.0,
                 volume: 1000.0,
+                provenance: CandleProvenance::Live,
             })
             .collect()
     }
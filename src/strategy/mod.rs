@@ -27,4 +27,9 @@ pub trait Strategy: Send + Sync {
     fn lookback_hours(&self) -> u64 {
         24 // Default 24 hours
     }
+
+    /// Tunable parameters, recorded with backtest runs (default: none)
+    fn parameters(&self) -> serde_json::Value {
+        serde_json::Value::Null
+    }
 }
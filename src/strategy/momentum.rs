@@ -1,5 +1,8 @@
 use super::{
-    signals::{analyze_market_conditions, validate_candle_uniformity, SignalConfig},
+    signals::{
+        analyze_market_conditions, validate_candle_provenance, validate_candle_uniformity,
+        SignalConfig,
+    },
     Strategy,
 };
 use crate::models::{Candle, Signal};
@@ -85,6 +88,7 @@ impl Strategy for MomentumStrategy {
         // Validate that candles are uniformly spaced (no gaps)
         let expected_interval_secs = self.poll_interval_minutes * 60;
         validate_candle_uniformity(candles, expected_interval_secs)?;
+        validate_candle_provenance(candles, self.config.max_interpolated_fraction)?;
 
         let prices = Self::extract_prices(candles);
         let volumes = Self::extract_volumes(candles);
@@ -111,11 +115,19 @@ impl Strategy for MomentumStrategy {
     fn lookback_hours(&self) -> u64 {
         self.config.lookback_hours
     }
+
+    fn parameters(&self) -> serde_json::Value {
+        serde_json::json!({
+            "signal": self.config,
+            "poll_interval_minutes": self.poll_interval_minutes,
+        })
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::models::CandleProvenance;
     use chrono::Utc;
 
     fn create_test_candles(prices: Vec<f64>, volumes: Vec<f64>) -> Vec<Candle> {
@@ -132,6 +144,7 @@ mod tests {
                 low: price * 0.99,
                 close: price,
                 volume,
+                provenance: CandleProvenance::Live,
             })
             .collect()
     }
@@ -195,6 +208,7 @@ mod tests {
             panic_volume_multiplier: 2.0,
             panic_price_drop_pct: 8.0,
             panic_drop_window_candles: 12,
+            max_interpolated_fraction: 1.0,
         };
 
         let strategy = MomentumStrategy::new(config);
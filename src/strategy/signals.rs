@@ -1,8 +1,10 @@
 use crate::indicators::{calculate_rsi, calculate_sma};
-use crate::models::{Candle, Signal};
+use crate::models::{Candle, CandleProvenance, Signal};
+use serde::{Deserialize, Serialize};
 
 /// Configuration for signal generation
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct SignalConfig {
     pub rsi_period: usize,
     pub rsi_oversold: f64,
@@ -17,6 +19,7 @@ pub struct SignalConfig {
     pub panic_volume_multiplier: f64, // Volume spike needed (e.g., 2.0x)
     pub panic_price_drop_pct: f64, // Recent price drop % (e.g., 8%)
     pub panic_drop_window_candles: usize, // How many candles to check for drop (e.g., 12 = 1hr at 5min)
+    pub max_interpolated_fraction: f64, // No signal when more of the lookback is interpolated (1.0 = off)
 }
 
 impl Default for SignalConfig {
@@ -31,10 +34,11 @@ impl Default for SignalConfig {
             lookback_hours: 24, // Default: analyze last 24 hours
             // Panic buy defaults (conservative but effective)
             enable_panic_buy: true,
-            panic_rsi_threshold: 30.0,     // Extreme oversold
-            panic_volume_multiplier: 2.0,  // 2x volume spike
-            panic_price_drop_pct: 8.0,     // 8% drop
-            panic_drop_window_candles: 12, // 1 hour at 5min intervals
+            panic_rsi_threshold: 30.0,      // Extreme oversold
+            panic_volume_multiplier: 2.0,   // 2x volume spike
+            panic_price_drop_pct: 8.0,      // 8% drop
+            panic_drop_window_candles: 12,  // 1 hour at 5min intervals
+            max_interpolated_fraction: 1.0, // Use interpolated candles like real ones
         }
     }
 }
@@ -114,6 +118,31 @@ pub fn validate_candle_uniformity(
     Ok(())
 }
 
+/// Share of `candles` synthesized to fill gaps rather than observed
+pub fn interpolated_fraction(candles: &[Candle]) -> f64 {
+    if candles.is_empty() {
+        return 0.0;
+    }
+    let interpolated = candles
+        .iter()
+        .filter(|c| c.provenance == CandleProvenance::Interpolated)
+        .count();
+    interpolated as f64 / candles.len() as f64
+}
+
+/// Reject windows where more than `max_fraction` of the candles are interpolated
+pub fn validate_candle_provenance(candles: &[Candle], max_fraction: f64) -> anyhow::Result<()> {
+    let fraction = interpolated_fraction(candles);
+    if fraction > max_fraction {
+        anyhow::bail!(
+            "{:.0}% of candles are interpolated (max allowed {:.0}%)",
+            fraction * 100.0,
+            max_fraction * 100.0
+        );
+    }
+    Ok(())
+}
+
 /// Analyze market conditions and generate composite signal
 pub fn analyze_market_conditions(
     prices: &[f64],
@@ -287,6 +316,7 @@ mod tests {
             low: 100.0,
             close: 100.0,
             volume: 1000.0,
+            provenance: CandleProvenance::Live,
         }
     }
 
@@ -346,6 +376,23 @@ mod tests {
         assert!(result.is_ok()); // Should pass with 50% tolerance
     }
 
+    #[test]
+    fn test_interpolated_share_limit() {
+        let mut candles: Vec<Candle> = (0..4).map(|i| create_test_candle(15 - i * 5)).collect();
+        assert!(validate_candle_provenance(&candles, 0.0).is_ok());
+
+        candles[1].provenance = CandleProvenance::Interpolated;
+        assert_eq!(interpolated_fraction(&candles), 0.25);
+        assert!(validate_candle_provenance(&candles, 0.25).is_ok());
+        assert!(validate_candle_provenance(&candles, 0.2).is_err());
+        assert!(validate_candle_provenance(
+            &candles,
+            SignalConfig::default().max_interpolated_fraction
+        )
+        .is_ok());
+        assert_eq!(interpolated_fraction(&[]), 0.0);
+    }
+
     #[test]
     fn test_signal_generation_buy() {
         // Uptrend with volume spike
@@ -415,6 +462,7 @@ mod tests {
             panic_volume_multiplier: 2.0,
             panic_price_drop_pct: 8.0,
             panic_drop_window_candles: 12,
+            max_interpolated_fraction: 1.0,
         };
 
         let prices = vec![100.0; 20];